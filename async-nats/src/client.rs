@@ -15,7 +15,7 @@ use core::pin::Pin;
 use core::task::{Context, Poll};
 
 use crate::connection::State;
-use crate::subject::ToSubject;
+use crate::subject::{Subject, ToSubject};
 use crate::{PublishMessage, ServerInfo};
 
 use super::{header::HeaderMap, status::StatusCode, Command, Message, Subscriber};
@@ -26,12 +26,16 @@ use futures::{Sink, SinkExt as _, StreamExt};
 use once_cell::sync::Lazy;
 use portable_atomic::AtomicU64;
 use regex::Regex;
+#[cfg(feature = "metrics")]
+use std::collections::HashMap;
 use std::fmt::Display;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
+#[cfg(feature = "metrics")]
+use std::sync::Weak;
 use std::time::Duration;
 use thiserror::Error;
-use tokio::sync::{mpsc, oneshot};
+use tokio::sync::{mpsc, oneshot, watch};
 use tokio_util::sync::PollSender;
 use tracing::trace;
 
@@ -84,6 +88,7 @@ pub struct Client {
     request_timeout: Option<Duration>,
     max_payload: Arc<AtomicUsize>,
     connection_stats: Arc<Statistics>,
+    draining: Arc<AtomicBool>,
 }
 
 impl Sink<PublishMessage> for Client {
@@ -132,6 +137,7 @@ impl Client {
             request_timeout,
             max_payload,
             connection_stats: statistics,
+            draining: Arc::new(AtomicBool::new(false)),
         }
     }
 
@@ -370,6 +376,10 @@ impl Client {
 
     /// Sends the request with headers.
     ///
+    /// The request can be cancelled by dropping the returned future, for
+    /// example in a `tokio::select!` branch that lost, without waiting for
+    /// the timeout. A late response to it is then discarded.
+    ///
     /// # Examples
     /// ```no_run
     /// # #[tokio::main]
@@ -553,9 +563,66 @@ impl Client {
     /// # }
     /// ```
     pub async fn subscribe<S: ToSubject>(&self, subject: S) -> Result<Subscriber, SubscribeError> {
-        let subject = subject.to_subject();
+        self.subscribe_with_buffer(subject.to_subject(), self.subscription_capacity, false)
+            .await
+    }
+
+    /// Subscribes to a subject with a buffer of `capacity` messages that applies backpressure
+    /// instead of dropping messages when it fills up.
+    ///
+    /// Messages of a [Subscriber] created with [Client::subscribe] are dropped and a
+    /// [crate::Event::SlowConsumer] is emitted when the consumer falls more than
+    /// [crate::ConnectOptions::subscription_capacity] messages behind. With this method,
+    /// the client instead stops reading from the connection until the subscriber catches up,
+    /// so no message is lost and memory use stays bounded by `capacity`.
+    ///
+    /// This comes at the cost of head-of-line blocking: all subscriptions share the same
+    /// connection, so a full subscriber stalls delivery to every other subscription and to
+    /// pending requests of this client, not just its own. Flushes wait as well, as their
+    /// PONG is read after the held back messages. A subscriber that stays full for longer
+    /// than a few ping intervals gets the connection closed as stale, and the server may
+    /// disconnect the client as a slow consumer once its outbound buffer for the client
+    /// fills up. Use it for subscribers that keep up on average but see bursts, or give
+    /// them a dedicated [Client]. Fails if `capacity` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), async_nats::Error> {
+    /// use futures::StreamExt;
+    /// let client = async_nats::connect("demo.nats.io").await?;
+    /// let mut subscription = client.subscribe_with_capacity("events.>", 128).await?;
+    /// while let Some(message) = subscription.next().await {
+    ///     println!("received message: {:?}", message);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn subscribe_with_capacity<S: ToSubject>(
+        &self,
+        subject: S,
+        capacity: usize,
+    ) -> Result<Subscriber, SubscribeError> {
+        if capacity == 0 {
+            return Err(SubscribeError(Box::new(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "subscription capacity must be greater than zero",
+            ))));
+        }
+        self.subscribe_with_buffer(subject.to_subject(), capacity, true)
+            .await
+    }
+
+    async fn subscribe_with_buffer(
+        &self,
+        subject: Subject,
+        capacity: usize,
+        backpressure: bool,
+    ) -> Result<Subscriber, SubscribeError> {
         let sid = self.next_subscription_id.fetch_add(1, Ordering::Relaxed);
-        let (sender, receiver) = mpsc::channel(self.subscription_capacity);
+        let drained = Arc::new(watch::channel(false).0);
+        let (sender, receiver) = mpsc::channel(capacity);
 
         self.sender
             .send(Command::Subscribe {
@@ -563,10 +630,12 @@ impl Client {
                 subject,
                 queue_group: None,
                 sender,
+                backpressure,
+                drained: drained.clone(),
             })
             .await?;
 
-        Ok(Subscriber::new(sid, self.sender.clone(), receiver))
+        Ok(Subscriber::new(sid, self.sender.clone(), receiver, drained))
     }
 
     /// Subscribes to a subject with a queue group to receive [messages][Message].
@@ -593,6 +662,7 @@ impl Client {
         let subject = subject.to_subject();
 
         let sid = self.next_subscription_id.fetch_add(1, Ordering::Relaxed);
+        let drained = Arc::new(watch::channel(false).0);
         let (sender, receiver) = mpsc::channel(self.subscription_capacity);
 
         self.sender
@@ -601,10 +671,12 @@ impl Client {
                 subject,
                 queue_group: Some(queue_group),
                 sender,
+                backpressure: false,
+                drained: drained.clone(),
             })
             .await?;
 
-        Ok(Subscriber::new(sid, self.sender.clone(), receiver))
+        Ok(Subscriber::new(sid, self.sender.clone(), receiver, drained))
     }
 
     /// Flushes the internal buffer ensuring that all messages are sent.
@@ -631,6 +703,34 @@ impl Client {
         Ok(())
     }
 
+    /// Subscribes to the shared inbox used by [`Client::request`] and flushes, so the
+    /// first request does not have to wait for the subscription to be set up.
+    ///
+    /// Calling it again once the inbox is established only flushes.
+    /// See also [`crate::ConnectOptions::warm_request_inbox`] to do this while connecting.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), async_nats::Error> {
+    /// let client = async_nats::connect("demo.nats.io").await?;
+    /// client.warm_request_inbox().await?;
+    /// let response = client.request("service", "data".into()).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn warm_request_inbox(&self) -> Result<(), FlushError> {
+        self.sender
+            .send(Command::WarmRequestInbox {
+                inbox_prefix: self.inbox_prefix.as_ref().into(),
+            })
+            .await
+            .map_err(|err| FlushError::with_source(FlushErrorKind::SendError, err))?;
+
+        self.flush().await
+    }
+
     /// Drains all subscriptions, stops any new messages from being published, and flushes any remaining
     /// messages, then closes the connection. Once completed, any associated streams associated with the
     /// client will be closed, and further client commands will fail
@@ -657,6 +757,8 @@ impl Client {
     /// # }
     /// ```
     pub async fn drain(&self) -> Result<(), DrainError> {
+        self.draining.store(true, Ordering::Relaxed);
+
         // Drain all subscriptions
         self.sender.send(Command::Drain { sid: None }).await?;
 
@@ -664,6 +766,22 @@ impl Client {
         Ok(())
     }
 
+    /// Returns `true` once [Client::drain] has been called on this client or any of its clones.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), async_nats::Error> {
+    /// let client = async_nats::connect("demo.nats.io").await?;
+    /// client.drain().await?;
+    /// assert!(client.is_draining());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn is_draining(&self) -> bool {
+        self.draining.load(Ordering::Relaxed)
+    }
+
     /// Returns the current state of the connection.
     ///
     /// # Examples
@@ -721,6 +839,93 @@ impl Client {
     pub fn statistics(&self) -> Arc<Statistics> {
         self.connection_stats.clone()
     }
+
+    /// Renders the [Client::statistics] in the Prometheus text exposition format, ready to be
+    /// served from an application's `/metrics` endpoint.
+    ///
+    /// All metrics are counters prefixed with `nats_client_`. Messages delivered to and
+    /// dropped by each active subscription are reported with `sid` and `subject` labels.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), async_nats::Error> {
+    /// let client = async_nats::connect("demo.nats.io").await?;
+    /// print!("{}", client.prometheus_metrics());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "metrics")]
+    pub fn prometheus_metrics(&self) -> String {
+        let stats = &self.connection_stats;
+        let mut encoder = crate::metrics::Encoder::new();
+        for (name, help, counter) in [
+            (
+                "nats_client_in_bytes_total",
+                "Payload bytes received.",
+                &stats.in_bytes,
+            ),
+            (
+                "nats_client_out_bytes_total",
+                "Payload bytes sent.",
+                &stats.out_bytes,
+            ),
+            (
+                "nats_client_in_messages_total",
+                "Messages received.",
+                &stats.in_messages,
+            ),
+            (
+                "nats_client_out_messages_total",
+                "Messages sent.",
+                &stats.out_messages,
+            ),
+            (
+                "nats_client_connects_total",
+                "Connections established, including reconnects.",
+                &stats.connects,
+            ),
+        ] {
+            encoder.family(name, "counter", help);
+            encoder.sample(name, &[], counter.load(Ordering::Relaxed));
+        }
+
+        let mut subscriptions: Vec<_> = stats
+            .subscriptions
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|(sid, statistics)| Some((*sid, statistics.upgrade()?)))
+            .collect();
+        subscriptions.sort_by_key(|(sid, _)| *sid);
+        type Counter = fn(&SubscriptionStatistics) -> &AtomicU64;
+        let counters: [(&str, &str, Counter); 2] = [
+            (
+                "nats_client_subscription_delivered_messages_total",
+                "Messages delivered to a subscription.",
+                |statistics| &statistics.delivered,
+            ),
+            (
+                "nats_client_subscription_dropped_messages_total",
+                "Messages dropped because a subscription couldn't keep up.",
+                |statistics| &statistics.dropped,
+            ),
+        ];
+        for (name, help, counter) in counters {
+            encoder.family(name, "counter", help);
+            for (sid, statistics) in &subscriptions {
+                encoder.sample(
+                    name,
+                    &[
+                        ("sid", &sid.to_string()),
+                        ("subject", statistics.subject.as_str()),
+                    ],
+                    counter(statistics).load(Ordering::Relaxed),
+                );
+            }
+        }
+        encoder.finish()
+    }
 }
 
 /// Used for building customized requests.
@@ -923,4 +1128,39 @@ pub struct Statistics {
     /// Number of times connection was established.
     /// Initial connect will be counted as well, then all successful reconnects.
     pub connects: AtomicU64,
+    /// Counters of the active subscriptions by sid, kept alive by the connection handler.
+    #[cfg(feature = "metrics")]
+    pub(crate) subscriptions: std::sync::Mutex<HashMap<u64, Weak<SubscriptionStatistics>>>,
+}
+
+/// Counters of a single subscription, rendered by [Client::prometheus_metrics].
+#[cfg(feature = "metrics")]
+#[derive(Debug)]
+pub(crate) struct SubscriptionStatistics {
+    pub(crate) subject: Subject,
+    /// Number of messages handed to the subscriber.
+    pub(crate) delivered: AtomicU64,
+    /// Number of messages dropped because the subscriber couldn't keep up.
+    pub(crate) dropped: AtomicU64,
+}
+
+#[cfg(feature = "metrics")]
+impl Statistics {
+    /// Starts counting the messages of subscription `sid`, for as long as the returned
+    /// counters are alive.
+    pub(crate) fn register_subscription(
+        &self,
+        sid: u64,
+        subject: Subject,
+    ) -> Arc<SubscriptionStatistics> {
+        let statistics = Arc::new(SubscriptionStatistics {
+            subject,
+            delivered: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+        });
+        let mut subscriptions = self.subscriptions.lock().unwrap();
+        subscriptions.retain(|_, statistics| statistics.strong_count() > 0);
+        subscriptions.insert(sid, Arc::downgrade(&statistics));
+        statistics
+    }
 }
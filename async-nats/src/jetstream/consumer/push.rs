@@ -40,6 +40,25 @@ use tracing::{debug, trace};
 
 const ORDERED_IDLE_HEARTBEAT: Duration = Duration::from_secs(5);
 
+/// How long an [Ordered] consumer waits past two heartbeat intervals before
+/// treating a heartbeat as missed, as a fraction of the interval. Spreads the
+/// recreation of many consumers that stalled at the same moment.
+const ORDERED_HEARTBEAT_JITTER: f64 = 0.1;
+
+fn ordered_idle_heartbeat(config: &OrderedConfig) -> Duration {
+    if config.idle_heartbeat.is_zero() {
+        ORDERED_IDLE_HEARTBEAT
+    } else {
+        config.idle_heartbeat
+    }
+}
+
+/// Time without any message after which an [Ordered] consumer recreates itself.
+fn missed_heartbeat_window(idle_heartbeat: Duration) -> Duration {
+    let jitter = idle_heartbeat.mul_f64(rand::random::<f64>() * ORDERED_HEARTBEAT_JITTER);
+    idle_heartbeat.saturating_mul(2) + jitter
+}
+
 impl Consumer<Config> {
     /// Returns a stream of messages for Push Consumer.
     ///
@@ -403,6 +422,11 @@ pub struct OrderedConfig {
     /// The maximum number of waiting consumers.
     #[serde(default, skip_serializing_if = "is_default")]
     pub max_waiting: i64,
+    /// Interval of idle heartbeats sent by the server, 5 seconds if left at zero.
+    /// The consumer is recreated when no message or heartbeat arrives for about
+    /// twice this long.
+    #[serde(default, with = "serde_nanos", skip_serializing_if = "is_default")]
+    pub idle_heartbeat: Duration,
     #[cfg(feature = "server_2_10")]
     // Additional consumer metadata.
     #[serde(default, skip_serializing_if = "is_default")]
@@ -435,6 +459,7 @@ impl FromConsumer for OrderedConfig {
             headers_only: config.headers_only,
             deliver_policy: config.deliver_policy,
             max_waiting: config.max_waiting,
+            idle_heartbeat: config.idle_heartbeat,
             #[cfg(feature = "server_2_10")]
             metadata: config.metadata,
         })
@@ -443,6 +468,7 @@ impl FromConsumer for OrderedConfig {
 
 impl IntoConsumerConfig for OrderedConfig {
     fn into_consumer_config(self) -> super::Config {
+        let idle_heartbeat = ordered_idle_heartbeat(&self);
         jetstream::consumer::Config {
             deliver_subject: Some(self.deliver_subject),
             durable_name: None,
@@ -463,7 +489,7 @@ impl IntoConsumerConfig for OrderedConfig {
             max_ack_pending: 0,
             headers_only: self.headers_only,
             flow_control: true,
-            idle_heartbeat: ORDERED_IDLE_HEARTBEAT,
+            idle_heartbeat,
             max_batch: 0,
             max_bytes: 0,
             max_expires: Duration::default(),
@@ -544,6 +570,7 @@ impl Consumer<OrderedConfig> {
             shutdown: shutdown_rx,
             handle,
             heartbeat_sleep: None,
+            missed_heartbeats: 0,
         })
     }
 }
@@ -558,6 +585,15 @@ pub struct Ordered {
     shutdown: tokio::sync::oneshot::Receiver<ConsumerRecreateError>,
     handle: JoinHandle<()>,
     heartbeat_sleep: Option<Pin<Box<tokio::time::Sleep>>>,
+    missed_heartbeats: u64,
+}
+
+impl Ordered {
+    /// Number of times no message or heartbeat arrived in time and the
+    /// consumer had to be recreated.
+    pub fn missed_heartbeats(&self) -> u64 {
+        self.missed_heartbeats
+    }
 }
 
 impl Drop for Ordered {
@@ -571,15 +607,22 @@ impl futures::Stream for Ordered {
     type Item = Result<Message, OrderedError>;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Option<Self::Item>> {
+        let idle_heartbeat = ordered_idle_heartbeat(&self.consumer.config);
         match self
             .heartbeat_sleep
             .get_or_insert_with(|| {
-                Box::pin(tokio::time::sleep(ORDERED_IDLE_HEARTBEAT.saturating_mul(2)))
+                Box::pin(tokio::time::sleep(missed_heartbeat_window(idle_heartbeat)))
             })
             .poll_unpin(cx)
         {
             Poll::Ready(_) => {
+                debug!("missed idle heartbeats, recreating ordered consumer");
                 self.heartbeat_sleep = None;
+                self.missed_heartbeats += 1;
+                // Drop the subscription only if it is not being recreated already.
+                if self.subscriber_future.is_none() {
+                    self.subscriber = None;
+                }
                 return Poll::Ready(Some(Err(OrderedError::new(
                     OrderedErrorKind::MissingHeartbeat,
                 ))));
@@ -13,6 +13,7 @@
 //
 //! Manage operations on [Context], create/delete/update [Stream]
 
+use crate::client::FlushError;
 use crate::error::Error;
 use crate::header::{IntoHeaderName, IntoHeaderValue};
 use crate::jetstream::account::Account;
@@ -29,10 +30,12 @@ use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serde_json::{self, json};
 use std::borrow::Borrow;
+use std::collections::VecDeque;
 use std::fmt::Display;
 use std::future::IntoFuture;
 use std::pin::Pin;
 use std::str::from_utf8;
+use std::sync::{Arc, Mutex};
 use std::task::Poll;
 use std::time::Duration;
 use tokio::sync::oneshot;
@@ -50,12 +53,28 @@ use super::stream::{
 #[cfg(feature = "server_2_10")]
 use super::stream::{Compression, ConsumerCreateStrictError, ConsumerUpdateError};
 
+/// Shortest idle heartbeat interval a consumer can be created with.
+const MIN_IDLE_HEARTBEAT: Duration = Duration::from_millis(100);
+
+/// Number of acks [Context::publish_async] keeps pending before it waits for the oldest one.
+const MAX_PENDING_ACKS: usize = 4096;
+
 /// A context which can perform jetstream scoped requests.
 #[derive(Debug, Clone)]
 pub struct Context {
     pub(crate) client: Client,
     pub(crate) prefix: String,
     pub(crate) timeout: Duration,
+    /// Acks of messages sent with [Context::publish_async], shared by clones.
+    pending_acks: Arc<Mutex<PendingAcks>>,
+}
+
+/// Acks not yet collected by [Context::flush_acks].
+#[derive(Debug, Default)]
+struct PendingAcks {
+    acks: VecDeque<PublishAckFuture>,
+    /// Outcome of the acks [Context::publish_async] already waited for.
+    summary: AckSummary,
 }
 
 impl Context {
@@ -64,6 +83,7 @@ impl Context {
             client,
             prefix: "$JS.API".to_string(),
             timeout: Duration::from_secs(5),
+            pending_acks: Arc::default(),
         }
     }
 
@@ -76,6 +96,7 @@ impl Context {
             client,
             prefix: prefix.to_string(),
             timeout: Duration::from_secs(5),
+            pending_acks: Arc::default(),
         }
     }
 
@@ -84,6 +105,7 @@ impl Context {
             client,
             prefix: format!("$JS.{}.API", domain.as_ref()),
             timeout: Duration::from_secs(5),
+            pending_acks: Arc::default(),
         }
     }
 
@@ -221,6 +243,84 @@ impl Context {
         })
     }
 
+    /// Publishes a message without waiting for its acknowledgment, which is
+    /// tracked by the context instead and collected by [Context::flush_acks].
+    ///
+    /// Once more than 4096 acks are pending, this waits for the oldest one
+    /// before returning, which keeps producers that rarely flush from
+    /// running ahead of the server.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), async_nats::Error> {
+    /// let client = async_nats::connect("localhost:4222").await?;
+    /// let jetstream = async_nats::jetstream::new(client);
+    ///
+    /// for i in 0..100 {
+    ///     jetstream
+    ///         .publish_async("events", format!("event {i}").into())
+    ///         .await?;
+    /// }
+    /// let summary = jetstream
+    ///     .flush_acks(std::time::Duration::from_secs(10))
+    ///     .await?;
+    /// assert_eq!(summary.failed(), 0);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn publish_async<S: ToSubject>(
+        &self,
+        subject: S,
+        payload: Bytes,
+    ) -> Result<(), PublishError> {
+        let ack = self
+            .send_publish(subject, Publish::build().payload(payload))
+            .await?;
+        let oldest = {
+            let mut pending = self.pending_acks.lock().unwrap();
+            pending.acks.push_back(ack);
+            if pending.acks.len() > MAX_PENDING_ACKS {
+                pending.acks.pop_front()
+            } else {
+                None
+            }
+        };
+        if let Some(ack) = oldest {
+            let result = ack.await;
+            self.pending_acks.lock().unwrap().summary.record(result);
+        }
+        Ok(())
+    }
+
+    /// Flushes the connection and waits for the acknowledgments of all
+    /// messages sent with [Context::publish_async] by this context or any of
+    /// its clones so far, returning how many were acknowledged and the errors
+    /// of those that weren't.
+    ///
+    /// Acks that didn't arrive within `timeout` are reported as
+    /// [PublishErrorKind::TimedOut] errors. Once this returns, every message
+    /// counted as acked is durably stored in its stream.
+    pub async fn flush_acks(&self, timeout: Duration) -> Result<AckSummary, FlushError> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        self.client.flush().await?;
+
+        let PendingAcks { acks, mut summary } =
+            std::mem::take(&mut *self.pending_acks.lock().unwrap());
+        let results = futures::future::join_all(acks.into_iter().map(|ack| async move {
+            tokio::time::timeout_at(deadline, ack.into_future())
+                .await
+                .unwrap_or_else(|_| Err(PublishError::new(PublishErrorKind::TimedOut)))
+        }))
+        .await;
+
+        for result in results {
+            summary.record(result);
+        }
+        Ok(summary)
+    }
+
     /// Query the server for account information
     pub async fn query_account(&self) -> Result<Account, AccountError> {
         let response: Response<Account> = self.request("INFO", b"").await?;
@@ -315,6 +415,83 @@ impl Context {
         }
     }
 
+    /// Creates a [Stream] like [Context::create_stream], then waits until it
+    /// is ready to use on all of its replicas.
+    ///
+    /// In clustered setups, a freshly created stream may briefly be missing on
+    /// some peers, or lack an elected leader. This polls the stream info every
+    /// 100ms until the cluster reports a leader and enough current replicas
+    /// for the configured `num_replicas`, and fails with
+    /// [CreateStreamErrorKind::TimedOut] if that hasn't happened within
+    /// `timeout`. Streams on servers without clustering are ready right away.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), async_nats::Error> {
+    /// use async_nats::jetstream::stream::Config;
+    /// use std::time::Duration;
+    /// let client = async_nats::connect("localhost:4222").await?;
+    /// let jetstream = async_nats::jetstream::new(client);
+    ///
+    /// let stream = jetstream
+    ///     .create_stream_and_wait(
+    ///         Config {
+    ///             name: "events".to_string(),
+    ///             num_replicas: 3,
+    ///             ..Default::default()
+    ///         },
+    ///         Duration::from_secs(10),
+    ///     )
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn create_stream_and_wait<S>(
+        &self,
+        stream_config: S,
+        timeout: Duration,
+    ) -> Result<Stream<Info>, CreateStreamError>
+    where
+        Config: From<S>,
+    {
+        const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+        let deadline = tokio::time::Instant::now() + timeout;
+        let mut stream = tokio::time::timeout_at(deadline, self.create_stream(stream_config))
+            .await
+            .map_err(|err| {
+                CreateStreamError::with_source(CreateStreamErrorKind::TimedOut, err)
+            })??;
+
+        loop {
+            if is_stream_ready(&stream.info) {
+                return Ok(stream);
+            }
+            if tokio::time::Instant::now() + POLL_INTERVAL > deadline {
+                return Err(CreateStreamError::new(CreateStreamErrorKind::TimedOut));
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+
+            match self.get_stream(&stream.name).await {
+                Ok(info) => stream = info,
+                Err(err) => match err.kind() {
+                    // Not every peer may know about the stream yet.
+                    GetStreamErrorKind::JetStream(err)
+                        if err.error_code() == ErrorCode::STREAM_NOT_FOUND => {}
+                    GetStreamErrorKind::JetStream(err) => return Err(err.into()),
+                    _ => {
+                        return Err(CreateStreamError::with_source(
+                            CreateStreamErrorKind::Response,
+                            err,
+                        ))
+                    }
+                },
+            }
+        }
+    }
+
     /// Checks for [Stream] existence on the server and returns handle to it.
     /// That handle can be used to manage and use [Consumer].
     /// This variant does not fetch [Stream] info from the server.
@@ -1095,6 +1272,13 @@ impl Context {
         action: ConsumerAction,
     ) -> Result<Consumer<C>, ConsumerError> {
         let config = config.into_consumer_config();
+        if !config.idle_heartbeat.is_zero()
+            && (config.idle_heartbeat < MIN_IDLE_HEARTBEAT
+                || (!config.inactive_threshold.is_zero()
+                    && config.idle_heartbeat >= config.inactive_threshold))
+        {
+            return Err(ConsumerError::new(ConsumerErrorKind::InvalidHeartbeat));
+        }
 
         let subject = {
             let filter = if config.filter_subject.is_empty() {
@@ -1324,6 +1508,29 @@ impl Display for PublishErrorKind {
 
 pub type PublishError = Error<PublishErrorKind>;
 
+/// The outcome of [Context::flush_acks].
+#[derive(Debug, Default)]
+pub struct AckSummary {
+    /// Number of messages the server acknowledged.
+    pub acked: usize,
+    /// Errors of the messages that weren't acknowledged.
+    pub errors: Vec<PublishError>,
+}
+
+impl AckSummary {
+    /// Number of messages that weren't acknowledged.
+    pub fn failed(&self) -> usize {
+        self.errors.len()
+    }
+
+    fn record(&mut self, result: Result<PublishAck, PublishError>) {
+        match result {
+            Ok(_) => self.acked += 1,
+            Err(err) => self.errors.push(err),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct PublishAckFuture {
     timeout: Duration,
@@ -1669,6 +1876,20 @@ impl Display for CreateStreamErrorKind {
 
 pub type CreateStreamError = Error<CreateStreamErrorKind>;
 
+/// Returns true once a stream has a leader and enough current replicas.
+fn is_stream_ready(info: &Info) -> bool {
+    let Some(cluster) = info.cluster.as_ref() else {
+        return true;
+    };
+    let current = cluster
+        .replicas
+        .iter()
+        .filter(|peer| peer.current && !peer.offline)
+        .count();
+    // The leader isn't listed among the replicas.
+    cluster.leader.is_some() && current + 1 >= info.config.num_replicas.max(1)
+}
+
 impl From<super::errors::Error> for CreateStreamError {
     fn from(error: super::errors::Error) -> Self {
         CreateStreamError::new(CreateStreamErrorKind::JetStream(error))
@@ -2214,6 +2214,7 @@ pub enum ConsumerErrorKind {
     Request,
     InvalidConsumerType,
     InvalidName,
+    InvalidHeartbeat,
     JetStream(super::errors::Error),
     Other,
 }
@@ -2227,6 +2228,7 @@ impl Display for ConsumerErrorKind {
             Self::Other => write!(f, "consumer error"),
             Self::InvalidConsumerType => write!(f, "invalid consumer type"),
             Self::InvalidName => write!(f, "invalid consumer name"),
+            Self::InvalidHeartbeat => write!(f, "invalid idle heartbeat"),
         }
     }
 }
@@ -2240,6 +2242,7 @@ pub enum ConsumerCreateStrictErrorKind {
     Request,
     InvalidConsumerType,
     InvalidName,
+    InvalidHeartbeat,
     AlreadyExists,
     JetStream(super::errors::Error),
     Other,
@@ -2254,6 +2257,7 @@ impl Display for ConsumerCreateStrictErrorKind {
             Self::Other => write!(f, "consumer error"),
             Self::InvalidConsumerType => write!(f, "invalid consumer type"),
             Self::InvalidName => write!(f, "invalid consumer name"),
+            Self::InvalidHeartbeat => write!(f, "invalid idle heartbeat"),
             Self::AlreadyExists => write!(f, "consumer already exists"),
         }
     }
@@ -2268,6 +2272,7 @@ pub enum ConsumerUpdateErrorKind {
     Request,
     InvalidConsumerType,
     InvalidName,
+    InvalidHeartbeat,
     DoesNotExist,
     JetStream(super::errors::Error),
     Other,
@@ -2282,6 +2287,7 @@ impl Display for ConsumerUpdateErrorKind {
             Self::Other => write!(f, "consumer error"),
             Self::InvalidConsumerType => write!(f, "invalid consumer type"),
             Self::InvalidName => write!(f, "invalid consumer name"),
+            Self::InvalidHeartbeat => write!(f, "invalid idle heartbeat"),
             Self::DoesNotExist => write!(f, "consumer does not exist"),
         }
     }
@@ -2334,6 +2340,9 @@ impl From<ConsumerError> for ConsumerUpdateError {
             ConsumerErrorKind::InvalidName => {
                 ConsumerUpdateError::new(ConsumerUpdateErrorKind::InvalidName)
             }
+            ConsumerErrorKind::InvalidHeartbeat => {
+                ConsumerUpdateError::new(ConsumerUpdateErrorKind::InvalidHeartbeat)
+            }
             ConsumerErrorKind::Other => ConsumerUpdateError::new(ConsumerUpdateErrorKind::Other),
         }
     }
@@ -2361,6 +2370,9 @@ impl From<ConsumerError> for ConsumerCreateStrictError {
             ConsumerErrorKind::InvalidName => {
                 ConsumerCreateStrictError::new(ConsumerCreateStrictErrorKind::InvalidName)
             }
+            ConsumerErrorKind::InvalidHeartbeat => {
+                ConsumerCreateStrictError::new(ConsumerCreateStrictErrorKind::InvalidHeartbeat)
+            }
             ConsumerErrorKind::Other => {
                 ConsumerCreateStrictError::new(ConsumerCreateStrictErrorKind::Other)
             }
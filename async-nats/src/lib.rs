@@ -225,6 +225,7 @@ use serde::{Deserialize, Serialize};
 use serde_repr::{Deserialize_repr, Serialize_repr};
 use tokio::io;
 use tokio::sync::mpsc;
+use tokio::sync::watch;
 use tokio::task;
 
 pub type Error = Box<dyn std::error::Error + Send + Sync + 'static>;
@@ -233,6 +234,9 @@ const VERSION: &str = env!("CARGO_PKG_VERSION");
 const LANG: &str = "rust";
 const MAX_PENDING_PINGS: usize = 2;
 const MULTIPLEXER_SID: u64 = 0;
+/// Lowest number of pending requests before the multiplexer looks for
+/// abandoned ones.
+const MULTIPLEXER_PRUNE_THRESHOLD: usize = 64;
 
 /// A re-export of the `rustls` crate used in this crate,
 /// for use in cases where manual client configurations
@@ -262,6 +266,8 @@ pub mod error;
 pub mod header;
 pub mod jetstream;
 pub mod message;
+#[cfg(feature = "metrics")]
+mod metrics;
 #[cfg(feature = "service")]
 pub mod service;
 pub mod status;
@@ -370,17 +376,29 @@ pub(crate) enum Command {
         subject: Subject,
         queue_group: Option<String>,
         sender: mpsc::Sender<Message>,
+        backpressure: bool,
+        drained: Arc<watch::Sender<bool>>,
     },
     Unsubscribe {
         sid: u64,
         max: Option<u64>,
     },
+    /// Unsubscribes from the server but keeps the subscription around to be resumed.
+    Pause {
+        sid: u64,
+    },
+    Resume {
+        sid: u64,
+    },
     Flush {
         observer: oneshot::Sender<()>,
     },
     Drain {
         sid: Option<u64>,
     },
+    WarmRequestInbox {
+        inbox_prefix: Subject,
+    },
     Reconnect,
 }
 
@@ -414,7 +432,29 @@ struct Subscription {
     queue_group: Option<String>,
     delivered: u64,
     max: Option<u64>,
-    is_draining: bool,
+    /// Number of the PING sent after the UNSUB of a draining subscription. Once its PONG
+    /// arrives, the server sent everything it had for the subscription and it is removed.
+    drain_ping: Option<u64>,
+    /// Whether reading from the connection waits for room when `sender` is full, instead of
+    /// dropping the message.
+    backpressure: bool,
+    /// Shared with the [Subscriber], set when the subscription was drained and nothing is left
+    /// in `sender`.
+    drained: Arc<watch::Sender<bool>>,
+    is_paused: bool,
+    #[cfg(feature = "metrics")]
+    statistics: Arc<client::SubscriptionStatistics>,
+}
+
+type ReservePermit = Pin<
+    Box<dyn Future<Output = Result<mpsc::OwnedPermit<Message>, mpsc::error::SendError<()>>> + Send>,
+>;
+
+/// A message held back until its subscription has room for it.
+struct Stalled {
+    sid: u64,
+    message: Message,
+    permit: ReservePermit,
 }
 
 #[derive(Debug)]
@@ -422,6 +462,9 @@ struct Multiplexer {
     subject: Subject,
     prefix: Subject,
     senders: HashMap<String, oneshot::Sender<Message>>,
+    /// Number of pending requests at which senders of requests that were
+    /// dropped or timed out are removed.
+    prune_at: usize,
 }
 
 /// A connection handler which facilitates communication from channels to a single shared connection.
@@ -435,7 +478,12 @@ pub(crate) struct ConnectionHandler {
     ping_interval: Interval,
     should_reconnect: bool,
     flush_observers: Vec<oneshot::Sender<()>>,
-    is_draining: bool,
+    /// Number of the PING sent when draining the whole client, whose PONG completes the drain.
+    drain_ping: Option<u64>,
+    pings_sent: u64,
+    pongs_received: u64,
+    /// Set while a subscription with backpressure is full, which pauses reading.
+    stalled: Option<Stalled>,
 }
 
 impl ConnectionHandler {
@@ -458,7 +506,10 @@ impl ConnectionHandler {
             ping_interval,
             should_reconnect: false,
             flush_observers: Vec::new(),
-            is_draining: false,
+            drain_ping: None,
+            pings_sent: 0,
+            pongs_received: 0,
+            stalled: None,
         }
     }
 
@@ -490,6 +541,7 @@ impl ConnectionHandler {
 
                     Poll::Ready(ExitReason::Disconnected(None))
                 } else {
+                    self.handler.pings_sent += 1;
                     self.handler.connection.enqueue_write_op(&ClientOp::Ping);
 
                     Poll::Pending
@@ -523,7 +575,22 @@ impl ConnectionHandler {
                     }
                 }
 
-                loop {
+                // A subscription with backpressure is full. Reading is paused until it has room
+                // again, which keeps messages in the socket rather than in memory.
+                if let Some(stalled) = self.handler.stalled.as_mut() {
+                    if let Poll::Ready(result) = stalled.permit.as_mut().poll(cx) {
+                        let Stalled { sid, message, .. } = self.handler.stalled.take().unwrap();
+                        match result {
+                            Ok(permit) => {
+                                permit.send(message);
+                                self.handler.delivered(sid);
+                            }
+                            Err(_) => self.handler.remove_closed(sid),
+                        }
+                    }
+                }
+
+                while self.handler.stalled.is_none() {
                     match self.handler.connection.poll_read_op(cx) {
                         Poll::Pending => break,
                         Poll::Ready(Ok(Some(server_op))) => {
@@ -538,17 +605,16 @@ impl ConnectionHandler {
                     }
                 }
 
-                // Before handling any commands, drop any subscriptions which are draining
-                // Note: safe to assume subscription drain has completed at this point, as we would have flushed
-                // all outgoing UNSUB messages in the previous call to this fn, and we would have processed and
-                // delivered any remaining messages to the subscription in the loop above.
-                self.handler.subscriptions.retain(|_, s| !s.is_draining);
-
-                if self.handler.is_draining {
-                    // The entire connection is draining. This means we flushed outgoing messages in the previous
-                    // call to this fn, we handled any remaining messages from the server in the loop above, and
-                    // all subs were drained, so drain is complete and we should exit instead of processing any
-                    // further messages
+                if self.handler.stalled.is_none()
+                    && self
+                        .handler
+                        .drain_ping
+                        .is_some_and(|ping| ping <= self.handler.pongs_received)
+                {
+                    // The entire connection is draining. The server answered the PING sent after all
+                    // UNSUBs, so it processed everything we published, all remaining messages were
+                    // delivered in the loop above and all subs were drained. Drain is complete and we
+                    // should exit instead of processing any further messages.
                     return Poll::Ready(ExitReason::Closed);
                 }
 
@@ -667,6 +733,47 @@ impl ConnectionHandler {
         }
     }
 
+    /// Counts a message handed to a subscription, removing the subscription once it
+    /// reached its `max`.
+    fn delivered(&mut self, sid: u64) {
+        if let Some(subscription) = self.subscriptions.get_mut(&sid) {
+            #[cfg(feature = "metrics")]
+            subscription.statistics.delivered.add(1, Ordering::Relaxed);
+            subscription.delivered += 1;
+            // if this `Subscription` has set `max` value, check if it
+            // was reached. If yes, remove the `Subscription` and in
+            // the result, `drop` the `sender` channel.
+            if let Some(max) = subscription.max {
+                if subscription.delivered.ge(&max) {
+                    self.subscriptions.remove(&sid);
+                }
+            }
+        }
+    }
+
+    /// Removes draining subscriptions once the PONG for the PING sent after their UNSUB arrived.
+    fn remove_drained(&mut self) {
+        let pongs_received = self.pongs_received;
+        self.subscriptions.retain(|_, subscription| {
+            let keep = subscription
+                .drain_ping
+                .is_none_or(|ping| ping > pongs_received);
+            // Otherwise the subscriber sees the channel close once it yielded what is left.
+            if !keep && subscription.sender.capacity() == subscription.sender.max_capacity() {
+                subscription.drained.send_replace(true);
+            }
+            keep
+        });
+    }
+
+    /// Removes a subscription whose channel was dropped and unsubscribes from the server.
+    fn remove_closed(&mut self, sid: u64) {
+        if self.subscriptions.remove(&sid).is_some() {
+            self.connection
+                .enqueue_write_op(&ClientOp::Unsubscribe { sid, max: None });
+        }
+    }
+
     fn handle_server_op(&mut self, server_op: ServerOp) {
         self.ping_interval.reset();
 
@@ -677,6 +784,8 @@ impl ConnectionHandler {
             ServerOp::Pong => {
                 debug!("received PONG");
                 self.pending_pings = self.pending_pings.saturating_sub(1);
+                self.pongs_received += 1;
+                self.remove_drained();
             }
             ServerOp::Error(error) => {
                 self.connector
@@ -713,28 +822,25 @@ impl ConnectionHandler {
                     // if the channel for subscription was dropped, remove the
                     // subscription from the map and unsubscribe.
                     match subscription.sender.try_send(message) {
-                        Ok(_) => {
-                            subscription.delivered += 1;
-                            // if this `Subscription` has set `max` value, check if it
-                            // was reached. If yes, remove the `Subscription` and in
-                            // the result, `drop` the `sender` channel.
-                            if let Some(max) = subscription.max {
-                                if subscription.delivered.ge(&max) {
-                                    self.subscriptions.remove(&sid);
-                                }
-                            }
+                        Ok(_) => self.delivered(sid),
+                        Err(mpsc::error::TrySendError::Full(message))
+                            if subscription.backpressure =>
+                        {
+                            self.stalled = Some(Stalled {
+                                sid,
+                                message,
+                                permit: Box::pin(subscription.sender.clone().reserve_owned()),
+                            });
                         }
                         Err(mpsc::error::TrySendError::Full(_)) => {
+                            #[cfg(feature = "metrics")]
+                            subscription.statistics.dropped.add(1, Ordering::Relaxed);
                             self.connector
                                 .events_tx
                                 .try_send(Event::SlowConsumer(sid))
                                 .ok();
                         }
-                        Err(mpsc::error::TrySendError::Closed(_)) => {
-                            self.subscriptions.remove(&sid);
-                            self.connection
-                                .enqueue_write_op(&ClientOp::Unsubscribe { sid, max: None });
-                        }
+                        Err(mpsc::error::TrySendError::Closed(_)) => self.remove_closed(sid),
                     }
                 } else if sid == MULTIPLEXER_SID {
                     if let Some(multiplexer) = self.multiplexer.as_mut() {
@@ -794,42 +900,79 @@ impl ConnectionHandler {
                         .enqueue_write_op(&ClientOp::Unsubscribe { sid, max });
                 }
             }
+            Command::Pause { sid } => {
+                if let Some(subscription) = self.subscriptions.get_mut(&sid) {
+                    if !mem::replace(&mut subscription.is_paused, true) {
+                        self.connection
+                            .enqueue_write_op(&ClientOp::Unsubscribe { sid, max: None });
+                    }
+                }
+            }
+            Command::Resume { sid } => {
+                if let Some(subscription) = self.subscriptions.get_mut(&sid) {
+                    if mem::replace(&mut subscription.is_paused, false) {
+                        self.connection.enqueue_write_op(&ClientOp::Subscribe {
+                            sid,
+                            subject: subscription.subject.to_owned(),
+                            queue_group: subscription.queue_group.to_owned(),
+                        });
+                    }
+                }
+            }
             Command::Flush { observer } => {
                 self.flush_observers.push(observer);
             }
             Command::Drain { sid } => {
+                // The server answers the PING only after it handled the UNSUBs before it and
+                // sent any messages still in flight, so its PONG marks the end of the drain.
+                let ping = self.pings_sent + 1;
                 let mut drain_sub = |sid: u64, sub: &mut Subscription| {
-                    sub.is_draining = true;
+                    sub.drain_ping = Some(ping);
                     self.connection
                         .enqueue_write_op(&ClientOp::Unsubscribe { sid, max: None });
                 };
 
                 if let Some(sid) = sid {
-                    if let Some(sub) = self.subscriptions.get_mut(&sid) {
-                        drain_sub(sid, sub);
+                    match self.subscriptions.get_mut(&sid) {
+                        Some(sub) => drain_sub(sid, sub),
+                        None => return,
                     }
                 } else {
                     // sid isn't set, so drain the whole client
                     self.connector.events_tx.try_send(Event::Draining).ok();
-                    self.is_draining = true;
+                    self.drain_ping = Some(ping);
                     for (&sid, sub) in self.subscriptions.iter_mut() {
                         drain_sub(sid, sub);
                     }
                 }
+
+                self.pending_pings += 1;
+                self.pings_sent = ping;
+                self.connection.enqueue_write_op(&ClientOp::Ping);
             }
             Command::Subscribe {
                 sid,
                 subject,
                 queue_group,
                 sender,
+                backpressure,
+                drained,
             } => {
                 let subscription = Subscription {
                     sender,
+                    drained,
                     delivered: 0,
                     max: None,
                     subject: subject.to_owned(),
                     queue_group: queue_group.to_owned(),
-                    is_draining: false,
+                    drain_ping: None,
+                    backpressure,
+                    is_paused: false,
+                    #[cfg(feature = "metrics")]
+                    statistics: self
+                        .connector
+                        .connect_stats
+                        .register_subscription(sid, subject.to_owned()),
                 };
 
                 self.subscriptions.insert(sid, subscription);
@@ -849,29 +992,19 @@ impl ConnectionHandler {
             } => {
                 let (prefix, token) = respond.rsplit_once('.').expect("malformed request subject");
 
-                let multiplexer = if let Some(multiplexer) = self.multiplexer.as_mut() {
-                    multiplexer
-                } else {
-                    let prefix = Subject::from(format!("{}.{}.", prefix, nuid::next()));
-                    let subject = Subject::from(format!("{}*", prefix));
-
-                    self.connection.enqueue_write_op(&ClientOp::Subscribe {
-                        sid: MULTIPLEXER_SID,
-                        subject: subject.clone(),
-                        queue_group: None,
-                    });
-
-                    self.multiplexer.insert(Multiplexer {
-                        subject,
-                        prefix,
-                        senders: HashMap::new(),
-                    })
-                };
                 self.connector
                     .connect_stats
                     .out_messages
                     .add(1, Ordering::Relaxed);
 
+                let multiplexer = self.multiplexer(prefix);
+                if multiplexer.senders.len() >= multiplexer.prune_at {
+                    // The receiver of a request that was cancelled by dropping its future,
+                    // or that timed out, is gone.
+                    multiplexer.senders.retain(|_, sender| !sender.is_closed());
+                    multiplexer.prune_at =
+                        (multiplexer.senders.len() * 2).max(MULTIPLEXER_PRUNE_THRESHOLD);
+                }
                 multiplexer.senders.insert(token.to_owned(), sender);
 
                 let respond: Subject = format!("{}{}", multiplexer.prefix, token).into();
@@ -918,14 +1051,44 @@ impl ConnectionHandler {
                 });
             }
 
+            Command::WarmRequestInbox { inbox_prefix } => {
+                self.multiplexer(&inbox_prefix);
+            }
+
             Command::Reconnect => {
                 self.should_reconnect = true;
             }
         }
     }
 
+    /// Returns the request multiplexer, subscribing to its wildcard inbox first
+    /// if this is the initial request.
+    fn multiplexer(&mut self, inbox_prefix: &str) -> &mut Multiplexer {
+        self.multiplexer.get_or_insert_with(|| {
+            let prefix = Subject::from(format!("{}.{}.", inbox_prefix, nuid::next()));
+            let subject = Subject::from(format!("{}*", prefix));
+
+            self.connection.enqueue_write_op(&ClientOp::Subscribe {
+                sid: MULTIPLEXER_SID,
+                subject: subject.clone(),
+                queue_group: None,
+            });
+
+            Multiplexer {
+                subject,
+                prefix,
+                senders: HashMap::new(),
+                prune_at: MULTIPLEXER_PRUNE_THRESHOLD,
+            }
+        })
+    }
+
     async fn handle_disconnect(&mut self) -> Result<(), ConnectError> {
         self.pending_pings = 0;
+        // PINGs in flight are lost along with the connection, and so are the messages of
+        // draining subscriptions, so those drains are complete.
+        self.pongs_received = self.pings_sent;
+        self.remove_drained();
         self.connector.events_tx.try_send(Event::Disconnected).ok();
         self.connector.state_tx.send(State::Disconnected).ok();
 
@@ -941,6 +1104,9 @@ impl ConnectionHandler {
             .retain(|_, subscription| !subscription.sender.is_closed());
 
         for (sid, subscription) in &self.subscriptions {
+            if subscription.is_paused {
+                continue;
+            }
             self.connection.enqueue_write_op(&ClientOp::Subscribe {
                 sid: *sid,
                 subject: subscription.subject.to_owned(),
@@ -1063,6 +1229,23 @@ pub async fn connect_with_options<A: ToServerAddrs>(
         connection_handler.process(&mut receiver).await
     });
 
+    if options.warm_request_inbox {
+        if options.retry_on_initial_connect {
+            // The connection is not established yet, so warm up in the background.
+            let client = client.clone();
+            task::spawn(async move {
+                if let Err(err) = client.warm_request_inbox().await {
+                    debug!(?err, "failed to warm up request inbox");
+                }
+            });
+        } else {
+            client
+                .warm_request_inbox()
+                .await
+                .map_err(|err| ConnectError::with_source(ConnectErrorKind::Io, err))?;
+        }
+    }
+
     Ok(client)
 }
 
@@ -1226,6 +1409,14 @@ pub struct Subscriber {
     sid: u64,
     receiver: mpsc::Receiver<Message>,
     sender: mpsc::Sender<Command>,
+    /// Number of messages yielded so far.
+    yielded: u64,
+    /// Set by [Subscriber::unsubscribe_after], ends the stream once that many messages were
+    /// yielded, even if more were received before the server got the `UNSUB`.
+    max: Option<u64>,
+    /// Set once the subscription was drained and no message is left in it, which resolves the
+    /// futures returned by [Subscriber::drain].
+    drained: Arc<watch::Sender<bool>>,
 }
 
 impl Subscriber {
@@ -1233,11 +1424,15 @@ impl Subscriber {
         sid: u64,
         sender: mpsc::Sender<Command>,
         receiver: mpsc::Receiver<Message>,
+        drained: Arc<watch::Sender<bool>>,
     ) -> Subscriber {
         Subscriber {
             sid,
             sender,
             receiver,
+            yielded: 0,
+            max: None,
+            drained,
         }
     }
 
@@ -1270,6 +1465,10 @@ impl Subscriber {
     /// This is the total number of messages received by this subscription in it's whole
     /// lifespan. If it already reached or surpassed the passed value, it will immediately stop.
     ///
+    /// The stream ends after yielding that many messages, which makes it fit for requests
+    /// expecting a known number of replies: the subscription is removed without having to
+    /// unsubscribe explicitly.
+    ///
     /// # Examples
     /// ```
     /// # use futures::StreamExt;
@@ -1298,12 +1497,19 @@ impl Subscriber {
                 max: Some(unsub_after),
             })
             .await?;
+        self.max = Some(unsub_after);
         Ok(())
     }
 
     /// Unsubscribes immediately but leaves the subscription open to allow any in-flight messages
     /// on the subscription to be delivered. The stream will be closed after any remaining messages
-    /// are delivered
+    /// are delivered.
+    ///
+    /// The returned future resolves only once the subscription is closed and no message is left
+    /// in it, that is once the stream ended. It does not borrow the [Subscriber], so keep
+    /// consuming the stream while it is pending, for example with [tokio::join!], or it never
+    /// resolves while messages are left. If the [Subscriber] is dropped before, it resolves right
+    /// away.
     ///
     /// # Examples
     /// ```no_run
@@ -1324,23 +1530,36 @@ impl Subscriber {
     /// });
     ///
     /// client.flush().await?;
-    /// subscriber.drain().await?;
-    ///
-    /// while let Some(message) = subscriber.next().await {
-    ///     println!("message received: {:?}", message);
-    /// }
+    /// let drained = subscriber.drain();
+    /// let consume = async {
+    ///     while let Some(message) = subscriber.next().await {
+    ///         println!("message received: {:?}", message);
+    ///     }
+    /// };
+    /// let (drained, ()) = tokio::join!(drained, consume);
+    /// drained?;
     /// println!("no more messages, unsubscribed");
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn drain(&mut self) -> Result<(), UnsubscribeError> {
-        self.sender
-            .send(Command::Drain {
-                sid: Some(self.sid),
-            })
-            .await?;
+    pub fn drain(&mut self) -> impl Future<Output = Result<(), UnsubscribeError>> + Send + 'static {
+        let sender = self.sender.clone();
+        let sid = self.sid;
+        let drained = self.drained();
+        async move {
+            sender.send(Command::Drain { sid: Some(sid) }).await?;
+            drained.await;
+            Ok(())
+        }
+    }
 
-        Ok(())
+    /// Resolves once the subscription was drained and no message is left in it, without
+    /// draining it.
+    pub(crate) fn drained(&self) -> impl Future<Output = ()> + Send + 'static {
+        let mut drained = self.drained.subscribe();
+        async move {
+            drained.wait_for(|drained| *drained).await.ok();
+        }
     }
 }
 
@@ -1357,6 +1576,7 @@ impl From<tokio::sync::mpsc::error::SendError<Command>> for UnsubscribeError {
 impl Drop for Subscriber {
     fn drop(&mut self) {
         self.receiver.close();
+        self.drained.send_replace(true);
         tokio::spawn({
             let sender = self.sender.clone();
             let sid = self.sid;
@@ -1374,7 +1594,22 @@ impl Stream for Subscriber {
     type Item = Message;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        self.receiver.poll_recv(cx)
+        if self.max.is_some_and(|max| self.yielded >= max) {
+            self.receiver.close();
+            self.drained.send_replace(true);
+            return Poll::Ready(None);
+        }
+        match self.receiver.poll_recv(cx) {
+            Poll::Ready(Some(message)) => {
+                self.yielded += 1;
+                Poll::Ready(Some(message))
+            }
+            Poll::Ready(None) => {
+                self.drained.send_replace(true);
+                Poll::Ready(None)
+            }
+            Poll::Pending => Poll::Pending,
+        }
     }
 }
 
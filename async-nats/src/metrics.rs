@@ -0,0 +1,86 @@
+// Copyright 2020-2022 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Rendering of statistics in the Prometheus text exposition format.
+
+use std::fmt::{Display, Write};
+
+/// Builds a Prometheus text exposition, one metric family at a time.
+pub(crate) struct Encoder {
+    output: String,
+}
+
+impl Encoder {
+    pub(crate) fn new() -> Encoder {
+        Encoder {
+            output: String::new(),
+        }
+    }
+
+    /// Starts a metric family. All of its samples have to follow before the next one starts.
+    pub(crate) fn family(&mut self, name: &str, kind: &str, help: &str) {
+        // Writing to a `String` can't fail.
+        let _ = writeln!(self.output, "# HELP {name} {help}");
+        let _ = writeln!(self.output, "# TYPE {name} {kind}");
+    }
+
+    pub(crate) fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: impl Display) {
+        self.output.push_str(name);
+        if !labels.is_empty() {
+            self.output.push('{');
+            for (i, (label, value)) in labels.iter().enumerate() {
+                if i > 0 {
+                    self.output.push(',');
+                }
+                let _ = write!(self.output, "{label}=\"{}\"", escape(value));
+            }
+            self.output.push('}');
+        }
+        let _ = writeln!(self.output, " {value}");
+    }
+
+    pub(crate) fn finish(self) -> String {
+        self.output
+    }
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode() {
+        let mut encoder = Encoder::new();
+        encoder.family("requests_total", "counter", "Number of requests.");
+        encoder.sample("requests_total", &[], 3);
+        encoder.sample(
+            "requests_total",
+            &[("endpoint", "a\"b"), ("subject", "c\\d\ne")],
+            1.5,
+        );
+        assert_eq!(
+            encoder.finish(),
+            "# HELP requests_total Number of requests.\n\
+             # TYPE requests_total counter\n\
+             requests_total 3\n\
+             requests_total{endpoint=\"a\\\"b\",subject=\"c\\\\d\\ne\"} 1.5\n"
+        );
+    }
+}
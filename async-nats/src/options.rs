@@ -60,6 +60,7 @@ pub struct ConnectOptions {
     pub(crate) inbox_prefix: String,
     pub(crate) request_timeout: Option<Duration>,
     pub(crate) retry_on_initial_connect: bool,
+    pub(crate) warm_request_inbox: bool,
     pub(crate) ignore_discovered_servers: bool,
     pub(crate) retain_servers_order: bool,
     pub(crate) read_buffer_capacity: u16,
@@ -84,6 +85,7 @@ impl fmt::Debug for ConnectOptions {
             .entry(&"sender_capacity", &self.sender_capacity)
             .entry(&"inbox_prefix", &self.inbox_prefix)
             .entry(&"retry_on_initial_connect", &self.retry_on_initial_connect)
+            .entry(&"warm_request_inbox", &self.warm_request_inbox)
             .entry(&"read_buffer_capacity", &self.read_buffer_capacity)
             .finish()
     }
@@ -109,6 +111,7 @@ impl Default for ConnectOptions {
             inbox_prefix: "_INBOX".to_string(),
             request_timeout: Some(Duration::from_secs(10)),
             retry_on_initial_connect: false,
+            warm_request_inbox: false,
             ignore_discovered_servers: false,
             retain_servers_order: false,
             read_buffer_capacity: 65535,
@@ -822,6 +825,28 @@ impl ConnectOptions {
         self
     }
 
+    /// Sets up the shared request inbox subscription while connecting, so the first
+    /// request does not pay for it. See [`Client::warm_request_inbox`].
+    ///
+    /// With [`ConnectOptions::retry_on_initial_connect`] the warm-up happens in the
+    /// background once the connection is established.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), async_nats::Error> {
+    /// async_nats::ConnectOptions::new()
+    ///     .warm_request_inbox()
+    ///     .connect("demo.nats.io")
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn warm_request_inbox(mut self) -> ConnectOptions {
+        self.warm_request_inbox = true;
+        self
+    }
+
     /// Specifies the number of consecutive reconnect attempts the client will
     /// make before giving up. This is useful for preventing zombie services
     /// from endlessly reaching the servers, but it can also be a footgun and
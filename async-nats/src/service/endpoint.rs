@@ -13,6 +13,7 @@
 
 use std::{
     collections::HashMap,
+    future::Future,
     sync::{Arc, Mutex},
     task::Poll,
     time::Instant,
@@ -24,15 +25,21 @@ use tracing::{debug, trace};
 
 use crate::{Client, Subscriber};
 
-use super::{error, Endpoints, Request, ShutdownReceiverFuture};
+use super::{error, EndpointStatsHandler, Endpoints, Request, ShutdownReceiverFuture};
 
 pub struct Endpoint {
     pub(crate) requests: Subscriber,
     pub(crate) stats: Arc<Mutex<Endpoints>>,
     pub(crate) client: Client,
     pub(crate) endpoint: String,
+    /// The subject the endpoint is subscribed to, including the group prefix.
+    pub(crate) subject: String,
     pub(crate) shutdown: Option<tokio::sync::broadcast::Receiver<()>>,
     pub(crate) shutdown_future: Option<ShutdownReceiverFuture>,
+    /// Requests with larger payloads are rejected without being yielded.
+    pub(crate) max_request_size: Option<usize>,
+    /// Set by [Endpoint::drain], so stopping the service lets the drain finish.
+    pub(crate) draining: bool,
 }
 
 impl Stream for Endpoint {
@@ -52,13 +59,17 @@ impl Stream for Endpoint {
             match shutdown.as_mut().poll(cx) {
                 Poll::Ready(_result) => {
                     debug!("got stop broadcast");
-                    self.requests
-                        .sender
-                        .try_send(crate::Command::Unsubscribe {
-                            sid: self.requests.sid,
-                            max: None,
-                        })
-                        .ok();
+                    // A draining endpoint already sent its UNSUB and still yields the requests
+                    // in flight, so it must not be cut short.
+                    if !self.draining {
+                        self.requests
+                            .sender
+                            .try_send(crate::Command::Unsubscribe {
+                                sid: self.requests.sid,
+                                max: None,
+                            })
+                            .ok();
+                    }
 
                     // Clear future, can't be resumed after completion
                     self.shutdown_future = None;
@@ -69,25 +80,49 @@ impl Stream for Endpoint {
             }
         }
 
-        trace!("checking for new messages");
-        match self.requests.poll_next_unpin(cx) {
-            Poll::Ready(message) => {
-                debug!("got next message");
-                match message {
-                    Some(message) => Poll::Ready(Some(Request {
+        loop {
+            trace!("checking for new messages");
+            match self.requests.poll_next_unpin(cx) {
+                Poll::Ready(message) => {
+                    debug!("got next message");
+                    let message = match message {
+                        Some(message) => message,
+                        None => return Poll::Ready(None),
+                    };
+                    let request = Request {
                         issued: Instant::now(),
                         stats: self.stats.clone(),
                         client: self.client.clone(),
                         message,
                         endpoint: self.endpoint.clone(),
-                    })),
-                    None => Poll::Ready(None),
+                    };
+                    match self.max_request_size {
+                        Some(max) if request.message.payload.len() > max => {
+                            debug!(
+                                "rejecting request of {} bytes on {}",
+                                request.message.payload.len(),
+                                self.endpoint
+                            );
+                            if request.message.reply.is_some() {
+                                tokio::spawn(async move {
+                                    request
+                                        .respond(Err(error::Error {
+                                            status: "Request Too Large".to_string(),
+                                            code: 413,
+                                        }))
+                                        .await
+                                        .ok();
+                                });
+                            }
+                        }
+                        _ => return Poll::Ready(Some(request)),
+                    }
                 }
-            }
 
-            Poll::Pending => {
-                trace!("still pending for messages");
-                Poll::Pending
+                Poll::Pending => {
+                    trace!("still pending for messages");
+                    return Poll::Pending;
+                }
             }
         }
     }
@@ -98,13 +133,95 @@ impl Stream for Endpoint {
 }
 
 impl Endpoint {
+    /// Checks whether a request published on `subject` would be routed to this [Endpoint],
+    /// following the wildcard rules of [crate::subject::subject_matches].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), async_nats::Error> {
+    /// use async_nats::service::ServiceExt;
+    /// let client = async_nats::connect("demo.nats.io").await?;
+    /// let service = client.service_builder().start("service", "1.0.0").await?;
+    /// let created = service.endpoint("orders.*.created").await?;
+    ///
+    /// assert!(created.matches("orders.eu.created"));
+    /// assert!(!created.matches("orders.eu.shipped"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn matches(&self, subject: &str) -> bool {
+        crate::subject::subject_matches(&self.subject, subject)
+    }
+
     /// Stops the [Endpoint] and unsubscribes from the subject.
+    ///
+    /// If the underlying [Client] is draining, the subscription is already being drained
+    /// along with the connection and this is a no-op.
     pub async fn stop(&mut self) -> Result<(), std::io::Error> {
+        if self.client.is_draining() {
+            return Ok(());
+        }
         self.requests
             .unsubscribe()
             .await
             .map_err(|_| std::io::Error::new(std::io::ErrorKind::Other, "failed to unsubscribe"))
     }
+
+    /// Stops the [Endpoint] from receiving new requests, while still yielding the ones that were
+    /// already sent to it. Unlike [Endpoint::stop], no request in flight is lost, which makes
+    /// this suitable for rolling restarts.
+    ///
+    /// Like [Subscriber::drain], the returned future resolves only once the remaining requests
+    /// were yielded and the stream ended, so keep consuming the [Endpoint] while it is pending.
+    /// If the underlying [Client] is draining, the subscription is already being drained along
+    /// with the connection and the future only waits for the stream to end.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), async_nats::Error> {
+    /// use async_nats::service::ServiceExt;
+    /// use futures::StreamExt;
+    /// let client = async_nats::connect("demo.nats.io").await?;
+    /// let service = client.service_builder().start("service", "1.0.0").await?;
+    /// let mut products = service.endpoint("products").await?;
+    ///
+    /// let drained = products.drain();
+    /// let handle = async {
+    ///     while let Some(request) = products.next().await {
+    ///         request.respond(Ok("data".into())).await?;
+    ///     }
+    ///     Ok::<(), async_nats::Error>(())
+    /// };
+    /// let (drained, handled) = tokio::join!(drained, handle);
+    /// drained?;
+    /// handled?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn drain(&mut self) -> impl Future<Output = Result<(), std::io::Error>> + Send + 'static {
+        self.draining = true;
+        let drain = if self.client.is_draining() {
+            None
+        } else {
+            Some(self.requests.drain())
+        };
+        let drained = self.requests.drained();
+        async move {
+            match drain {
+                Some(drain) => drain
+                    .await
+                    .map_err(|_| std::io::Error::other("failed to drain")),
+                None => {
+                    drained.await;
+                    Ok(())
+                }
+            }
+        }
+    }
 }
 
 /// Stats of a single endpoint.
@@ -136,8 +253,17 @@ pub(crate) struct Inner {
     pub(crate) last_error: Option<error::Error>,
     /// Custom data added by [Config::stats_handler]
     pub(crate) data: Option<serde_json::Value>,
+    /// Handler set with [crate::service::EndpointBuilder::stats_handler], taking precedence
+    /// over the service wide one.
+    #[serde(skip)]
+    pub(crate) stats_handler: Option<EndpointStatsHandler>,
     /// Queue group to which this endpoint is assigned to.
     pub(crate) queue_group: String,
+    /// Set while the endpoint is disabled with [crate::service::Service::disable_endpoint].
+    pub(crate) disabled: bool,
+    /// Subscription id of the endpoint requests.
+    #[serde(skip)]
+    pub(crate) sid: u64,
 }
 
 impl From<Inner> for Stats {
@@ -152,6 +278,7 @@ impl From<Inner> for Stats {
             last_error: inner.last_error,
             data: inner.data,
             queue_group: inner.queue_group,
+            disabled: inner.disabled,
         }
     }
 }
@@ -182,6 +309,9 @@ pub struct Stats {
     pub data: Option<serde_json::Value>,
     /// Queue group to which this endpoint is assigned to.
     pub queue_group: String,
+    /// Set while the endpoint is disabled and doesn't receive requests.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub disabled: bool,
 }
 
 mod serde_error_string {
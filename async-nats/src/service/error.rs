@@ -15,6 +15,9 @@ use std::fmt::Display;
 
 use serde::{Deserialize, Serialize};
 
+use super::{NATS_SERVICE_ERROR, NATS_SERVICE_ERROR_CODE};
+use crate::HeaderMap;
+
 impl std::error::Error for Error {}
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -32,3 +35,21 @@ impl Display for Error {
         )
     }
 }
+
+impl Error {
+    /// Parses the error a service responded with from the reply headers.
+    /// Returns `None` for replies without service error headers.
+    pub fn from_headers(headers: &HeaderMap) -> Option<Error> {
+        let status = headers.get(NATS_SERVICE_ERROR);
+        let code = headers.get(NATS_SERVICE_ERROR_CODE);
+        if status.is_none() && code.is_none() {
+            return None;
+        }
+        Some(Error {
+            status: status.map(|s| s.as_str().to_string()).unwrap_or_default(),
+            code: code
+                .and_then(|c| c.as_str().parse().ok())
+                .unwrap_or_default(),
+        })
+    }
+}
@@ -35,7 +35,7 @@ use time::OffsetDateTime;
 use tokio::{sync::broadcast::Sender, task::JoinHandle};
 use tracing::debug;
 
-use crate::{Client, Error, HeaderMap, Message, PublishError, Subscriber};
+use crate::{subject::ToSubject, Client, Error, HeaderMap, Message, PublishError, Subscriber};
 
 use self::endpoint::Endpoint;
 
@@ -74,6 +74,9 @@ pub struct PingResponse {
     /// Additional metadata
     #[serde(default, deserialize_with = "endpoint::null_meta_as_default")]
     pub metadata: HashMap<String, String>,
+    /// Set when the connection of the service is draining.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub draining: bool,
 }
 
 /// Response for `STATS` requests.
@@ -114,6 +117,10 @@ pub struct Info {
     pub metadata: HashMap<String, String>,
     /// Info about all service endpoints.
     pub endpoints: Vec<endpoint::Info>,
+    /// Set when the connection of the service is draining and its endpoints no longer accept
+    /// new requests.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub draining: bool,
 }
 
 /// Configuration of the [Service].
@@ -280,6 +287,82 @@ pub trait ServiceExt {
     fn service_builder(&self) -> ServiceBuilder;
 }
 
+/// Sends a request to a service endpoint and returns the reply payload,
+/// turning replies with the `Nats-Service-Error` and
+/// `Nats-Service-Error-Code` headers set by [Request::respond] into
+/// [RequestErrorKind::Service] errors.
+///
+/// # Examples
+///
+/// ```no_run
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), async_nats::Error> {
+/// use async_nats::service::{self, RequestErrorKind};
+/// let client = async_nats::connect("demo.nats.io").await?;
+/// match service::request_full(&client, "products.get", "1".into()).await {
+///     Ok(payload) => println!("product: {:?}", payload),
+///     Err(err) => match err.kind() {
+///         RequestErrorKind::Service(err) => println!("service error {}", err.code),
+///         _ => return Err(err.into()),
+///     },
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub async fn request_full<S: ToSubject>(
+    client: &Client,
+    subject: S,
+    payload: Bytes,
+) -> Result<Bytes, RequestError> {
+    let message = client.request(subject, payload).await?;
+    if let Some(err) = message
+        .headers
+        .as_ref()
+        .and_then(error::Error::from_headers)
+    {
+        return Err(RequestError::new(RequestErrorKind::Service(err)));
+    }
+    Ok(message.payload)
+}
+
+/// The kind of error returned by [request_full].
+#[derive(Clone, Debug, PartialEq)]
+pub enum RequestErrorKind {
+    /// The service didn't respond in time.
+    TimedOut,
+    /// No service is listening on the subject.
+    NoResponders,
+    /// The service responded with an error.
+    Service(error::Error),
+    /// Other errors, client/io related.
+    Other,
+}
+
+impl Display for RequestErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TimedOut => write!(f, "request timed out"),
+            Self::NoResponders => write!(f, "no responders"),
+            Self::Service(err) => write!(f, "{}", err),
+            Self::Other => write!(f, "request failed"),
+        }
+    }
+}
+
+/// Error returned by [request_full].
+pub type RequestError = crate::error::Error<RequestErrorKind>;
+
+impl From<crate::RequestError> for RequestError {
+    fn from(err: crate::RequestError) -> Self {
+        let kind = match err.kind() {
+            crate::RequestErrorKind::TimedOut => RequestErrorKind::TimedOut,
+            crate::RequestErrorKind::NoResponders => RequestErrorKind::NoResponders,
+            crate::RequestErrorKind::Other => RequestErrorKind::Other,
+        };
+        RequestError::with_source(kind, err)
+    }
+}
+
 impl ServiceExt for crate::Client {
     type Output = Pin<Box<dyn Future<Output = Result<Service, crate::Error>> + Send>>;
 
@@ -358,6 +441,7 @@ impl Service {
             version: config.version.clone(),
             metadata: config.metadata.clone().unwrap_or_default(),
             endpoints: Vec::new(),
+            draining: false,
         };
 
         let (shutdown_tx, _) = tokio::sync::broadcast::channel(1);
@@ -386,6 +470,7 @@ impl Service {
                                 id: info.id.clone(),
                                 version: info.version.clone(),
                                 metadata: info.metadata.clone(),
+                                draining: client.is_draining(),
                             })?;
                             client.publish(ping.reply.unwrap(), pong.into()).await?;
                         },
@@ -393,7 +478,7 @@ impl Service {
                             let info = info.clone();
 
                             let endpoints: Vec<endpoint::Info> = {
-                                endpoints_state.lock().unwrap().endpoints.values().map(|value| {
+                                endpoints_state.lock().unwrap().endpoints.values().filter(|value| !value.disabled).map(|value| {
                                     endpoint::Info {
                                         name: value.name.to_owned(),
                                         subject: value.subject.to_owned(),
@@ -404,17 +489,38 @@ impl Service {
                             };
                             let info = Info {
                                 endpoints,
+                                draining: client.is_draining(),
                                 ..info
                             };
                             let info_json = serde_json::to_vec(&info).map(Bytes::from)?;
                             client.publish(info_request.reply.unwrap(), info_json.clone()).await?;
                         },
                         Some(stats_request) = stats.next() => {
-                            if let Some(stats_callback) = stats_callback.as_mut() {
-                                let mut endpoint_stats_locked = endpoints_state.lock().unwrap();
-                                for (key, value) in &mut endpoint_stats_locked.endpoints {
-                                    let data = stats_callback.0(key.to_string(), value.clone().into());
-                                    value.data = Some(data);
+                            // Handlers run on a snapshot, so requests aren't blocked on the lock
+                            // while they do.
+                            let mut endpoints: Vec<(String, endpoint::Inner)> = endpoints_state
+                                .lock()
+                                .unwrap()
+                                .endpoints
+                                .iter()
+                                .map(|(key, value)| (key.to_owned(), value.to_owned()))
+                                .collect();
+                            let mut updated = false;
+                            for (key, value) in &mut endpoints {
+                                let data = match (value.stats_handler.clone(), stats_callback.as_mut()) {
+                                    (Some(handler), _) => handler.0(value.clone().into()),
+                                    (None, Some(stats_callback)) => stats_callback.0(key.to_string(), value.clone().into()),
+                                    (None, None) => continue,
+                                };
+                                value.data = Some(data);
+                                updated = true;
+                            }
+                            if updated {
+                                let mut endpoints_state = endpoints_state.lock().unwrap();
+                                for (key, value) in &endpoints {
+                                    if let Some(inner) = endpoints_state.endpoints.get_mut(key) {
+                                        inner.data = value.data.clone();
+                                    }
                                 }
                             }
                             let stats = serde_json::to_vec(&Stats {
@@ -423,7 +529,7 @@ impl Service {
                                 id: info.id.clone(),
                                 version: info.version.clone(),
                                 started,
-                                endpoints: endpoints_state.lock().unwrap().endpoints.values().cloned().map(Into::into).collect(),
+                                endpoints: endpoints.into_iter().map(|(_, value)| value.into()).collect(),
                             })?;
                             client.publish(stats_request.reply.unwrap(), stats.into()).await?;
                         },
@@ -474,9 +580,145 @@ impl Service {
             .collect()
     }
 
+    /// Renders the [Stats] of every endpoint in the Prometheus text exposition format, ready to
+    /// be served from an application's `/metrics` endpoint.
+    ///
+    /// Metrics are prefixed with `nats_service_` and labeled with the `service` name, its
+    /// `id`, and the `endpoint` name, `subject` and `queue_group`, which tells apart endpoints
+    /// load balanced in different groups.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), async_nats::Error> {
+    /// use async_nats::service::ServiceExt;
+    /// let client = async_nats::connect("demo.nats.io").await?;
+    /// let service = client.service_builder().start("service", "1.0.0").await?;
+    /// let products = service.endpoint("products").await?;
+    ///
+    /// print!("{}", service.prometheus_metrics().await);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "metrics")]
+    pub async fn prometheus_metrics(&self) -> String {
+        let mut stats: Vec<endpoint::Stats> = self.stats().await.into_values().collect();
+        stats.sort_by(|a, b| a.name.cmp(&b.name));
+
+        type Value = fn(&endpoint::Stats) -> f64;
+        let families: [(&str, &str, &str, Value); 4] = [
+            (
+                "nats_service_requests_total",
+                "counter",
+                "Requests handled by the endpoint.",
+                |stats| stats.requests as f64,
+            ),
+            (
+                "nats_service_errors_total",
+                "counter",
+                "Requests the endpoint responded to with an error.",
+                |stats| stats.errors as f64,
+            ),
+            (
+                "nats_service_processing_seconds_total",
+                "counter",
+                "Total time spent processing requests.",
+                |stats| stats.processing_time.as_secs_f64(),
+            ),
+            (
+                "nats_service_average_processing_seconds",
+                "gauge",
+                "Average time spent processing a request.",
+                |stats| stats.average_processing_time.as_secs_f64(),
+            ),
+        ];
+
+        let mut encoder = crate::metrics::Encoder::new();
+        for (name, kind, help, value) in families {
+            encoder.family(name, kind, help);
+            for stats in &stats {
+                encoder.sample(
+                    name,
+                    &[
+                        ("service", &self.info.name),
+                        ("id", &self.info.id),
+                        ("endpoint", &stats.name),
+                        ("subject", &stats.subject),
+                        ("queue_group", &stats.queue_group),
+                    ],
+                    value(stats),
+                );
+            }
+        }
+        encoder.finish()
+    }
+
+    /// Temporarily stops an endpoint from receiving requests, leaving the rest of the [Service]
+    /// running.
+    ///
+    /// The endpoint's subscription is removed from the server, but the [Endpoint] stream stays
+    /// open and picks up requests again after [Service::enable_endpoint]. Its stats are kept
+    /// and reported as disabled in `STATS` responses, while `INFO` responses leave the
+    /// endpoint out until it is enabled. `name` is the endpoint name, which defaults to its
+    /// subject.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), async_nats::Error> {
+    /// use async_nats::service::ServiceExt;
+    /// let client = async_nats::connect("demo.nats.io").await?;
+    /// let service = client.service_builder().start("service", "1.0.0").await?;
+    /// let products = service.endpoint("products").await?;
+    ///
+    /// service.disable_endpoint("products").await?;
+    /// service.enable_endpoint("products").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn disable_endpoint(&self, name: &str) -> Result<(), Error> {
+        self.set_endpoint_disabled(name, true).await
+    }
+
+    /// Resumes an endpoint stopped with [Service::disable_endpoint].
+    pub async fn enable_endpoint(&self, name: &str) -> Result<(), Error> {
+        self.set_endpoint_disabled(name, false).await
+    }
+
+    async fn set_endpoint_disabled(&self, name: &str, disabled: bool) -> Result<(), Error> {
+        let sid = {
+            let mut state = self.endpoints_state.lock().unwrap();
+            let endpoint = state.endpoints.get_mut(name).ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("endpoint {name} not found"),
+                )
+            })?;
+            if endpoint.disabled == disabled {
+                return Ok(());
+            }
+            endpoint.disabled = disabled;
+            endpoint.sid
+        };
+
+        let command = if disabled {
+            crate::Command::Pause { sid }
+        } else {
+            crate::Command::Resume { sid }
+        };
+        self.client.sender.send(command).await?;
+        self.client.flush().await?;
+        Ok(())
+    }
+
     /// Returns [Info] for this service instance.
     pub async fn info(&self) -> Info {
-        self.info.clone()
+        Info {
+            draining: self.client.is_draining(),
+            ..self.info.clone()
+        }
     }
 
     /// Creates a group for endpoints under common prefix.
@@ -802,6 +1044,8 @@ pub struct EndpointBuilder {
     subjects: Arc<Mutex<Vec<String>>>,
     queue_group: String,
     prefix: Option<String>,
+    max_request_size: Option<usize>,
+    stats_handler: Option<EndpointStatsHandler>,
 }
 
 impl EndpointBuilder {
@@ -821,6 +1065,8 @@ impl EndpointBuilder {
             metadata: None,
             queue_group,
             prefix: None,
+            max_request_size: None,
+            stats_handler: None,
         }
     }
 
@@ -842,6 +1088,70 @@ impl EndpointBuilder {
         self
     }
 
+    /// Rejects requests with a payload larger than `bytes` before they reach the
+    /// [Endpoint] stream, responding with a `413` service error which counts
+    /// towards the endpoint's errors. By default any size is processed.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), async_nats::Error> {
+    /// use async_nats::service::ServiceExt;
+    /// # let client = async_nats::connect("demo.nats.io").await?;
+    /// # let service = client
+    /// #    .service_builder().start("serviceA", "1.0.0").await?;
+    /// let endpoint = service
+    ///     .endpoint_builder()
+    ///     .max_request_size(64 * 1024)
+    ///     .add("upload")
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn max_request_size(mut self, bytes: usize) -> EndpointBuilder {
+        self.max_request_size = Some(bytes);
+        self
+    }
+
+    /// Custom handler for providing the `data` in this [Endpoint]'s stats, used instead of
+    /// [Config::stats_handler] if both are set.
+    ///
+    /// The handler is called with the current stats whenever a `STATS` request is received,
+    /// rather than for every request, so it can report counters the application keeps on its
+    /// own.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), async_nats::Error> {
+    /// use async_nats::service::ServiceExt;
+    /// use std::sync::atomic::{AtomicU64, Ordering};
+    /// use std::sync::Arc;
+    /// # let client = async_nats::connect("demo.nats.io").await?;
+    /// # let service = client
+    /// #    .service_builder().start("serviceA", "1.0.0").await?;
+    /// let cache_hits = Arc::new(AtomicU64::new(0));
+    /// let endpoint = service
+    ///     .endpoint_builder()
+    ///     .stats_handler({
+    ///         let cache_hits = cache_hits.clone();
+    ///         move |_stats| serde_json::json!({ "cache_hits": cache_hits.load(Ordering::Relaxed) })
+    ///     })
+    ///     .add("products")
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn stats_handler<F>(mut self, handler: F) -> EndpointBuilder
+    where
+        F: Fn(endpoint::Stats) -> serde_json::Value + Send + Sync + 'static,
+    {
+        self.stats_handler = Some(EndpointStatsHandler(Arc::new(handler)));
+        self
+    }
+
     /// Finalizes the builder and adds the [Endpoint].
     pub async fn add<S: ToString>(self, subject: S) -> Result<Endpoint, Error> {
         let mut subject = subject.to_string();
@@ -862,7 +1172,7 @@ impl EndpointBuilder {
         let shutdown_rx = self.shutdown_tx.subscribe();
 
         let mut stats = self.stats.lock().unwrap();
-        stats
+        let inner = stats
             .endpoints
             .entry(endpoint_name.clone())
             .or_insert(endpoint::Inner {
@@ -872,14 +1182,22 @@ impl EndpointBuilder {
                 queue_group: self.queue_group.clone(),
                 ..Default::default()
             });
+        inner.sid = requests.sid;
+        inner.disabled = false;
+        if self.stats_handler.is_some() {
+            inner.stats_handler = self.stats_handler;
+        }
         self.subjects.lock().unwrap().push(subject.clone());
         Ok(Endpoint {
             requests,
             stats: self.stats.clone(),
             client: self.client.clone(),
             endpoint: endpoint_name,
+            subject,
             shutdown: Some(shutdown_rx),
             shutdown_future: None,
+            max_request_size: self.max_request_size,
+            draining: false,
         })
     }
 }
@@ -892,6 +1210,17 @@ impl std::fmt::Debug for StatsHandler {
     }
 }
 
+#[derive(Clone)]
+pub(crate) struct EndpointStatsHandler(
+    pub(crate) Arc<dyn Fn(endpoint::Stats) -> serde_json::Value + Send + Sync>,
+);
+
+impl std::fmt::Debug for EndpointStatsHandler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Endpoint stats handler")
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
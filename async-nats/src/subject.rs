@@ -130,6 +130,47 @@ impl fmt::Display for Subject {
     }
 }
 
+/// Checks whether `subject` would be delivered to a subscription on `pattern`.
+///
+/// Tokens are separated by `.` and compared case-sensitively. A `*` token matches exactly one
+/// token, while a `>` token, which is only valid at the end of the pattern, matches one or more
+/// remaining tokens. Patterns or subjects with empty tokens never match.
+///
+/// # Examples
+///
+/// ```
+/// use async_nats::subject::subject_matches;
+///
+/// assert!(subject_matches("orders.*.created", "orders.eu.created"));
+/// assert!(subject_matches("orders.>", "orders.us.shipped"));
+/// assert!(!subject_matches("orders.>", "orders"));
+/// assert!(!subject_matches("orders.*", "Orders.us"));
+/// ```
+pub fn subject_matches(pattern: &str, subject: &str) -> bool {
+    let mut pattern = pattern.split('.').peekable();
+    let mut subject = subject.split('.');
+
+    while let Some(token) = pattern.next() {
+        if token.is_empty() {
+            return false;
+        }
+        let Some(next) = subject.next() else {
+            return false;
+        };
+        if next.is_empty() {
+            return false;
+        }
+        match token {
+            ">" => return pattern.peek().is_none() && subject.all(|token| !token.is_empty()),
+            "*" => {}
+            token if token != next => return false,
+            _ => {}
+        }
+    }
+
+    subject.next().is_none()
+}
+
 pub trait ToSubject {
     fn to_subject(&self) -> Subject;
 }
@@ -169,3 +210,36 @@ impl<'de> Deserialize<'de> for Subject {
         Ok(String::deserialize(deserializer)?.into())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subject_matches() {
+        assert!(subject_matches("foo.bar", "foo.bar"));
+        assert!(!subject_matches("foo.bar", "foo.baz"));
+        assert!(!subject_matches("foo.bar", "foo.bar.baz"));
+        assert!(!subject_matches("foo.bar.baz", "foo.bar"));
+        assert!(!subject_matches("foo.bar", "FOO.bar"));
+
+        assert!(subject_matches("foo.*", "foo.bar"));
+        assert!(subject_matches("*.bar", "foo.bar"));
+        assert!(subject_matches("orders.*.created", "orders.eu.created"));
+        assert!(!subject_matches("foo.*", "foo"));
+        assert!(!subject_matches("foo.*", "foo."));
+        assert!(!subject_matches("foo.*", "foo.bar.baz"));
+
+        assert!(subject_matches(">", "foo"));
+        assert!(subject_matches("foo.>", "foo.bar"));
+        assert!(subject_matches("foo.>", "foo.bar.baz"));
+        assert!(subject_matches("foo.*.>", "foo.bar.baz"));
+        assert!(!subject_matches("foo.>", "foo"));
+        assert!(!subject_matches("foo.>", "foo.bar."));
+        assert!(!subject_matches("foo.>.baz", "foo.bar.baz"));
+
+        assert!(!subject_matches("", ""));
+        assert!(!subject_matches("foo..bar", "foo..bar"));
+        assert!(!subject_matches("foo.bar", "foo..bar"));
+    }
+}
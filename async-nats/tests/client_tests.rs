@@ -257,6 +257,39 @@ mod client {
         assert_eq!(resp.unwrap().payload, Bytes::from("reply"));
     }
 
+    #[tokio::test]
+    async fn request_warm_inbox() {
+        let server = nats_server::run_basic_server();
+        let client = async_nats::ConnectOptions::new()
+            .warm_request_inbox()
+            .connect(server.client_url())
+            .await
+            .unwrap();
+
+        // Warming up again only flushes.
+        client.warm_request_inbox().await.unwrap();
+
+        let mut sub = client.subscribe("test").await.unwrap();
+        tokio::spawn({
+            let client = client.clone();
+            async move {
+                let msg = sub.next().await.unwrap();
+                client
+                    .publish(msg.reply.unwrap(), "reply".into())
+                    .await
+                    .unwrap();
+            }
+        });
+
+        let resp = tokio::time::timeout(
+            tokio::time::Duration::from_millis(500),
+            client.request("test", "request".into()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(resp.unwrap().payload, Bytes::from("reply"));
+    }
+
     #[tokio::test]
     async fn request_timeout() {
         let server = nats_server::run_basic_server();
@@ -272,6 +305,53 @@ mod client {
         assert_eq!(err.kind(), RequestErrorKind::TimedOut)
     }
 
+    #[tokio::test]
+    async fn request_cancelled_by_drop() {
+        let server = nats_server::run_basic_server();
+        let client = async_nats::connect(server.client_url()).await.unwrap();
+
+        let mut sub = client.subscribe("service").await.unwrap();
+        client.flush().await.unwrap();
+
+        // Abandon more requests than the multiplexer keeps before pruning.
+        for _ in 0..200 {
+            tokio::time::timeout(
+                Duration::from_millis(1),
+                client.request("service", "abandoned".into()),
+            )
+            .await
+            .ok();
+        }
+
+        // Late replies to the abandoned requests are dropped.
+        let mut abandoned = 0;
+        while let Ok(Some(message)) =
+            tokio::time::timeout(Duration::from_millis(200), sub.next()).await
+        {
+            if message.payload == "abandoned" {
+                client
+                    .publish(message.reply.unwrap(), "late".into())
+                    .await
+                    .unwrap();
+                abandoned += 1;
+            }
+        }
+        assert!(abandoned > 0);
+
+        tokio::spawn({
+            let client = client.clone();
+            async move {
+                let message = sub.next().await.unwrap();
+                client
+                    .publish(message.reply.unwrap(), "reply".into())
+                    .await
+                    .unwrap();
+            }
+        });
+        let response = client.request("service", "request".into()).await.unwrap();
+        assert_eq!(response.payload, "reply");
+    }
+
     #[tokio::test]
     async fn request_no_responders() {
         let server = nats_server::run_basic_server();
@@ -375,6 +455,29 @@ mod client {
         assert!(sub.next().await.is_none());
     }
 
+    #[tokio::test]
+    async fn unsubscribe_after_already_received() {
+        let server = nats_server::run_basic_server();
+        let client = async_nats::connect(server.client_url()).await.unwrap();
+
+        let mut sub = client.subscribe("test").await.unwrap();
+        for _ in 0..3 {
+            client.publish("test", "data".into()).await.unwrap();
+        }
+        client.flush().await.unwrap();
+        assert!(sub.next().await.is_some());
+        assert!(sub.next().await.is_some());
+
+        // Fewer than already yielded, the stream ends right away, without the third message.
+        sub.unsubscribe_after(1).await.unwrap();
+        assert!(sub.next().await.is_none());
+
+        // The server stopped delivering as well.
+        client.publish("test", "data".into()).await.unwrap();
+        client.flush().await.unwrap();
+        assert!(sub.next().await.is_none());
+    }
+
     #[tokio::test]
     async fn connect_invalid() {
         assert!(async_nats::connect("localhost:1111").await.is_err());
@@ -595,6 +698,54 @@ mod client {
             .unwrap()
             .unwrap();
     }
+    #[tokio::test]
+    async fn subscribe_with_capacity() {
+        let server = nats_server::run_basic_server();
+
+        let (tx, mut rx) = tokio::sync::mpsc::channel(128);
+        let client = ConnectOptions::new()
+            .event_callback(move |event| {
+                let tx = tx.clone();
+                async move {
+                    if let Event::SlowConsumer(_) = event {
+                        tx.send(()).await.unwrap()
+                    }
+                }
+            })
+            .connect(server.client_url())
+            .await
+            .unwrap();
+
+        let mut sub = client.subscribe_with_capacity("data", 2).await.unwrap();
+        for i in 0..20 {
+            client.publish("data", i.to_string().into()).await.unwrap();
+        }
+        // The PONG of the flush is read only once the subscriber caught up.
+        let flush = tokio::spawn({
+            let client = client.clone();
+            async move { client.flush().await }
+        });
+
+        // Let the subscriber fall behind before catching up.
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        assert!(!flush.is_finished());
+        for i in 0..20 {
+            let message = tokio::time::timeout(Duration::from_secs(5), sub.next())
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(message.payload, i.to_string());
+        }
+        tokio::time::timeout(Duration::from_secs(5), flush)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert!(rx.try_recv().is_err());
+
+        assert!(client.subscribe_with_capacity("data", 0).await.is_err());
+    }
+
     #[tokio::test]
     async fn no_echo() {
         // no_echo disabled.
@@ -1019,6 +1170,36 @@ mod client {
         assert_eq!(stats.connects.load(Ordering::Relaxed), 2);
     }
 
+    #[tokio::test]
+    async fn client_prometheus_metrics() {
+        let server = nats_server::run_basic_server();
+        let client = async_nats::connect(server.client_url()).await.unwrap();
+
+        let mut subscriber = client.subscribe("metrics").await.unwrap();
+        client.publish("metrics", "data".into()).await.unwrap();
+        subscriber.next().await.unwrap();
+
+        let metrics = client.prometheus_metrics();
+        assert!(metrics.contains("# TYPE nats_client_out_messages_total counter\n"));
+        assert!(metrics.contains("\nnats_client_out_messages_total 1\n"));
+        assert!(metrics.contains("\nnats_client_in_messages_total 1\n"));
+        assert!(metrics.contains("\nnats_client_connects_total 1\n"));
+        assert!(metrics.contains(
+            "\nnats_client_subscription_delivered_messages_total{sid=\"1\",subject=\"metrics\"} 1\n"
+        ));
+        assert!(metrics.contains(
+            "\nnats_client_subscription_dropped_messages_total{sid=\"1\",subject=\"metrics\"} 0\n"
+        ));
+
+        // Only active subscriptions are reported.
+        drop(subscriber);
+        client.flush().await.unwrap();
+        client.publish("metrics", "data".into()).await.unwrap();
+        client.flush().await.unwrap();
+        let metrics = client.prometheus_metrics();
+        assert!(!metrics.contains("sid=\"1\""));
+    }
+
     #[tokio::test]
     async fn client_timeout() {
         let server = nats_server::run_basic_server();
@@ -1095,12 +1276,16 @@ mod client {
         client.flush().await.unwrap();
 
         // Send the drain command
-        sub.drain().await.expect("Expected to drain the sub");
+        let drained = sub.drain();
 
         // we should receive all published data then close immediately
-        assert!(sub.next().await.is_some());
-        assert!(sub.next().await.is_some());
-        assert!(sub.next().await.is_none());
+        let consume = async {
+            assert!(sub.next().await.is_some());
+            assert!(sub.next().await.is_some());
+            assert!(sub.next().await.is_none());
+        };
+        let (drained, ()) = tokio::join!(drained, consume);
+        drained.expect("Expected to drain the sub");
     }
 
     #[tokio::test]
@@ -1125,16 +1310,20 @@ mod client {
         assert!(sub.next().await.is_some());
 
         // now drain the subscription
-        sub.drain().await.unwrap();
+        let drained = sub.drain();
 
         // yield to the runtime to ensure constant_writer gets a chance to publish a message or two to the subject
         tokio::time::sleep(Duration::from_millis(1)).await;
 
         // assert the subscription stream is closed after draining
         let sleep_fut = async move { while sub.next().await.is_some() {} };
-        tokio::time::timeout(Duration::from_secs(10), sleep_fut)
-            .await
-            .expect("Expected stream to drain within 10s");
+        let (drained, ()) = tokio::time::timeout(
+            Duration::from_secs(10),
+            futures::future::join(drained, sleep_fut),
+        )
+        .await
+        .expect("Expected stream to drain within 10s");
+        drained.unwrap();
 
         // assert constant_writer doesn't fail to write after the only sub is drained (i.e. client operations still work fine)
         assert!(!constant_writer.is_finished());
@@ -1144,6 +1333,48 @@ mod client {
         assert!(sub2.next().await.is_some());
     }
 
+    #[tokio::test]
+    async fn drain_subscription_waits_until_empty() {
+        let server = nats_server::run_basic_server();
+        let client = async_nats::connect(server.client_url()).await.unwrap();
+
+        let mut sub = client.subscribe("test").await.unwrap();
+        for _ in 0..100 {
+            client.publish("test", "data".into()).await.unwrap();
+        }
+        client.flush().await.unwrap();
+
+        let first = sub.next().await.unwrap();
+        assert_eq!(first.payload, "data");
+
+        // Pending while messages are left to be yielded.
+        let mut drained = tokio::spawn(sub.drain());
+        tokio::time::timeout(Duration::from_millis(500), &mut drained)
+            .await
+            .unwrap_err();
+
+        let mut remaining = 0;
+        while sub.next().await.is_some() {
+            remaining += 1;
+        }
+        assert_eq!(remaining, 99);
+        tokio::time::timeout(Duration::from_secs(5), drained)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+
+        // Resolves without polling the stream when nothing is left.
+        let mut sub = client.subscribe("test").await.unwrap();
+        client.publish("test", "data".into()).await.unwrap();
+        assert!(sub.next().await.is_some());
+        tokio::time::timeout(Duration::from_secs(5), sub.drain())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(sub.next().await.is_none());
+    }
+
     #[tokio::test]
     async fn drain_client_basic() {
         let server = nats_server::run_basic_server();
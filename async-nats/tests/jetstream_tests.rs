@@ -333,6 +333,89 @@ mod jetstream {
             .unwrap();
     }
 
+    #[cfg(not(target_os = "windows"))]
+    #[tokio::test]
+    async fn create_stream_and_wait() {
+        use crate::jetstream::stream::StorageType;
+        let cluster = nats_server::run_cluster("tests/configs/jetstream.conf");
+        tokio::time::sleep(Duration::from_secs(5)).await;
+        let client = async_nats::connect(cluster.client_url()).await.unwrap();
+        let context = async_nats::jetstream::new(client);
+
+        let mut stream = context
+            .create_stream_and_wait(
+                stream::Config {
+                    name: "ready".to_string(),
+                    num_replicas: 3,
+                    storage: StorageType::Memory,
+                    ..Default::default()
+                },
+                Duration::from_secs(10),
+            )
+            .await
+            .unwrap();
+
+        let info = stream.info().await.unwrap();
+        let cluster = info.cluster.as_ref().unwrap();
+        assert!(cluster.leader.is_some());
+        assert!(cluster.replicas.iter().all(|peer| peer.current));
+
+        // The stream accepts messages right away.
+        context
+            .publish("ready", "data".into())
+            .await
+            .unwrap()
+            .await
+            .unwrap();
+
+        let server = nats_server::run_server("tests/configs/jetstream.conf");
+        let client = async_nats::connect(server.client_url()).await.unwrap();
+        let context = async_nats::jetstream::new(client);
+        context
+            .create_stream_and_wait("single", Duration::from_secs(1))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn publish_async_flush_acks() {
+        let server = nats_server::run_server("tests/configs/jetstream.conf");
+        let client = async_nats::connect(server.client_url()).await.unwrap();
+        let context = async_nats::jetstream::new(client);
+        context
+            .create_stream(stream::Config {
+                name: "events".to_string(),
+                subjects: vec!["events.>".to_string()],
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        for i in 0..50 {
+            context
+                .publish_async(format!("events.{i}"), "data".into())
+                .await
+                .unwrap();
+        }
+        // Clones share the pending acks.
+        context
+            .clone()
+            .publish_async("missing", "data".into())
+            .await
+            .unwrap();
+
+        let summary = context.flush_acks(Duration::from_secs(5)).await.unwrap();
+        assert_eq!(summary.acked, 50);
+        assert_eq!(summary.failed(), 1);
+
+        let summary = context.flush_acks(Duration::from_secs(5)).await.unwrap();
+        assert_eq!(summary.acked, 0);
+        assert_eq!(summary.failed(), 0);
+
+        let stream = context.get_stream("events").await.unwrap();
+        assert_eq!(stream.cached_info().state.messages, 50);
+    }
+
     #[cfg(not(target_os = "windows"))]
     #[tokio::test]
     async fn create_stream_with_replicas() {
@@ -1515,6 +1598,85 @@ mod jetstream {
         }
     }
 
+    #[tokio::test]
+    async fn push_ordered_missed_heartbeat() {
+        let server = nats_server::run_server("tests/configs/jetstream.conf");
+        let client = async_nats::connect(server.client_url()).await.unwrap();
+        let context = async_nats::jetstream::new(client);
+
+        let stream = context
+            .create_stream(stream::Config {
+                name: "events".into(),
+                subjects: vec!["events.>".into()],
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let err = stream
+            .create_consumer(consumer::push::OrderedConfig {
+                deliver_subject: "push".into(),
+                idle_heartbeat: Duration::from_millis(10),
+                ..Default::default()
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.kind(),
+            async_nats::jetstream::stream::ConsumerErrorKind::InvalidHeartbeat
+        );
+
+        let mut consumer: OrderedPushConsumer = stream
+            .create_consumer(consumer::push::OrderedConfig {
+                deliver_subject: "push".into(),
+                idle_heartbeat: Duration::from_millis(500),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            consumer.info().await.unwrap().config.idle_heartbeat,
+            Duration::from_millis(500)
+        );
+        let name = consumer.cached_info().name.clone();
+
+        let mut messages = consumer.messages().await.unwrap();
+        context
+            .publish("events.1", "data".into())
+            .await
+            .unwrap()
+            .await
+            .unwrap();
+        messages.next().await.unwrap().unwrap();
+
+        // Without the consumer no heartbeats arrive anymore.
+        stream.delete_consumer(&name).await.unwrap();
+        let err = tokio::time::timeout(Duration::from_secs(2), messages.next())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(
+            err.kind(),
+            async_nats::jetstream::consumer::push::OrderedErrorKind::MissingHeartbeat
+        );
+        assert_eq!(messages.missed_heartbeats(), 1);
+
+        // The consumer gets recreated and continues after the last message.
+        context
+            .publish("events.2", "data".into())
+            .await
+            .unwrap()
+            .await
+            .unwrap();
+        let message = tokio::time::timeout(Duration::from_secs(5), messages.next())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(message.info().unwrap().stream_sequence, 2);
+    }
+
     // test added just to be sure, that if messages have arrived to the stream already, we won't
     // miss them in AckPolicy::None setup.
     #[tokio::test]
@@ -13,7 +13,7 @@
 
 #[cfg(feature = "service")]
 mod service {
-    use std::{collections::HashMap, str::from_utf8};
+    use std::{collections::HashMap, str::from_utf8, time::Duration};
 
     use async_nats::service::{self, Info, ServiceExt, Stats};
     use futures::StreamExt;
@@ -552,6 +552,64 @@ mod service {
         }
     }
 
+    #[tokio::test]
+    async fn disable_endpoint() {
+        let server = nats_server::run_basic_server();
+        let client = async_nats::connect(server.client_url()).await.unwrap();
+
+        let service = client
+            .service_builder()
+            .start("service", "1.0.0")
+            .await
+            .unwrap();
+
+        let mut products = service.endpoint("products").await.unwrap();
+        let _orders = service.endpoint("orders").await.unwrap();
+        tokio::task::spawn(async move {
+            while let Some(request) = products.next().await {
+                request.respond(Ok("data".into())).await.unwrap();
+            }
+        });
+        client.request("products", "".into()).await.unwrap();
+
+        service.disable_endpoint("products").await.unwrap();
+        client.request("products", "".into()).await.unwrap_err();
+
+        let info: Info = serde_json::from_slice(
+            &client
+                .request("$SRV.INFO", "".into())
+                .await
+                .unwrap()
+                .payload,
+        )
+        .unwrap();
+        assert_eq!(info.endpoints.len(), 1);
+        assert_eq!(info.endpoints[0].name, "orders");
+
+        let stats: Stats = serde_json::from_slice(
+            &client
+                .request("$SRV.STATS", "".into())
+                .await
+                .unwrap()
+                .payload,
+        )
+        .unwrap();
+        let stats = stats
+            .endpoints
+            .iter()
+            .find(|endpoint| endpoint.name == "products")
+            .unwrap();
+        assert!(stats.disabled);
+        assert_eq!(stats.requests, 1);
+
+        service.enable_endpoint("products").await.unwrap();
+        client.request("products", "".into()).await.unwrap();
+        let stats = service.stats().await;
+        assert!(!stats["products"].disabled);
+
+        service.disable_endpoint("missing").await.unwrap_err();
+    }
+
     #[tokio::test]
     async fn stop() {
         let server = nats_server::run_basic_server();
@@ -570,6 +628,31 @@ mod service {
         assert!(endpoint.next().await.is_none());
     }
 
+    #[tokio::test]
+    async fn drain() {
+        let server = nats_server::run_basic_server();
+        let client = async_nats::connect(server.client_url()).await.unwrap();
+
+        let service = client
+            .service_builder()
+            .start("service", "1.0.0")
+            .await
+            .unwrap();
+
+        let mut endpoint = service.endpoint("products").await.unwrap();
+        assert!(!service.info().await.draining);
+
+        client.drain().await.unwrap();
+        assert!(service.info().await.draining);
+
+        // The endpoint is drained along with the connection.
+        let next = tokio::time::timeout(Duration::from_secs(5), endpoint.next())
+            .await
+            .unwrap();
+        assert!(next.is_none());
+        endpoint.stop().await.unwrap();
+    }
+
     #[tokio::test]
     #[cfg(not(target_os = "windows"))]
     async fn cross_clients_tests() {
@@ -608,4 +691,273 @@ mod service {
 
         Command::new("deno").args(["run", "-A", "--unstable", "https://raw.githubusercontent.com/nats-io/nats.deno/main/tests/helpers/service-check.ts", "--server", &server.client_url(), "--name", "cross"]).output().unwrap();
     }
+
+    #[tokio::test]
+    async fn request_full() {
+        let server = nats_server::run_basic_server();
+        let client = async_nats::connect(server.client_url()).await.unwrap();
+
+        let service = client
+            .service_builder()
+            .start("serviceA", "1.0.0")
+            .await
+            .unwrap();
+        let mut endpoint = service.endpoint("products").await.unwrap();
+        tokio::task::spawn(async move {
+            while let Some(request) = endpoint.next().await {
+                let response = if request.message.payload.as_ref() == b"fail" {
+                    Err(service::error::Error {
+                        code: 404,
+                        status: "not found".into(),
+                    })
+                } else {
+                    Ok(request.message.payload.clone())
+                };
+                request.respond(response).await.unwrap();
+            }
+        });
+
+        let payload = service::request_full(&client, "products", "data".into())
+            .await
+            .unwrap();
+        assert_eq!(payload, "data");
+
+        let err = service::request_full(&client, "products", "fail".into())
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.kind(),
+            service::RequestErrorKind::Service(service::error::Error {
+                code: 404,
+                status: "not found".into(),
+            })
+        );
+
+        let err = service::request_full(&client, "missing", "data".into())
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), service::RequestErrorKind::NoResponders);
+    }
+
+    #[tokio::test]
+    async fn max_request_size() {
+        let server = nats_server::run_basic_server();
+        let client = async_nats::connect(server.client_url()).await.unwrap();
+
+        let service = client
+            .service_builder()
+            .start("serviceA", "1.0.0")
+            .await
+            .unwrap();
+        let mut endpoint = service
+            .endpoint_builder()
+            .max_request_size(8)
+            .add("products")
+            .await
+            .unwrap();
+        tokio::task::spawn(async move {
+            while let Some(request) = endpoint.next().await {
+                assert!(request.message.payload.len() <= 8);
+                let payload = request.message.payload.clone();
+                request.respond(Ok(payload)).await.unwrap();
+            }
+        });
+
+        let err = service::request_full(&client, "products", "oversized payload".into())
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.kind(),
+            service::RequestErrorKind::Service(service::error::Error {
+                code: 413,
+                status: "Request Too Large".into(),
+            })
+        );
+
+        let payload = service::request_full(&client, "products", "small".into())
+            .await
+            .unwrap();
+        assert_eq!(payload, "small");
+
+        let stats = service.stats().await;
+        let stats = stats.get("products").unwrap();
+        assert_eq!(stats.requests, 2);
+        assert_eq!(stats.errors, 1);
+    }
+
+    #[tokio::test]
+    async fn prometheus_metrics() {
+        let server = nats_server::run_basic_server();
+        let client = async_nats::connect(server.client_url()).await.unwrap();
+
+        let service = client
+            .service_builder()
+            .start("serviceA", "1.0.0")
+            .await
+            .unwrap();
+        let mut endpoint = service.endpoint("products").await.unwrap();
+        tokio::task::spawn(async move {
+            while let Some(request) = endpoint.next().await {
+                request.respond(Ok("data".into())).await.unwrap();
+            }
+        });
+        client.request("products", "data".into()).await.unwrap();
+
+        let metrics = service.prometheus_metrics().await;
+        let labels = format!(
+            "service=\"serviceA\",id=\"{}\",endpoint=\"products\",subject=\"products\",queue_group=\"q\"",
+            service.info().await.id
+        );
+        assert!(metrics.contains("# TYPE nats_service_requests_total counter\n"));
+        assert!(metrics.contains(&format!("nats_service_requests_total{{{labels}}} 1\n")));
+        assert!(metrics.contains(&format!("nats_service_errors_total{{{labels}}} 0\n")));
+        assert!(metrics.contains("# TYPE nats_service_average_processing_seconds gauge\n"));
+    }
+
+    #[tokio::test]
+    async fn endpoint_stats_handler() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let server = nats_server::run_basic_server();
+        let client = async_nats::connect(server.client_url()).await.unwrap();
+
+        let service = client
+            .service_builder()
+            .stats_handler(|_, _| serde_json::json!("service"))
+            .start("serviceA", "1.0.0")
+            .await
+            .unwrap();
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut products = service
+            .endpoint_builder()
+            .stats_handler({
+                let calls = calls.clone();
+                move |stats| {
+                    let calls = calls.fetch_add(1, Ordering::Relaxed) + 1;
+                    serde_json::json!({ "calls": calls, "requests": stats.requests })
+                }
+            })
+            .add("products")
+            .await
+            .unwrap();
+        let _orders = service.endpoint("orders").await.unwrap();
+        tokio::task::spawn(async move {
+            while let Some(request) = products.next().await {
+                request.respond(Ok("data".into())).await.unwrap();
+            }
+        });
+
+        for _ in 0..3 {
+            client.request("products", "data".into()).await.unwrap();
+        }
+        // Called lazily, not on every request.
+        assert_eq!(calls.load(Ordering::Relaxed), 0);
+
+        let stats = client
+            .request("$SRV.STATS", "".into())
+            .await
+            .map(|message| serde_json::from_slice::<Stats>(&message.payload))
+            .unwrap()
+            .unwrap();
+        let data = |name: &str| {
+            stats
+                .endpoints
+                .iter()
+                .find(|endpoint| endpoint.name == name)
+                .unwrap()
+                .data
+                .clone()
+        };
+        assert_eq!(
+            data("products"),
+            Some(serde_json::json!({ "calls": 1, "requests": 3 }))
+        );
+        assert_eq!(data("orders"), Some(serde_json::json!("service")));
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+
+        assert_eq!(
+            service.stats().await.get("products").unwrap().data,
+            Some(serde_json::json!({ "calls": 1, "requests": 3 }))
+        );
+    }
+
+    #[tokio::test]
+    async fn endpoint_drain() {
+        let server = nats_server::run_basic_server();
+        let client = async_nats::connect(server.client_url()).await.unwrap();
+
+        let service = client
+            .service_builder()
+            .start("serviceA", "1.0.0")
+            .await
+            .unwrap();
+        let mut endpoint = service.endpoint("products").await.unwrap();
+
+        let inbox = client.new_inbox();
+        let mut responses = client.subscribe(inbox.clone()).await.unwrap();
+        for _ in 0..10 {
+            client
+                .publish_with_reply("products", inbox.clone(), "data".into())
+                .await
+                .unwrap();
+        }
+        client.flush().await.unwrap();
+
+        // Pending while requests are left, polling it sends the UNSUB.
+        let drained = endpoint.drain();
+        tokio::pin!(drained);
+        tokio::time::timeout(Duration::from_millis(500), &mut drained)
+            .await
+            .unwrap_err();
+
+        // Requests sent after the drain don't reach the endpoint.
+        let err = client.request("products", "data".into()).await.unwrap_err();
+        assert_eq!(err.kind(), async_nats::RequestErrorKind::NoResponders);
+
+        // The ones in flight before are all yielded, then the stream ends and the drain resolves.
+        let handle = async {
+            let mut handled = 0;
+            while let Some(request) = endpoint.next().await {
+                request.respond(Ok("done".into())).await.unwrap();
+                handled += 1;
+            }
+            handled
+        };
+        let (drained, handled) = tokio::time::timeout(
+            Duration::from_secs(5),
+            futures::future::join(drained, handle),
+        )
+        .await
+        .unwrap();
+        drained.unwrap();
+        assert_eq!(handled, 10);
+
+        for _ in 0..10 {
+            let response = responses.next().await.unwrap();
+            assert_eq!(response.payload, "done");
+        }
+    }
+
+    #[tokio::test]
+    async fn endpoint_matches() {
+        let server = nats_server::run_basic_server();
+        let client = async_nats::connect(server.client_url()).await.unwrap();
+
+        let service = client
+            .service_builder()
+            .start("serviceA", "1.0.0")
+            .await
+            .unwrap();
+        let created = service.endpoint("orders.*.created").await.unwrap();
+        let us = service.group("orders").endpoint("us.>").await.unwrap();
+
+        assert!(created.matches("orders.us.created"));
+        assert!(!created.matches("orders.us.shipped"));
+        assert!(us.matches("orders.us.created"));
+        assert!(us.matches("orders.us.east.shipped"));
+        assert!(!us.matches("orders.us"));
+        assert!(!us.matches("orders.eu.created"));
+    }
 }
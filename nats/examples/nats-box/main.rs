@@ -1,3 +1,8 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::PathBuf;
+use std::time::Duration;
+
 use quicli::prelude::*;
 use structopt::{clap::ArgGroup, StructOpt};
 
@@ -26,19 +31,82 @@ struct Cli {
 enum Command {
     /// The type of operation, can be one of pub, sub, qsub, req, reply.
     #[structopt(name = "pub", about = "Publishes a message to a given subject")]
-    Pub { subject: String, msg: String },
+    Pub {
+        subject: String,
+        msg: Option<String>,
+        #[structopt(long, help = "read the message from a file, or stdin for '-'")]
+        file: Option<PathBuf>,
+    },
     #[structopt(name = "sub", about = "Subscribes to a given subject")]
     Sub {
         subject: String,
         #[structopt(long, help = "parse message data as json")]
         json: bool,
+        #[structopt(long, help = "append raw message data to a file, or stdout for '-'")]
+        output: Option<PathBuf>,
     },
     #[structopt(name = "request", about = "Sends a request and waits on reply")]
-    Request { subject: String, msg: String },
+    Request {
+        subject: String,
+        msg: Option<String>,
+        #[structopt(long, help = "read the message from a file, or stdin for '-'")]
+        file: Option<PathBuf>,
+        #[structopt(long, help = "write raw response data to a file, or stdout for '-'")]
+        output: Option<PathBuf>,
+        #[structopt(long, default_value = "5", help = "seconds to wait for a response")]
+        timeout: u64,
+        #[structopt(
+            long,
+            default_value = "0",
+            help = "times to retry if there are no responders"
+        )]
+        retries: usize,
+    },
     #[structopt(name = "reply", about = "Listens for requests and sends the reply")]
     Reply { subject: String, resp: String },
 }
 
+/// Returns the payload given on the command line or read from `file`, which
+/// may be empty.
+fn read_payload(msg: Option<String>, file: Option<PathBuf>) -> io::Result<Vec<u8>> {
+    match (msg, file) {
+        (Some(_), Some(_)) => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "either a message or --file can be given, not both",
+        )),
+        (Some(msg), None) => Ok(msg.into_bytes()),
+        (None, Some(path)) => {
+            let mut payload = Vec::new();
+            if path.as_os_str() == "-" {
+                io::stdin().read_to_end(&mut payload)?;
+            } else {
+                File::open(path)?.read_to_end(&mut payload)?;
+            }
+            Ok(payload)
+        }
+        (None, None) => Ok(Vec::new()),
+    }
+}
+
+/// Opens the destination for raw message data, stdout for `-`.
+fn open_output(path: PathBuf) -> io::Result<Box<dyn Write>> {
+    if path.as_os_str() == "-" {
+        Ok(Box::new(io::stdout()))
+    } else {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Box::new(file))
+    }
+}
+
+/// Describes a payload for humans, without assuming it is UTF-8.
+fn describe(data: &[u8]) -> String {
+    match std::str::from_utf8(data) {
+        Ok("") => "<empty>".to_string(),
+        Ok(text) => format!("'{text}'"),
+        Err(_) => format!("<{} bytes of binary data>", data.len()),
+    }
+}
+
 fn main() -> CliResult {
     let args = Cli::from_args();
 
@@ -49,6 +117,14 @@ fn main() -> CliResult {
     } else {
         nats::Options::new()
     };
+    let opts = match &args.cmd {
+        Command::Request {
+            timeout, retries, ..
+        } => opts
+            .with_request_timeout(Some(Duration::from_secs(*timeout)))
+            .with_request_retries(*retries),
+        _ => opts,
+    };
 
     let nc = opts
         .with_name("nats-box rust example")
@@ -57,14 +133,26 @@ fn main() -> CliResult {
         .connect(args.server)?;
 
     match args.cmd {
-        Command::Pub { subject, msg } => {
-            nc.publish(&subject, &msg)?;
-            println!("Published to '{subject}': '{msg}'");
+        Command::Pub { subject, msg, file } => {
+            let payload = read_payload(msg, file)?;
+            nc.publish(&subject, &payload)?;
+            eprintln!("Published to '{subject}': {}", describe(&payload));
         }
-        Command::Sub { subject, json } => {
+        Command::Sub {
+            subject,
+            json,
+            output,
+        } => {
+            let mut output = output.map(open_output).transpose()?;
             let sub = nc.subscribe(&subject)?;
-            println!("Listening on '{subject}'");
+            eprintln!("Listening on '{subject}'");
             for msg in sub.messages() {
+                if let Some(output) = output.as_mut() {
+                    output.write_all(&msg.data)?;
+                    output.flush()?;
+                    eprintln!("Received {} on '{}'", describe(&msg.data), msg.subject);
+                    continue;
+                }
                 println!("Received a {msg:?}");
                 if json {
                     match serde_json::from_slice::<serde_json::Value>(&msg.data) {
@@ -74,10 +162,25 @@ fn main() -> CliResult {
                 }
             }
         }
-        Command::Request { subject, msg } => {
-            println!("Waiting on response for '{subject}'");
-            let resp = nc.request(&subject, msg)?;
-            println!("Response is {resp:?}");
+        Command::Request {
+            subject,
+            msg,
+            file,
+            output,
+            ..
+        } => {
+            let payload = read_payload(msg, file)?;
+            eprintln!("Waiting on response for '{subject}'");
+            let resp = nc.request(&subject, payload)?;
+            match output {
+                Some(path) => {
+                    let mut output = open_output(path)?;
+                    output.write_all(&resp.data)?;
+                    output.flush()?;
+                    eprintln!("Response is {}", describe(&resp.data));
+                }
+                None => println!("Response is {resp:?}"),
+            }
         }
         Command::Reply { subject, resp } => {
             let sub = nc.queue_subscribe(&subject, "rust-box")?;
@@ -18,12 +18,9 @@ fn main() -> std::io::Result<()> {
     };
 
     let sub = nc.subscribe(&subj)?;
-    nc.publish(&subj, serde_json::to_vec(&p)?)?;
+    nc.publish_json(&subj, &p)?;
 
-    let mut p2 = sub.iter().map(move |msg| {
-        let p: Person = serde_json::from_slice(&msg.data).unwrap();
-        p
-    });
+    let mut p2 = sub.iter().map(move |msg| msg.json::<Person>().unwrap());
     println!("received {:?}", p2.next().unwrap());
 
     Ok(())
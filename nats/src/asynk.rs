@@ -403,6 +403,11 @@ pub struct Message {
     /// using `JetStream`.
     #[doc(hidden)]
     pub double_acked: Arc<AtomicBool>,
+
+    /// Set if a warning should be logged when this message is dropped
+    /// without being acknowledged.
+    #[doc(hidden)]
+    pub ack_tracker: Option<Arc<crate::message::AckTracker>>,
 }
 
 impl From<crate::Message> for Message {
@@ -414,6 +419,7 @@ impl From<crate::Message> for Message {
             headers: sync.headers,
             client: sync.client,
             double_acked: sync.double_acked,
+            ack_tracker: sync.ack_tracker,
         }
     }
 }
@@ -469,6 +475,7 @@ impl Default for Message {
             headers: None,
             client: None,
             double_acked: Arc::new(AtomicBool::new(false)),
+            ack_tracker: None,
         }
     }
 }
@@ -0,0 +1,90 @@
+// Copyright 2020-2022 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::convert::TryFrom;
+
+use webpki::types::CertificateDer;
+use x509_parser::prelude::{FromDer, X509Certificate};
+
+/// An X.509 certificate presented by the server during the TLS handshake.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Certificate {
+    der: Vec<u8>,
+    subject: String,
+    issuer: String,
+}
+
+impl Certificate {
+    /// Parses a DER encoded certificate, returning `None` if it is malformed.
+    pub(crate) fn from_der(der: &CertificateDer<'_>) -> Option<Certificate> {
+        webpki::EndEntityCert::try_from(der).ok()?;
+        let (_, cert) = X509Certificate::from_der(der).ok()?;
+        Some(Certificate {
+            der: der.to_vec(),
+            subject: cert.subject().to_string(),
+            issuer: cert.issuer().to_string(),
+        })
+    }
+
+    /// The DER encoding of the certificate, e.g. for pinning by comparing
+    /// its hash against a known value.
+    pub fn der(&self) -> &[u8] {
+        &self.der
+    }
+
+    /// The certificate's subject, formatted as comma separated `KEY=value`
+    /// attributes in the order they appear in the certificate, for example
+    /// `O=Example, CN=nats.example.com`.
+    pub fn subject(&self) -> &str {
+        &self.subject
+    }
+
+    /// The certificate's issuer, formatted like [`Certificate::subject`].
+    pub fn issuer(&self) -> &str {
+        &self.issuer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::path::Path;
+
+    #[test]
+    fn parse_server_certificate() {
+        let path =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/configs/certs/server-cert.pem");
+        let certs = crate::auth_utils::load_certs(&path).unwrap();
+        let cert = Certificate::from_der(&certs[0]).unwrap();
+
+        assert_eq!(cert.der(), certs[0].as_ref());
+        assert_eq!(
+            cert.subject(),
+            "O=mkcert development certificate, OU=stjepan@MacBook-Pro-2.local (Stjepan Glavina)"
+        );
+        assert_eq!(
+            cert.issuer(),
+            "O=mkcert development CA, OU=stjepan@localhost (Stjepan Glavina), CN=mkcert stjepan@localhost (Stjepan Glavina)"
+        );
+    }
+
+    #[test]
+    fn reject_malformed_certificate() {
+        let path =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/configs/certs/server-cert.pem");
+        let certs = crate::auth_utils::load_certs(&path).unwrap();
+        let truncated = CertificateDer::from(&certs[0][..certs[0].len() / 2]);
+        assert!(Certificate::from_der(&truncated).is_none());
+    }
+}
@@ -16,6 +16,7 @@ use std::fmt;
 use std::io::prelude::*;
 use std::io::{self, BufReader, BufWriter, Error, ErrorKind};
 use std::mem;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
 use std::thread::{self, JoinHandle};
@@ -23,12 +24,18 @@ use std::time::{Duration, Instant};
 
 use crossbeam_channel as channel;
 use crossbeam_channel::RecvTimeoutError;
-use parking_lot::Mutex;
+use parking_lot::{Condvar, Mutex, MutexGuard};
 
+use crate::certificate::Certificate;
 use crate::connector::{Connector, NatsStream, ServerAddress};
-use crate::message::Message;
-use crate::proto::{self, ClientOp, ServerOp};
-use crate::{header::HeaderMap, inject_delay, inject_io_failure, Options, ServerInfo};
+use crate::message::{AckStats, Message};
+use crate::proto::{self, ClientOp, ServerOp, ValidationError};
+use crate::subscription::MessageSizeStats;
+#[cfg(feature = "unstable-testing")]
+use crate::testing;
+use crate::{
+    header::HeaderMap, inject_delay, inject_io_failure, Options, OutboundPolicy, ServerInfo,
+};
 
 const BUF_CAPACITY: usize = 32 * 1024;
 
@@ -42,11 +49,18 @@ pub(crate) struct State {
     write: Mutex<WriteState>,
     pub(crate) read: Mutex<ReadState>,
     meta: Mutex<MetaState>,
+    /// Notified when a writer is put back after reconnecting, or on close.
+    reconnected: Condvar,
+    /// Number of times an established connection was lost.
+    disconnects: AtomicUsize,
 }
 
 struct MetaState {
     /// Set of subjects that are currently muted.
     mutes: HashSet<u64>,
+
+    /// Subjects the server refused publishes to since the last reconnect.
+    denied_publishes: HashSet<String>,
 }
 
 struct WriteState {
@@ -73,6 +87,10 @@ pub(crate) struct ReadState {
     /// Current subscriptions.
     pub(crate) subscriptions: HashMap<u64, Subscription>,
 
+    /// Server subscriptions shared by local subscribers, keyed by the sid
+    /// registered with the server.
+    muxes: HashMap<u64, Mux>,
+
     /// Expected pongs and their notification channels.
     pongs: VecDeque<channel::Sender<()>>,
 
@@ -81,6 +99,9 @@ pub(crate) struct ReadState {
 
     /// Used for client side monitoring of connection health.
     pings_out: u8,
+
+    /// Payload sizes of the messages received on all subscriptions.
+    pub(crate) size_stats: MessageSizeStats,
 }
 
 /// A predicate used to preprocess messages for a subscription as they arrive over the wire.
@@ -94,6 +115,33 @@ pub(crate) struct Subscription {
     preprocess: Preprocessor,
     pub(crate) pending_messages_limit: Option<usize>,
     pub(crate) dropped_messages: usize,
+    pub(crate) size_stats: MessageSizeStats,
+
+    /// The server subscription this one is attached to, if it is multiplexed.
+    mux_sid: Option<u64>,
+}
+
+/// A server subscription fanned out to several local subscriptions.
+struct Mux {
+    subject: String,
+    queue_group: Option<String>,
+    members: Vec<u64>,
+    /// Index of the member next in line for a message, for queue groups.
+    next: usize,
+}
+
+impl Mux {
+    /// Returns the local subscriptions a message is handed to. Members of a
+    /// queue group take turns, as they would with server subscriptions of
+    /// their own, while everyone else gets a copy.
+    fn targets(&mut self) -> &[u64] {
+        if self.queue_group.is_none() || self.members.is_empty() {
+            return &self.members;
+        }
+        let index = self.next % self.members.len();
+        self.next = index + 1;
+        std::slice::from_ref(&self.members[index])
+    }
 }
 
 /// A NATS client.
@@ -105,12 +153,21 @@ pub struct Client {
     /// Server info provided by the last INFO message.
     pub(crate) server_info: Arc<Mutex<ServerInfo>>,
 
+    /// Certificate presented by the server on the most recent connection.
+    peer_certificate: Arc<Mutex<Option<Certificate>>>,
+
     /// Set to `true` if shutdown has been requested.
     shutdown: Arc<Mutex<bool>>,
 
     /// The options that this `Client` was created using.
     pub(crate) options: Arc<Options>,
 
+    /// Server list waiting to be picked up by the connector on reconnect.
+    server_updates: Arc<Mutex<Option<Vec<ServerAddress>>>>,
+
+    /// Latencies of server confirmed `JetStream` acks.
+    pub(crate) ack_stats: Arc<Mutex<AckStats>>,
+
     /// handler of client thread.
     pub(crate) client_thread: Arc<Mutex<Option<JoinHandle<()>>>>,
 
@@ -120,7 +177,14 @@ pub struct Client {
 
 impl Client {
     /// Creates a new client that will begin connecting in the background.
-    pub(crate) fn connect(urls: Vec<ServerAddress>, options: Options) -> io::Result<Client> {
+    ///
+    /// If `stream` is set, the first connection is established over it
+    /// instead of connecting to one of the URLs.
+    pub(crate) fn connect(
+        urls: Vec<ServerAddress>,
+        stream: Option<NatsStream>,
+        options: Options,
+    ) -> io::Result<Client> {
         // A channel for coordinating flushes.
         let (flush_kicker, flush_wanted) = channel::bounded(1);
 
@@ -133,7 +197,10 @@ impl Client {
             state: Arc::new(State {
                 meta: Mutex::new(MetaState {
                     mutes: HashSet::new(),
+                    denied_publishes: HashSet::new(),
                 }),
+                reconnected: Condvar::new(),
+                disconnects: AtomicUsize::new(0),
                 write: Mutex::new(WriteState {
                     writer: None,
                     flush_kicker,
@@ -142,14 +209,19 @@ impl Client {
                 }),
                 read: Mutex::new(ReadState {
                     subscriptions: HashMap::new(),
+                    muxes: HashMap::new(),
                     pongs: VecDeque::from(vec![pong_sender]),
                     last_active: Instant::now(),
                     pings_out: 0,
+                    size_stats: MessageSizeStats::default(),
                 }),
             }),
             server_info: Arc::new(Mutex::new(ServerInfo::default())),
+            peer_certificate: Arc::new(Mutex::new(None)),
             shutdown: Arc::new(Mutex::new(false)),
             options: Arc::new(options),
+            server_updates: Arc::new(Mutex::new(None)),
+            ack_stats: Arc::new(Mutex::new(AckStats::default())),
             client_thread: Arc::new(Mutex::new(None)),
             flush_thread: Arc::new(Mutex::new(None)),
         };
@@ -158,7 +230,10 @@ impl Client {
 
         // Connector for creating the initial connection and reconnecting when
         // it is broken.
-        let connector = Connector::new(urls, options.clone())?;
+        let mut connector = Connector::new(urls, options.clone(), client.server_updates.clone())?;
+        if let Some(stream) = stream {
+            connector.set_custom_stream(stream);
+        }
 
         // Spawn the client thread responsible for:
         // - Maintaining a connection to the server and reconnecting when it is
@@ -207,7 +282,23 @@ impl Client {
                 const PING_INTERVAL: Duration = Duration::from_secs(2 * 60);
                 const MAX_PINGS_OUT: u8 = 2;
 
+                #[cfg(feature = "unstable-testing")]
+                let ping_interval = client
+                    .options
+                    .testing
+                    .ping_interval
+                    .unwrap_or(PING_INTERVAL);
+                #[cfg(not(feature = "unstable-testing"))]
+                let ping_interval = PING_INTERVAL;
+
+                // With an auto flush interval the thread wakes up more often
+                // than it needs to check for a PING.
+                let auto_flush_interval = client.options.auto_flush_interval;
+                let tick = auto_flush_interval
+                    .map_or(ping_interval, |interval| interval.min(ping_interval));
+
                 let mut last = Instant::now().checked_sub(MIN_FLUSH_BETWEEN).unwrap();
+                let mut last_ping_check = Instant::now();
 
                 // Wait until at least one message is buffered.
                 loop {
@@ -215,7 +306,7 @@ impl Client {
                     if client.check_shutdown().is_err() {
                         break;
                     }
-                    match flush_wanted.recv_timeout(PING_INTERVAL) {
+                    match flush_wanted.recv_timeout(tick) {
                         Ok(_) => {
                             let since = last.elapsed();
                             if since < MIN_FLUSH_BETWEEN {
@@ -245,13 +336,32 @@ impl Client {
                             let mut write = client.state.write.lock();
                             let mut read = client.state.read.lock();
 
+                            // Flush messages published since the last tick.
+                            if auto_flush_interval.is_some() {
+                                if let Some(writer) = write.writer.as_mut() {
+                                    if !writer.buffer().is_empty() && writer.flush().is_err() {
+                                        writer.get_ref().shutdown();
+                                        write.writer = None;
+                                        read.pongs.clear();
+                                    }
+                                }
+                            }
+
+                            if last_ping_check.elapsed() < ping_interval {
+                                // NB see locking protocol for state.write and state.read
+                                drop(read);
+                                drop(write);
+                                continue;
+                            }
+                            last_ping_check = Instant::now();
+
                             if read.pings_out >= MAX_PINGS_OUT {
                                 if let Some(writer) = write.writer.as_mut() {
                                     writer.get_ref().shutdown();
                                 }
                                 write.writer = None;
                                 read.pongs.clear();
-                            } else if read.last_active.elapsed() > PING_INTERVAL {
+                            } else if read.last_active.elapsed() > ping_interval {
                                 read.pings_out += 1;
                                 read.pongs.push_back(write.flush_kicker.clone());
                                 // Send out a PING here.
@@ -290,6 +400,18 @@ impl Client {
         self.server_info.lock().clone()
     }
 
+    /// Retrieves the TLS certificate presented by the most recently connected
+    /// server.
+    pub(crate) fn peer_certificate(&self) -> Option<Certificate> {
+        self.peer_certificate.lock().clone()
+    }
+
+    /// Returns how many times an established connection was lost, which
+    /// tells whether one was lost since an earlier call.
+    pub(crate) fn disconnects(&self) -> usize {
+        self.state.disconnects.load(Ordering::Acquire)
+    }
+
     /// Makes a round trip to the server to ensure buffered messages reach it.
     pub(crate) fn flush(&self, timeout: Duration) -> io::Result<()> {
         let pong = {
@@ -347,9 +469,16 @@ impl Client {
 
         // Initiate shutdown process.
         if self.shutdown() {
+            // Wake up publishers waiting for a reconnect.
+            self.state.reconnected.notify_all();
+
             // Clear all subscriptions.
             let old_subscriptions = mem::take(&mut read.subscriptions);
-            for (sid, _) in old_subscriptions {
+            for (sid, subscription) in old_subscriptions {
+                if subscription.mux_sid.is_some() {
+                    continue;
+                }
+
                 // Send an UNSUB message and ignore errors.
                 if let Some(writer) = write.writer.as_mut() {
                     let max_msgs = None;
@@ -358,6 +487,13 @@ impl Client {
                 }
             }
             read.subscriptions.clear();
+            for (sid, _) in mem::take(&mut read.muxes) {
+                if let Some(writer) = write.writer.as_mut() {
+                    let max_msgs = None;
+                    proto::encode(writer, ClientOp::Unsub { sid, max_msgs }).ok();
+                    write.flush_kicker.try_send(()).ok();
+                }
+            }
 
             // Flush the writer in case there are buffered messages.
             if let Some(mut writer) = write.writer.as_mut() {
@@ -389,6 +525,19 @@ impl Client {
         !old
     }
 
+    /// Fails publishes to subjects the server has already denied.
+    fn check_publish_permission(&self, subject: &str) -> io::Result<()> {
+        if self.options.publish_permission_cache
+            && self.state.meta.lock().denied_publishes.contains(subject)
+        {
+            return Err(Error::new(
+                ErrorKind::PermissionDenied,
+                format!("permissions violation for publish to {:?}", subject),
+            ));
+        }
+        Ok(())
+    }
+
     fn check_shutdown(&self) -> io::Result<()> {
         if *self.shutdown.lock() {
             Err(Error::new(ErrorKind::NotConnected, "the client is closed"))
@@ -397,24 +546,48 @@ impl Client {
         }
     }
 
+    /// Replaces the configured server list. The new list is used starting
+    /// with the next reconnect.
+    pub(crate) fn update_servers(&self, urls: Vec<ServerAddress>) -> io::Result<()> {
+        // Check if the client is closed.
+        self.check_shutdown()?;
+
+        *self.server_updates.lock() = Some(urls);
+        Ok(())
+    }
+
     /// Subscribes to a subject.
     pub(crate) fn subscribe(
         &self,
         subject: &str,
         queue_group: Option<&str>,
     ) -> io::Result<(u64, channel::Receiver<Message>)> {
-        self.subscribe_with_preprocessor(subject, queue_group, Box::new(|_, _| false))
+        self.subscribe_with_preprocessor(
+            subject,
+            queue_group,
+            Box::new(|_, _| false),
+            self.options.local_subscription_mux,
+        )
     }
 
     /// Subscribe to a subject with a message preprocessor.
+    ///
+    /// If `mux` is set, the server subscription is shared with other local
+    /// subscribers to the same subject and queue group.
     pub(crate) fn subscribe_with_preprocessor(
         &self,
         subject: &str,
         queue_group: Option<&str>,
         message_processor: Preprocessor,
+        mux: bool,
     ) -> io::Result<(u64, channel::Receiver<Message>)> {
         inject_delay();
 
+        proto::validate_subject(subject)?;
+        if let Some(queue_group) = queue_group {
+            proto::validate_subject(queue_group)?;
+        }
+
         let mut write = self.state.write.lock();
         let mut read = self.state.read.lock();
 
@@ -425,8 +598,46 @@ impl Client {
         let sid = write.next_sid;
         write.next_sid += 1;
 
+        // A multiplexed subscription attaches to the server subscription of
+        // an earlier local subscriber, so its own sid is never sent to the
+        // server. The first one registers a server subscription under a sid
+        // of its own.
+        let mut server_sid = Some(sid);
+        let mut mux_sid = None;
+        if mux {
+            let existing = read
+                .muxes
+                .iter()
+                .find(|(_, mux)| {
+                    mux.subject == subject && mux.queue_group.as_deref() == queue_group
+                })
+                .map(|(mux_sid, _)| *mux_sid);
+            let shared_sid = if let Some(existing) = existing {
+                server_sid = None;
+                existing
+            } else {
+                let shared_sid = write.next_sid;
+                write.next_sid += 1;
+                read.muxes.insert(
+                    shared_sid,
+                    Mux {
+                        subject: subject.to_string(),
+                        queue_group: queue_group.map(ToString::to_string),
+                        members: Vec::new(),
+                        next: 0,
+                    },
+                );
+                server_sid = Some(shared_sid);
+                shared_sid
+            };
+            if let Some(mux) = read.muxes.get_mut(&shared_sid) {
+                mux.members.push(sid);
+            }
+            mux_sid = Some(shared_sid);
+        }
+
         // If connected, send a SUB operation.
-        if let Some(writer) = write.writer.as_mut() {
+        if let (Some(sid), Some(writer)) = (server_sid, write.writer.as_mut()) {
             let op = ClientOp::Sub {
                 subject,
                 queue_group,
@@ -447,6 +658,8 @@ impl Client {
                 preprocess: message_processor,
                 pending_messages_limit: None,
                 dropped_messages: 0,
+                size_stats: MessageSizeStats::default(),
+                mux_sid,
             },
         );
 
@@ -469,12 +682,25 @@ impl Client {
         // Inject random delays when testing.
         inject_delay();
 
+        proto::validate_subject(new_subject)?;
+
         let mut write = self.state.write.lock();
         let mut read = self.state.read.lock();
 
         // Check if the client is closed.
         self.check_shutdown()?;
 
+        if read
+            .subscriptions
+            .get(&old_sid)
+            .map_or(false, |subscription| subscription.mux_sid.is_some())
+        {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "cannot resubscribe a multiplexed subscription",
+            ));
+        }
+
         let subscription = read
             .subscriptions
             .remove(&old_sid)
@@ -526,7 +752,9 @@ impl Client {
         let mut read = self.state.read.lock();
 
         // Remove the subscription from the map.
-        if read.subscriptions.remove(&sid).is_none() {
+        let subscription = if let Some(subscription) = read.subscriptions.remove(&sid) {
+            subscription
+        } else {
             // already unsubscribed
 
             // NB see locking protocol for state.write and state.read
@@ -534,7 +762,28 @@ impl Client {
             drop(write);
 
             return Ok(());
-        }
+        };
+
+        // A multiplexed subscription only gives up the server subscription
+        // once its last local subscriber is gone.
+        let sid = match subscription.mux_sid {
+            Some(mux_sid) => {
+                let remaining = read.muxes.get_mut(&mux_sid).map_or(0, |mux| {
+                    mux.members.retain(|member| *member != sid);
+                    mux.members.len()
+                });
+                if remaining > 0 {
+                    // NB see locking protocol for state.write and state.read
+                    drop(read);
+                    drop(write);
+
+                    return Ok(());
+                }
+                read.muxes.remove(&mux_sid);
+                mux_sid
+            }
+            None => sid,
+        };
 
         // Send an UNSUB message.
         if let Some(writer) = write.writer.as_mut() {
@@ -561,18 +810,14 @@ impl Client {
         // Inject random delays when testing.
         inject_delay();
 
-        let server_info = self.server_info.lock();
-        if headers.is_some() && !server_info.headers {
-            return Err(Error::new(
-                ErrorKind::InvalidInput,
-                "the server does not support headers",
-            ));
-        }
-        drop(server_info);
+        validate_publish(subject, reply_to, headers)?;
+        self.check_publish_size(headers, msg.len())?;
 
         // Check if the client is closed.
         self.check_shutdown()?;
 
+        self.check_publish_permission(subject)?;
+
         let op = match headers {
             Some(headers) if !headers.is_empty() => ClientOp::Hpub {
                 subject,
@@ -587,8 +832,7 @@ impl Client {
             },
         };
 
-        let mut write = self.state.write.lock();
-
+        let mut write = self.lock_for_publish()?;
         let written = write.buffer.written;
 
         match write.writer.as_mut() {
@@ -620,6 +864,153 @@ impl Client {
         }
     }
 
+    /// Publishes a message whose payload of `len` bytes is read from
+    /// `payload` while it is written to the server, rather than held in
+    /// memory. Other publishes wait until the whole payload has been copied.
+    ///
+    /// If the source fails or ends early, the message cannot be completed
+    /// and the connection is dropped, so the client reconnects. While
+    /// disconnected, the payload is read fully into the reconnect buffer.
+    pub(crate) fn publish_streamed(
+        &self,
+        subject: &str,
+        reply_to: Option<&str>,
+        headers: Option<&HeaderMap>,
+        payload: &mut dyn Read,
+        len: usize,
+    ) -> io::Result<()> {
+        // Inject random delays when testing.
+        inject_delay();
+
+        validate_publish(subject, reply_to, headers)?;
+        self.check_publish_size(headers, len)?;
+
+        // Check if the client is closed.
+        self.check_shutdown()?;
+
+        self.check_publish_permission(subject)?;
+
+        let headers = headers.filter(|headers| !headers.is_empty());
+        let mut write = self.lock_for_publish()?;
+        let written = write.buffer.written;
+
+        match write.writer.as_mut() {
+            None => {
+                // If reconnecting, the message has to be buffered whole.
+                let mut data = Vec::with_capacity(len);
+                payload.take(len as u64).read_to_end(&mut data)?;
+                if data.len() < len {
+                    return Err(proto::short_payload(len, data.len() as u64));
+                }
+                let op = match headers {
+                    Some(headers) => ClientOp::Hpub {
+                        subject,
+                        reply_to,
+                        payload: &data,
+                        headers,
+                    },
+                    None => ClientOp::Pub {
+                        subject,
+                        reply_to,
+                        payload: &data,
+                    },
+                };
+                proto::encode(&mut write.buffer, op)?;
+                write.buffer.flush()?;
+                Ok(())
+            }
+            Some(writer) => {
+                assert_eq!(written, 0);
+
+                let res =
+                    proto::encode_streamed(&mut *writer, subject, reply_to, headers, payload, len);
+
+                // A partially written message leaves the protocol stream
+                // corrupted, so the connection has to go.
+                if res.is_err() {
+                    writer.get_ref().shutdown();
+                    write.writer = None;
+
+                    // NB see locking protocol for state.write and state.read
+                    let mut read = self.state.read.lock();
+                    read.pongs.clear();
+                }
+
+                write.flush_kicker.try_send(()).ok();
+
+                res
+            }
+        }
+    }
+
+    /// Fails publishes the server would reject, either for using headers it
+    /// does not support or for exceeding the max payload from its INFO.
+    fn check_publish_size(&self, headers: Option<&HeaderMap>, len: usize) -> io::Result<()> {
+        let server_info = self.server_info.lock();
+        if headers.is_some() && !server_info.headers {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "the server does not support headers",
+            ));
+        }
+
+        let size = headers
+            .filter(|headers| !headers.is_empty())
+            .map_or(0, |headers| headers.to_bytes().len())
+            + len;
+        if server_info.max_payload > 0 && size > server_info.max_payload {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                ValidationError::PayloadTooLarge {
+                    size,
+                    max_payload: server_info.max_payload,
+                },
+            ));
+        }
+        Ok(())
+    }
+
+    /// Locks the write state for publishing, waiting for the client to
+    /// reconnect or failing as the outbound policy says if it is
+    /// disconnected.
+    fn lock_for_publish(&self) -> io::Result<MutexGuard<'_, WriteState>> {
+        let mut write = self.state.write.lock();
+
+        if write.writer.is_none() {
+            match self.options.outbound_policy {
+                OutboundPolicy::Buffer => {}
+                OutboundPolicy::ErrorImmediately => {
+                    return Err(Error::new(
+                        ErrorKind::NotConnected,
+                        "the client is disconnected",
+                    ));
+                }
+                OutboundPolicy::Block(timeout) => {
+                    let deadline = Instant::now() + timeout;
+                    while write.writer.is_none() {
+                        self.check_shutdown()?;
+                        if self
+                            .state
+                            .reconnected
+                            .wait_until(&mut write, deadline)
+                            .timed_out()
+                        {
+                            break;
+                        }
+                    }
+                    if write.writer.is_none() {
+                        return Err(Error::new(
+                            ErrorKind::TimedOut,
+                            "timed out waiting for the client to reconnect",
+                        ));
+                    }
+                }
+            }
+        }
+
+        Ok(write)
+    }
+
     /// Attempts to publish a message without blocking.
     ///
     /// This only works when the write buffer has enough space to encode the
@@ -631,11 +1022,22 @@ impl Client {
         headers: Option<&HeaderMap>,
         msg: &[u8],
     ) -> Option<io::Result<()>> {
+        if let Err(err) = validate_publish(subject, reply_to, headers) {
+            return Some(Err(err));
+        }
+        if let Err(err) = self.check_publish_size(headers, msg.len()) {
+            return Some(Err(err));
+        }
+
         // Check if the client is closed.
         if let Err(e) = self.check_shutdown() {
             return Some(Err(e));
         }
 
+        if let Err(e) = self.check_publish_permission(subject) {
+            return Some(Err(e));
+        }
+
         // Estimate how many bytes the message will consume when written into
         // the stream. We must make a conservative guess: it's okay to
         // overestimate but not to underestimate.
@@ -665,11 +1067,20 @@ impl Client {
         let mut write = self.state.write.try_lock()?;
 
         match write.writer.as_mut() {
-            None => {
-                // If reconnecting, write into the buffer.
-                let res = proto::encode(&mut write.buffer, op).and_then(|_| write.buffer.flush());
-                Some(res)
-            }
+            None => match self.options.outbound_policy {
+                OutboundPolicy::Buffer => {
+                    // If reconnecting, write into the buffer.
+                    let res =
+                        proto::encode(&mut write.buffer, op).and_then(|_| write.buffer.flush());
+                    Some(res)
+                }
+                OutboundPolicy::ErrorImmediately => Some(Err(Error::new(
+                    ErrorKind::NotConnected,
+                    "the client is disconnected",
+                ))),
+                // Publishing would have to wait for the reconnect.
+                OutboundPolicy::Block(_) => None,
+            },
             Some(mut writer) => {
                 // Check if there's enough space in the buffer to encode the
                 // whole message.
@@ -720,6 +1131,7 @@ impl Client {
                     // If the client stopped gracefully, return.
                     return Ok(());
                 } else {
+                    self.state.disconnects.fetch_add(1, Ordering::AcqRel);
                     connector.get_options().disconnect_callback.call();
                     self.state.write.lock().writer = None;
                 }
@@ -764,6 +1176,10 @@ impl Client {
 
         // Restart subscriptions that existed before the last reconnect.
         for (sid, subscription) in &read.subscriptions {
+            if subscription.mux_sid.is_some() {
+                continue;
+            }
+
             // Send a SUB operation to the server.
             proto::encode(
                 &mut writer,
@@ -774,6 +1190,19 @@ impl Client {
                 },
             )?;
         }
+        for (sid, mux) in &read.muxes {
+            proto::encode(
+                &mut writer,
+                ClientOp::Sub {
+                    subject: mux.subject.as_str(),
+                    queue_group: mux.queue_group.as_deref(),
+                    sid: *sid,
+                },
+            )?;
+        }
+
+        // Permissions may have changed while disconnected.
+        self.state.meta.lock().denied_publishes.clear();
 
         // Take out expected PONGs.
         let pongs = mem::take(&mut read.pongs);
@@ -785,9 +1214,13 @@ impl Client {
         writer.write_all(buffered)?;
         writer.flush()?;
 
-        // All good, continue with this connection.
+        // All good, continue with this connection. The server info must be
+        // stored before completing PONGs below, because `connect` returns as
+        // soon as the first one completes.
         *self.server_info.lock() = server_info;
+        *self.peer_certificate.lock() = writer.get_ref().peer_certificate();
         write.writer = Some(writer);
+        self.state.reconnected.notify_all();
 
         // Complete PONGs because the connection is healthy.
         for p in pongs {
@@ -814,6 +1247,49 @@ impl Client {
         read.last_active = Instant::now();
     }
 
+    /// Hands a message to a subscription, applying its preprocessor and
+    /// slow consumer limit.
+    fn deliver(
+        &self,
+        connector: &Connector,
+        sid: u64,
+        subscription: &mut Subscription,
+        size_stats: &mut MessageSizeStats,
+        msg: Message,
+    ) {
+        // Preprocess and drop the message from the buffer if it the predicate
+        // returns true
+        let preprocess = &subscription.preprocess;
+        if preprocess(sid, &msg) {
+            return;
+        }
+
+        size_stats.record(msg.data.len());
+        subscription.size_stats.record(msg.data.len());
+
+        //check if subscription has set limits for slow consumers
+        if let Some(pending_messages_limit) = subscription.pending_messages_limit {
+            if pending_messages_limit <= subscription.messages.len() {
+                connector.get_options().error_callback.call(
+                    self,
+                    Error::new(
+                        ErrorKind::Other,
+                        format!(
+                            "slow consumer detected for subscription on subject {}. dropping messages",
+                            subscription.subject
+                        ),
+                    ),
+                );
+                subscription.dropped_messages += 1;
+                return;
+            }
+        }
+
+        // Send a message or drop it if the channel is
+        // disconnected or full.
+        subscription.messages.send(msg).unwrap();
+    }
+
     /// Reads messages from the server and dispatches them to subscribers.
     fn dispatch(&self, mut reader: impl BufRead, connector: &mut Connector) -> io::Result<()> {
         // Handle operations received from the server.
@@ -825,6 +1301,13 @@ impl Client {
                 break;
             }
 
+            #[cfg(feature = "unstable-testing")]
+            if matches!(op, ServerOp::Pong)
+                && !self.options.testing.on_pong(testing::PongEvent::Received)
+            {
+                continue;
+            }
+
             // Track activity.
             self.update_activity();
 
@@ -838,6 +1321,11 @@ impl Client {
                 }
 
                 ServerOp::Ping => {
+                    #[cfg(feature = "unstable-testing")]
+                    if !self.options.testing.on_pong(testing::PongEvent::Sent) {
+                        continue;
+                    }
+
                     // Respond with a PONG if connected.
                     let mut write = self.state.write.lock();
                     let read = self.state.read.lock();
@@ -887,48 +1375,33 @@ impl Client {
                     }
 
                     let mut read = self.state.read.lock();
-
-                    // Send the message to matching subscription.
-                    if let Some(subscription) = read.subscriptions.get(&sid) {
-                        let msg = Message {
-                            subject,
-                            reply: reply_to,
-                            data: payload,
-                            headers: None,
-                            client: Some(self.clone()),
-                            double_acked: Default::default(),
-                        };
-
-                        // Preprocess and drop the message from the buffer if it the predicate
-                        // returns true
-                        let preprocess = &subscription.preprocess;
-                        if preprocess(sid, &msg) {
-                            continue;
+                    let ReadState {
+                        subscriptions,
+                        muxes,
+                        size_stats,
+                        ..
+                    } = &mut *read;
+
+                    let msg = Message {
+                        subject,
+                        reply: reply_to,
+                        data: payload,
+                        headers: None,
+                        client: Some(self.clone()),
+                        double_acked: Default::default(),
+                        ack_tracker: None,
+                    };
+
+                    // Send the message to matching subscriptions, fanning it out
+                    // when the server subscription is shared.
+                    let targets = match muxes.get_mut(&sid) {
+                        Some(mux) => mux.targets(),
+                        None => std::slice::from_ref(&sid),
+                    };
+                    for target in targets {
+                        if let Some(subscription) = subscriptions.get_mut(target) {
+                            self.deliver(connector, *target, subscription, size_stats, msg.clone());
                         }
-
-                        //check if subscription has set limits for slow consumers
-                        if let Some(pending_messages_limit) = subscription.pending_messages_limit {
-                            if pending_messages_limit <= subscription.messages.len() {
-                                connector.get_options().error_callback.call(
-                                    self,
-                                    io::Error::new(
-                                        ErrorKind::Other,
-                                        format!(
-                                            "slow consumer detected for subscription on subject {}. dropping messages",
-                                            subscription.subject
-                                        ),
-                                    ),
-                                );
-                                read.subscriptions
-                                    .entry(sid)
-                                    .and_modify(|sub| sub.dropped_messages += 1);
-                                continue;
-                            }
-                        }
-
-                        // Send a message or drop it if the channel is
-                        // disconnected or full.
-                        subscription.messages.send(msg).unwrap();
                     }
                 }
 
@@ -944,48 +1417,51 @@ impl Client {
                         continue;
                     }
 
-                    let read = self.state.read.lock();
-                    // Send the message to matching subscription.
-                    if let Some(subscription) = read.subscriptions.get(&sid) {
-                        let msg = Message {
-                            subject,
-                            reply: reply_to,
-                            data: payload,
-                            headers: Some(headers),
-                            client: Some(self.clone()),
-                            double_acked: Default::default(),
-                        };
-
-                        // Preprocess and drop the message from the buffer if it the predicate
-                        // returns true
-                        let preprocess = &subscription.preprocess;
-                        if preprocess(sid, &msg) {
-                            continue;
-                        }
-
-                        //check if subscription has set limits for slow consumers
-                        if let Some(pending_messages_limit) = subscription.pending_messages_limit {
-                            if pending_messages_limit <= subscription.messages.len() {
-                                connector.get_options().error_callback.call(
-                                    self,
-                                    io::Error::new(
-                                        ErrorKind::Other,
-                                        format!(
-                                            "slow consumer detected for subscription on subject {}. dropping messages",
-                                            subscription.subject
-                                        ),
-                                    ),
-                                );
-                                continue;
-                            }
+                    let mut read = self.state.read.lock();
+                    let ReadState {
+                        subscriptions,
+                        muxes,
+                        size_stats,
+                        ..
+                    } = &mut *read;
+
+                    let msg = Message {
+                        subject,
+                        reply: reply_to,
+                        data: payload,
+                        headers: Some(headers),
+                        client: Some(self.clone()),
+                        double_acked: Default::default(),
+                        ack_tracker: None,
+                    };
+
+                    // Send the message to matching subscriptions, fanning it out
+                    // when the server subscription is shared.
+                    let targets = match muxes.get_mut(&sid) {
+                        Some(mux) => mux.targets(),
+                        None => std::slice::from_ref(&sid),
+                    };
+                    for target in targets {
+                        if let Some(subscription) = subscriptions.get_mut(target) {
+                            self.deliver(connector, *target, subscription, size_stats, msg.clone());
                         }
-                        // Send a message or drop it if the channel is
-                        // disconnected or full.
-                        subscription.messages.send(msg).unwrap();
                     }
                 }
 
                 ServerOp::Err(msg) => {
+                    if self.options.publish_permission_cache {
+                        if let Some(subject) = msg
+                            .strip_prefix("Permissions Violation for Publish to ")
+                            .map(|subject| subject.trim_matches('"'))
+                        {
+                            self.state
+                                .meta
+                                .lock()
+                                .denied_publishes
+                                .insert(subject.to_string());
+                        }
+                    }
+
                     connector
                         .get_options()
                         .error_callback
@@ -1002,6 +1478,22 @@ impl Client {
     }
 }
 
+/// Rejects publish arguments that would corrupt the protocol stream.
+fn validate_publish(
+    subject: &str,
+    reply_to: Option<&str>,
+    headers: Option<&HeaderMap>,
+) -> io::Result<()> {
+    proto::validate_subject(subject)?;
+    if let Some(reply_to) = reply_to {
+        proto::validate_subject(reply_to)?;
+    }
+    if let Some(headers) = headers {
+        proto::validate_headers(headers)?;
+    }
+    Ok(())
+}
+
 impl fmt::Debug for Client {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
         f.debug_struct("Client").finish()
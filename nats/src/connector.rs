@@ -13,8 +13,10 @@
 
 use lazy_static::__Deref;
 use parking_lot::{Mutex, MutexGuard};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::convert::TryFrom;
+use std::error;
+use std::fmt;
 use std::io::prelude::*;
 use std::io::{self, BufReader, Error, ErrorKind};
 use std::net::{Shutdown, SocketAddr, TcpStream, ToSocketAddrs};
@@ -25,11 +27,43 @@ use std::time::Duration;
 use url::{Host, Url};
 
 use crate::auth_utils;
+use crate::certificate::Certificate;
 use crate::proto::{self, ClientOp, ServerOp};
 use crate::rustls::{ClientConfig, ClientConnection};
 use crate::secure_wipe::SecureString;
+use crate::srv::{self, SrvResolver};
 use crate::{connect::ConnectInfo, inject_io_failure, AuthStyle, Options, ServerInfo};
 
+/// An error establishing a connection that is not caused by the TCP
+/// connection itself.
+///
+/// It is returned wrapped in an `io::Error` and can be recovered with
+/// `into_inner` and `downcast`, which tells a server rejecting the TLS
+/// handshake apart from a server that could not be reached at all.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ConnectError {
+    /// The TCP connection was established, but the TLS handshake with the
+    /// server failed, for example because its certificate is not trusted.
+    TlsHandshake(Error),
+}
+
+impl fmt::Display for ConnectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConnectError::TlsHandshake(err) => write!(f, "TLS handshake failed: {err}"),
+        }
+    }
+}
+
+impl error::Error for ConnectError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            ConnectError::TlsHandshake(err) => Some(err),
+        }
+    }
+}
+
 /// Maintains a list of servers and establishes connections.
 ///
 /// Clients use this helper to hold a list of known servers discovered through
@@ -39,26 +73,43 @@ pub(crate) struct Connector {
     /// A map of servers and number of connect attempts.
     attempts: HashMap<ServerAddress, usize>,
 
+    /// Servers learned through INFO messages rather than configured by the user.
+    discovered: HashSet<ServerAddress>,
+
+    /// A replacement server list requested while the client is running.
+    /// It is applied before the next connect attempt.
+    server_updates: Arc<Mutex<Option<Vec<ServerAddress>>>>,
+
     /// Configured options for establishing connections.
     options: Arc<Options>,
 
     /// TLS config.
     tls_config: Arc<ClientConfig>,
+
+    /// A user provided stream to establish the first connection over.
+    custom_stream: Option<NatsStream>,
+
+    /// Set once a user provided stream has been used, after which the
+    /// connector cannot reconnect.
+    custom_stream_used: bool,
 }
 
 fn configure_tls(options: &Arc<Options>) -> Result<ClientConfig, Error> {
     let mut root_store = rustls::RootCertStore::empty();
 
-    // load native system certs only if user did not specify them
+    // load native system certs only if user did not specify them, falling
+    // back to the bundled webpki roots if there are none to be found
     if options.tls_client_config.is_some() || options.certificates.is_empty() {
-        let native_certs = rustls_native_certs::load_native_certs().map_err(|err| {
-            io::Error::new(
-                ErrorKind::Other,
-                format!("could not load platform certs: {err}"),
-            )
-        })?;
+        match rustls_native_certs::load_native_certs() {
+            Ok(native_certs) => {
+                root_store.add_parsable_certificates(native_certs);
+            }
+            Err(err) => log::warn!("could not load platform certs: {err}"),
+        }
 
-        root_store.add_parsable_certificates(native_certs);
+        if root_store.is_empty() {
+            root_store.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+        }
     }
 
     if let Some(config) = &options.tls_client_config {
@@ -106,23 +157,66 @@ fn configure_tls(options: &Arc<Options>) -> Result<ClientConfig, Error> {
 
 impl Connector {
     /// Creates a new connector with the URLs and options.
-    pub(crate) fn new(urls: Vec<ServerAddress>, options: Arc<Options>) -> io::Result<Connector> {
+    ///
+    /// A list stored into `server_updates` replaces the configured URLs before
+    /// the next connect attempt.
+    pub(crate) fn new(
+        urls: Vec<ServerAddress>,
+        options: Arc<Options>,
+        server_updates: Arc<Mutex<Option<Vec<ServerAddress>>>>,
+    ) -> io::Result<Connector> {
         let tls_config = configure_tls(&options)?;
 
         let connector = Connector {
             attempts: urls.into_iter().map(|url| (url, 0)).collect(),
+            discovered: HashSet::new(),
+            server_updates,
             options,
             tls_config: Arc::new(tls_config),
+            custom_stream: None,
+            custom_stream_used: false,
         };
 
         Ok(connector)
     }
 
-    /// Adds an URL to the list of servers.
+    /// Makes the first connection over the given stream instead of
+    /// connecting to one of the servers.
+    pub(crate) fn set_custom_stream(&mut self, stream: NatsStream) {
+        self.custom_stream = Some(stream);
+    }
+
+    /// Adds an URL discovered through an INFO message to the list of servers,
+    /// unless [`Options::with_no_failover`] is set.
     pub(crate) fn add_server(&mut self, url: ServerAddress) {
+        if self.options.no_failover {
+            return;
+        }
+        if !self.attempts.contains_key(&url) {
+            self.discovered.insert(url.clone());
+        }
         self.attempts.insert(url, 0);
     }
 
+    /// Replaces the configured servers with the most recently requested list,
+    /// if there is one. Servers discovered through INFO messages are kept, and
+    /// servers present in both lists keep their connect attempt counters.
+    fn apply_server_updates(&mut self) {
+        let urls = match self.server_updates.lock().take() {
+            Some(urls) => urls,
+            None => return,
+        };
+
+        let discovered = &self.discovered;
+        self.attempts
+            .retain(|server, _| urls.contains(server) || discovered.contains(server));
+
+        for url in urls {
+            self.discovered.remove(&url);
+            self.attempts.entry(url).or_insert(0);
+        }
+    }
+
     pub(crate) fn get_options(&self) -> Arc<Options> {
         self.options.clone()
     }
@@ -161,7 +255,21 @@ impl Connector {
         // fail.
         let mut last_err = Error::new(ErrorKind::AddrNotAvailable, "no socket addresses");
 
+        if let Some(stream) = self.custom_stream.take() {
+            self.custom_stream_used = true;
+            return self.connect_custom(stream);
+        }
+        if self.custom_stream_used {
+            return Err(Error::new(
+                ErrorKind::NotConnected,
+                "cannot reconnect a connection established over a custom stream",
+            ));
+        }
+
         loop {
+            // Pick up a server list set through `Connection::update_servers`.
+            self.apply_server_updates();
+
             // Shuffle the list of servers.
             let mut servers = self.get_servers()?;
             fastrand::shuffle(&mut servers);
@@ -174,26 +282,21 @@ impl Connector {
                 let sleep_duration = self.options.reconnect_delay_callback.call(*reconnects);
                 *reconnects += 1;
 
-                let lookup_res = server.socket_addrs();
-
-                let mut addrs = match lookup_res {
-                    Ok(addrs) => addrs.collect::<Vec<_>>(),
+                let addrs = match self.resolve(server) {
+                    Ok(addrs) => addrs,
                     Err(err) => {
                         last_err = err;
                         continue;
                     }
                 };
 
-                // Shuffle the resolved socket addresses.
-                fastrand::shuffle(&mut addrs);
-
-                for addr in addrs {
+                for (addr, target) in addrs {
                     // Sleep for some time if this is not the first connection
                     // attempt for this server.
                     thread::sleep(sleep_duration);
 
                     // Try connecting to this address.
-                    let res = self.connect_addr(addr, server);
+                    let res = self.connect_addr(addr, &target);
 
                     // Check if connecting worked out.
                     let (server_info, stream) = match res {
@@ -221,6 +324,63 @@ impl Connector {
         }
     }
 
+    /// Resolves a server into the socket addresses to try, in order, along
+    /// with the address of the server each of them belongs to.
+    ///
+    /// With SRV discovery enabled, SRV names are expanded into their targets
+    /// here, so they are looked up again on every connect attempt.
+    fn resolve(&self, server: &ServerAddress) -> io::Result<Vec<(SocketAddr, ServerAddress)>> {
+        if !self.options.srv_discovery || !srv::is_srv_name(server.host()) {
+            let mut addrs = server.socket_addrs()?.collect::<Vec<_>>();
+
+            // Shuffle the resolved socket addresses.
+            fastrand::shuffle(&mut addrs);
+
+            return Ok(addrs
+                .into_iter()
+                .map(|addr| (addr, server.clone()))
+                .collect());
+        }
+
+        let records = match &self.options.srv_resolver {
+            Some(resolver) => resolver.resolve_srv(server.host()),
+            None => srv::SystemResolver.resolve_srv(server.host()),
+        }?;
+
+        let mut resolved = Vec::new();
+        let mut last_err = Error::new(
+            ErrorKind::NotFound,
+            format!("no SRV targets found for {}", server.host()),
+        );
+        for record in srv::order(records) {
+            let mut url = server.0.clone();
+            let target = record.target.trim_end_matches('.');
+            if url.set_host(Some(target)).is_err() || url.set_port(Some(record.port)).is_err() {
+                last_err = Error::new(
+                    ErrorKind::InvalidData,
+                    format!("invalid SRV target: {target}"),
+                );
+                continue;
+            }
+            let target = ServerAddress(url);
+
+            match target.socket_addrs() {
+                Ok(addrs) => {
+                    let mut addrs = addrs.collect::<Vec<_>>();
+                    fastrand::shuffle(&mut addrs);
+                    resolved.extend(addrs.into_iter().map(|addr| (addr, target.clone())));
+                }
+                Err(err) => last_err = err,
+            }
+        }
+
+        if resolved.is_empty() {
+            Err(last_err)
+        } else {
+            Ok(resolved)
+        }
+    }
+
     /// Attempts to establish a connection to a single socket address.
     fn connect_addr(
         &self,
@@ -233,26 +393,11 @@ impl Connector {
         // Connect to the remote socket.
         let mut stream = TcpStream::connect(addr)?;
         stream.set_nodelay(true)?;
+        if let Some(keepalive) = self.options.tcp_keepalive {
+            set_keepalive(&stream, keepalive)?;
+        }
 
-        // Expect an INFO message.
-        let mut line = crate::SecureVec::with_capacity(1024);
-        while !line.ends_with(b"\r\n") {
-            let byte = &mut [0];
-            stream.read_exact(byte)?;
-            line.push(byte[0]);
-        }
-        let server_info = match proto::decode(&line[..])? {
-            Some(ServerOp::Info(server_info)) => server_info,
-            Some(op) => {
-                return Err(Error::new(
-                    ErrorKind::Other,
-                    format!("expected INFO, received: {op:?}"),
-                ));
-            }
-            None => {
-                return Err(Error::new(ErrorKind::UnexpectedEof, "connection closed"));
-            }
-        };
+        let server_info = read_info(&mut stream)?;
 
         // Check if TLS authentication is required:
         // - Has `self.options.tls_required(true)` been set?
@@ -275,15 +420,47 @@ impl Connector {
                     )
                 })?;
 
-            Some(
-                ClientConnection::new(self.tls_config.clone(), server_name)
-                    .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?,
-            )
+            let mut session = ClientConnection::new(self.tls_config.clone(), server_name)
+                .map_err(|err| Error::new(ErrorKind::InvalidInput, err))?;
+
+            // Complete the handshake while the socket is still blocking, so
+            // its failures are not mistaken for ones of the CONNECT exchange.
+            while session.is_handshaking() {
+                session
+                    .complete_io(&mut stream)
+                    .map_err(|err| Error::new(err.kind(), ConnectError::TlsHandshake(err)))?;
+            }
+
+            Some(session)
         } else {
             None
         };
-        let mut stream = NatsStream::new(stream, session)?;
+        let stream = NatsStream::new(stream, session)?;
 
+        self.handshake(stream, server_info, tls_required, &server.auth())
+    }
+
+    /// Establishes a connection over a user provided stream. The stream is
+    /// used as is, it is never upgraded to TLS.
+    fn connect_custom(&self, stream: NatsStream) -> io::Result<(ServerInfo, NatsStream)> {
+        let server_info = read_info(&stream)?;
+
+        // There is no server address to ask for TLS, but the options and
+        // the INFO line still decide whether TLS is announced in CONNECT.
+        let tls_required = self.options.tls_required || server_info.tls_required;
+
+        self.handshake(stream, server_info, tls_required, &AuthStyle::NoAuth)
+    }
+
+    /// Sends CONNECT over a freshly established stream and waits for the
+    /// server to accept it.
+    fn handshake(
+        &self,
+        mut stream: NatsStream,
+        server_info: ServerInfo,
+        tls_required: bool,
+        server_auth: &AuthStyle,
+    ) -> io::Result<(ServerInfo, NatsStream)> {
         // Data that will be formatted as a CONNECT message.
         let mut connect_info = ConnectInfo {
             tls_required,
@@ -304,11 +481,10 @@ impl Connector {
             no_responders: true,
         };
 
-        let server_auth = server.auth();
         let auth = if let AuthStyle::NoAuth = server_auth {
             &self.options.auth
         } else {
-            &server_auth
+            server_auth
         };
 
         // Fill in the info that authenticates the client.
@@ -377,9 +553,28 @@ impl Connector {
     }
 }
 
+/// Reads the INFO line sent by the server on a new connection.
+fn read_info(mut stream: impl Read) -> io::Result<ServerInfo> {
+    let mut line = crate::SecureVec::with_capacity(1024);
+    while !line.ends_with(b"\r\n") {
+        let byte = &mut [0];
+        stream.read_exact(byte)?;
+        line.push(byte[0]);
+    }
+    match proto::decode(&line[..])? {
+        Some(ServerOp::Info(server_info)) => Ok(server_info),
+        Some(op) => Err(Error::new(
+            ErrorKind::Other,
+            format!("expected INFO, received: {op:?}"),
+        )),
+        None => Err(Error::new(ErrorKind::UnexpectedEof, "connection closed")),
+    }
+}
+
 /// A raw NATS stream of bytes.
 ///
-/// The stream uses the TCP protocol, optionally secured by TLS.
+/// The stream uses the TCP protocol, optionally secured by TLS, or a
+/// transport provided by the user.
 #[derive(Clone)]
 pub(crate) struct NatsStream {
     flavor: Arc<Flavor>,
@@ -388,6 +583,33 @@ pub(crate) struct NatsStream {
 enum Flavor {
     Tcp(TcpStream),
     Tls(Box<Mutex<TlsStream>>),
+    Custom(Box<dyn CustomStream>),
+}
+
+/// A user provided transport that can be read from and written to
+/// concurrently through shared references, like `TcpStream`.
+trait CustomStream: Send + Sync {
+    fn read(&self, buf: &mut [u8]) -> io::Result<usize>;
+    fn write(&self, buf: &[u8]) -> io::Result<usize>;
+    fn flush(&self) -> io::Result<()>;
+}
+
+impl<S> CustomStream for S
+where
+    S: Send + Sync,
+    for<'a> &'a S: Read + Write,
+{
+    fn read(&self, buf: &mut [u8]) -> io::Result<usize> {
+        (&mut &*self).read(buf)
+    }
+
+    fn write(&self, buf: &[u8]) -> io::Result<usize> {
+        (&mut &*self).write(buf)
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        (&mut &*self).flush()
+    }
 }
 
 struct TlsStream {
@@ -409,10 +631,32 @@ impl NatsStream {
         Ok(NatsStream { flavor })
     }
 
+    /// Creates a NATS stream from a user provided transport.
+    pub(crate) fn custom<S>(stream: S) -> NatsStream
+    where
+        S: Send + Sync + 'static,
+        for<'a> &'a S: Read + Write,
+    {
+        let flavor = Arc::new(Flavor::Custom(Box::new(stream)));
+        NatsStream { flavor }
+    }
+
     pub(crate) fn set_write_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
         match &*self.flavor {
             Flavor::Tcp(tcp) => tcp.set_write_timeout(timeout),
             Flavor::Tls(tls) => tls.lock().tcp.set_write_timeout(timeout),
+            // Timeouts have to be configured on the stream by the user.
+            Flavor::Custom(_) => Ok(()),
+        }
+    }
+
+    /// Returns the certificate the server presented, if the stream uses TLS.
+    pub(crate) fn peer_certificate(&self) -> Option<Certificate> {
+        match &*self.flavor {
+            Flavor::Tls(tls) => {
+                Certificate::from_der(tls.lock().session.peer_certificates()?.first()?)
+            }
+            Flavor::Tcp(_) | Flavor::Custom(_) => None,
         }
     }
 
@@ -421,6 +665,7 @@ impl NatsStream {
         match &*self.flavor {
             Flavor::Tcp(tcp) => tcp.shutdown(Shutdown::Both),
             Flavor::Tls(tls) => tls.lock().tcp.shutdown(Shutdown::Both),
+            Flavor::Custom(_) => Ok(()),
         }
         .ok();
     }
@@ -440,6 +685,7 @@ impl Read for &NatsStream {
                 Ok(0) if !eof => Err(io::ErrorKind::WouldBlock.into()),
                 res => res,
             }),
+            Flavor::Custom(stream) => stream.read(buf),
         }
     }
 }
@@ -459,6 +705,7 @@ impl Write for &NatsStream {
         match &*self.flavor {
             Flavor::Tcp(tcp) => (tcp.deref()).write(buf),
             Flavor::Tls(tls) => tls_op(tls, |session, _| session.writer().write(buf)),
+            Flavor::Custom(stream) => stream.write(buf),
         }
     }
 
@@ -466,6 +713,7 @@ impl Write for &NatsStream {
         match &*self.flavor {
             Flavor::Tcp(tcp) => (tcp.deref()).flush(),
             Flavor::Tls(tls) => tls_op(tls, |session, _| session.writer().flush()),
+            Flavor::Custom(stream) => stream.flush(),
         }
     }
 }
@@ -473,7 +721,7 @@ impl Write for &NatsStream {
 /// Performs a blocking operation on a TLS stream.
 ///
 /// However, note that the inner TCP stream is in non-blocking mode.
-fn tls_op<T: std::fmt::Debug>(
+fn tls_op<T: fmt::Debug>(
     tls: &Mutex<TlsStream>,
     mut op: impl FnMut(&mut ClientConnection, bool) -> io::Result<T>,
 ) -> io::Result<T> {
@@ -515,6 +763,23 @@ fn tls_op<T: std::fmt::Debug>(
     }
 }
 
+/// Enables TCP keepalive probes on the socket.
+fn set_keepalive(stream: &TcpStream, keepalive: Duration) -> io::Result<()> {
+    let params = socket2::TcpKeepalive::new().with_time(keepalive);
+    #[cfg(any(
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos",
+        target_os = "netbsd",
+        target_os = "windows",
+    ))]
+    let params = params.with_interval(keepalive);
+
+    socket2::SockRef::from(stream).set_tcp_keepalive(&params)
+}
+
 /// Waits until the TLS stream becomes ready.
 fn tls_wait(mut tls: MutexGuard<'_, TlsStream>) -> io::Result<()> {
     #[cfg(unix)]
@@ -778,4 +1043,192 @@ mod tests {
             matches!(address.auth(), AuthStyle::UserPass(username, password) if &username == "myuser" && &password == "mypass")
         );
     }
+
+    #[test]
+    fn server_updates_keep_discovered() {
+        let updates = Arc::new(Mutex::new(None));
+        let mut connector = Connector::new(
+            "nats://a:4222,nats://b:4222".into_server_list().unwrap(),
+            Arc::new(Options::default()),
+            updates.clone(),
+        )
+        .unwrap();
+        connector.add_server("nats://c:4222".parse().unwrap());
+
+        *updates.lock() = Some("nats://b:4222,nats://d:4222".into_server_list().unwrap());
+        connector.apply_server_updates();
+
+        let mut servers: Vec<_> = connector
+            .attempts
+            .keys()
+            .map(|server| server.host().to_string())
+            .collect();
+        servers.sort();
+        assert_eq!(servers, vec!["b", "c", "d"]);
+    }
+
+    struct StaticResolver(Vec<srv::SrvRecord>);
+
+    impl SrvResolver for StaticResolver {
+        fn resolve_srv(&self, _name: &str) -> io::Result<Vec<srv::SrvRecord>> {
+            Ok(self.0.clone())
+        }
+    }
+
+    #[test]
+    fn resolve_srv_targets() {
+        let resolver = StaticResolver(vec![
+            srv::SrvRecord {
+                priority: 20,
+                weight: 1,
+                port: 4223,
+                target: "127.0.0.2.".to_string(),
+            },
+            srv::SrvRecord {
+                priority: 10,
+                weight: 1,
+                port: 4222,
+                target: "127.0.0.1.".to_string(),
+            },
+        ]);
+        let options = Options::new()
+            .with_srv_discovery(true)
+            .with_srv_resolver(resolver);
+        let server: ServerAddress = "tls://_nats._tcp.example.com".parse().unwrap();
+        let connector = Connector::new(
+            vec![server.clone()],
+            Arc::new(options),
+            Arc::new(Mutex::new(None)),
+        )
+        .unwrap();
+
+        let resolved = connector.resolve(&server).unwrap();
+        let targets: Vec<_> = resolved
+            .iter()
+            .map(|(addr, target)| (addr.to_string(), target.host().to_string()))
+            .collect();
+        assert_eq!(
+            targets,
+            vec![
+                ("127.0.0.1:4222".to_string(), "127.0.0.1".to_string()),
+                ("127.0.0.2:4223".to_string(), "127.0.0.2".to_string()),
+            ]
+        );
+        assert!(resolved.iter().all(|(_, target)| target.tls_required()));
+    }
+
+    #[test]
+    fn tcp_keepalive() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+
+        set_keepalive(&stream, Duration::from_secs(30)).unwrap();
+        assert!(socket2::SockRef::from(&stream).keepalive().unwrap());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn connect_over_custom_stream() {
+        use std::os::unix::net::UnixStream;
+
+        let (client, server) = UnixStream::pair().unwrap();
+
+        // A minimal server accepting the CONNECT.
+        let server = thread::spawn(move || {
+            let mut writer = &server;
+            writer
+                .write_all(
+                    br#"INFO {"server_id":"custom","host":"0.0.0.0","port":4222,"version":"2.10.0","max_payload":1048576,"proto":1,"client_id":1,"go":"go1.21"}"#,
+                )
+                .unwrap();
+            writer.write_all(b"\r\n").unwrap();
+            let mut lines = Vec::new();
+            for line in BufReader::new(&server).lines() {
+                let line = line.unwrap();
+                let ping = line == "PING";
+                lines.push(line);
+                if ping {
+                    writer.write_all(b"PONG\r\n").unwrap();
+                    break;
+                }
+            }
+            lines
+        });
+
+        let mut connector = Connector::new(
+            Vec::new(),
+            Arc::new(Options::new().tls_required(true)),
+            Arc::new(Mutex::new(None)),
+        )
+        .unwrap();
+        connector.set_custom_stream(NatsStream::custom(client));
+
+        let (info, _stream) = connector.connect(false).unwrap();
+        assert_eq!(info.server_id, "custom");
+
+        let lines = server.join().unwrap();
+        assert!(lines[0].starts_with("CONNECT {"));
+        assert!(lines[0].contains(r#""tls_required":true"#));
+        assert_eq!(lines[1], "PING");
+
+        match connector.connect(true) {
+            Err(err) => assert_eq!(err.kind(), ErrorKind::NotConnected),
+            Ok(_) => panic!("should not reconnect"),
+        }
+    }
+
+    #[test]
+    fn no_failover_ignores_connect_urls() {
+        fn connect(options: Options) -> Vec<String> {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            let port = listener.local_addr().unwrap().port();
+
+            // A minimal server advertising two peers.
+            let server = thread::spawn(move || {
+                let (stream, _) = listener.accept().unwrap();
+                let mut writer = &stream;
+                writer
+                    .write_all(
+                        br#"INFO {"server_id":"mock","host":"0.0.0.0","port":4222,"version":"2.10.0","max_payload":1048576,"proto":1,"client_id":1,"go":"go1.21","connect_urls":["10.0.0.1:4222","10.0.0.2:4222"]}"#,
+                    )
+                    .unwrap();
+                writer.write_all(b"\r\n").unwrap();
+                for line in BufReader::new(&stream).lines() {
+                    if line.unwrap() == "PING" {
+                        writer.write_all(b"PONG\r\n").unwrap();
+                        break;
+                    }
+                }
+            });
+
+            let mut connector = Connector::new(
+                format!("nats://127.0.0.1:{port}")
+                    .into_server_list()
+                    .unwrap(),
+                Arc::new(options),
+                Arc::new(Mutex::new(None)),
+            )
+            .unwrap();
+            let (info, _stream) = connector.connect(false).unwrap();
+            assert_eq!(info.connect_urls.len(), 2);
+            server.join().unwrap();
+
+            let mut servers: Vec<_> = connector
+                .attempts
+                .keys()
+                .map(|server| server.host().to_string())
+                .collect();
+            servers.sort();
+            servers
+        }
+
+        assert_eq!(
+            connect(Options::default()),
+            vec!["10.0.0.1", "10.0.0.2", "127.0.0.1"]
+        );
+        assert_eq!(
+            connect(Options::default().with_no_failover(true)),
+            vec!["127.0.0.1"]
+        );
+    }
 }
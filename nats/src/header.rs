@@ -47,12 +47,29 @@ pub const NATS_EXPECTED_LAST_SEQUENCE: &str = "Nats-Expected-Last-Sequence";
 /// Nats-Expected-Last-Subject-Sequence
 pub const NATS_EXPECTED_LAST_SUBJECT_SEQUENCE: &str = "Nats-Expected-Last-Subject-Sequence";
 
+/// Nats-Expected-Last-Subject-Sequence-Subject
+pub const NATS_EXPECTED_LAST_SUBJECT_SEQUENCE_SUBJECT: &str =
+    "Nats-Expected-Last-Subject-Sequence-Subject";
+
+/// Nats-Rollup
+pub const NATS_ROLLUP: &str = "Nats-Rollup";
+
 /// Nats-Last-Consumer
 pub const NATS_LAST_CONSUMER: &str = "Nats-Last-Consumer";
 
 /// Nats-Consumer-Stalled
 pub const NATS_CONSUMER_STALLED: &str = "Nats-Consumer-Stalled";
 
+/// Content-Type
+pub const CONTENT_TYPE: &str = "Content-Type";
+
+/// Nats-Chunk-Seq, the position of a chunk of a streamed response,
+/// starting at 0.
+pub const NATS_CHUNK_SEQUENCE: &str = "Nats-Chunk-Seq";
+
+/// Nats-Chunk-Last, set to `true` on the final chunk of a streamed response.
+pub const NATS_CHUNK_LAST: &str = "Nats-Chunk-Last";
+
 /// A multi-map from header name to a set of values for that header
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct HeaderMap {
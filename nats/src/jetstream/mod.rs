@@ -144,12 +144,14 @@ use crate::{
 pub struct JetStreamOptions {
     pub(crate) api_prefix: String,
     pub(crate) has_domain: bool,
+    pub(crate) warn_on_unacked_drop: bool,
 }
 
 impl Debug for JetStreamOptions {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
         f.debug_map()
             .entry(&"api_prefix", &self.api_prefix)
+            .entry(&"warn_on_unacked_drop", &self.warn_on_unacked_drop)
             .finish()
     }
 }
@@ -159,6 +161,7 @@ impl Default for JetStreamOptions {
         JetStreamOptions {
             api_prefix: "$JS.API.".to_string(),
             has_domain: false,
+            warn_on_unacked_drop: false,
         }
     }
 }
@@ -206,6 +209,24 @@ impl JetStreamOptions {
             self.api_prefix(format!("$JS.{domain}.API"))
         }
     }
+
+    /// Log a warning whenever a message received from a subscription is
+    /// dropped without being acknowledged. Such messages are redelivered by
+    /// the server once their ack wait expires, which is usually a bug in
+    /// consumers using an explicit ack policy.
+    ///
+    /// Disabled by default. Sending `AckKind::Progress` does not count as an
+    /// acknowledgement.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let options = nats::JetStreamOptions::new().warn_on_unacked_drop(true);
+    /// ```
+    pub fn warn_on_unacked_drop(mut self, warn: bool) -> Self {
+        self.warn_on_unacked_drop = warn;
+        self
+    }
 }
 
 /// `ApiResponse` is a standard response from the `JetStream` JSON Api
@@ -441,6 +462,8 @@ pub enum ErrorCode {
     ConsumerDescriptionTooLong = 10107,
     /// Header size exceeds maximum allowed of 64k
     StreamHeaderExceedsMaximum = 10097,
+    /// Stream rollup failed, e.g. because the stream does not allow rollups
+    StreamRollupFailed = 10111,
 }
 
 /// `Error` type returned from an API response when an error occurs.
@@ -461,6 +484,20 @@ impl Error {
     pub fn error_code(&self) -> ErrorCode {
         self.err_code
     }
+
+    /// Returns the actual last sequence if a publish was rejected because
+    /// an expected last sequence did not match.
+    pub fn wrong_last_sequence(&self) -> Option<u64> {
+        if self.err_code != ErrorCode::StreamWrongLastSequence {
+            return None;
+        }
+        self.description
+            .as_deref()?
+            .rsplit(' ')
+            .next()?
+            .parse()
+            .ok()
+    }
 }
 
 impl fmt::Display for Error {
@@ -477,6 +514,57 @@ impl fmt::Display for Error {
 
 impl error::Error for Error {}
 
+/// Error returned by [`JetStream::update_stream`] when the new configuration
+/// is rejected for changing a field that can't be updated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamConfigError {
+    /// The named field cannot be changed on an existing stream.
+    ///
+    /// This covers `storage`, `retention`, `max_consumers` and
+    /// `template_owner`, as well as clearing `sealed`, `deny_delete` or
+    /// `deny_purge` once they have been set.
+    ImmutableField(&'static str),
+}
+
+impl fmt::Display for StreamConfigError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StreamConfigError::ImmutableField(name) => {
+                write!(fmt, "stream field {} can not be changed", name)
+            }
+        }
+    }
+}
+
+impl error::Error for StreamConfigError {}
+
+impl StreamConfig {
+    /// Returns the first field that may not change when updating a stream
+    /// from `self` to `new`.
+    ///
+    /// The server stores the default `max_consumers` of 0 as -1, so any
+    /// value up to 0 is treated as unlimited.
+    fn immutable_change(&self, new: &StreamConfig) -> Option<&'static str> {
+        if self.storage != new.storage {
+            Some("storage")
+        } else if self.retention != new.retention {
+            Some("retention")
+        } else if self.max_consumers.max(0) != new.max_consumers.max(0) {
+            Some("max_consumers")
+        } else if self.template_owner != new.template_owner {
+            Some("template_owner")
+        } else if self.sealed && !new.sealed {
+            Some("sealed")
+        } else if self.deny_delete && !new.deny_delete {
+            Some("deny_delete")
+        } else if self.deny_purge && !new.deny_purge {
+            Some("deny_purge")
+        } else {
+            None
+        }
+    }
+}
+
 #[derive(Debug, Default, Serialize, Deserialize, Clone)]
 struct PagedRequest {
     offset: i64,
@@ -585,6 +673,41 @@ impl JetStream {
         self.publish_with_options_or_headers(subject, Some(options), None, data)
     }
 
+    /// Publishes a message to `JetStream` with the message id `id`, making the
+    /// publish idempotent: when the stream already stored a message with the
+    /// same id, the new one is dropped and the returned ack has `duplicate`
+    /// set, with the sequence of the original message.
+    ///
+    /// Ids are only remembered for the stream's `duplicate_window`, two
+    /// minutes by default. A duplicate published after the window has passed
+    /// is stored again.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let client = nats::connect("demo.nats.io")?;
+    /// # let context = nats::jetstream::new(client);
+    /// let ack = context.publish_dedup("orders", "order-42", "created")?;
+    /// let retry = context.publish_dedup("orders", "order-42", "created")?;
+    /// assert!(retry.duplicate);
+    /// assert_eq!(ack.sequence, retry.sequence);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn publish_dedup(
+        &self,
+        subject: &str,
+        id: &str,
+        data: impl AsRef<[u8]>,
+    ) -> io::Result<PublishAck> {
+        let options = PublishOptions {
+            id: Some(id.to_string()),
+            ..Default::default()
+        };
+        self.publish_with_options_or_headers(subject, Some(&options), None, data)
+    }
+
     /// Publishes a `Message` to `JetStream`.
     pub fn publish_message(&self, message: &Message) -> io::Result<PublishAck> {
         self.publish_with_options_or_headers(
@@ -640,19 +763,61 @@ impl JetStream {
                 headers.insert(header::NATS_EXPECTED_LAST_SUBJECT_SEQUENCE, v.to_string());
             }
 
+            if let Some(v) = options.expected_last_subject_sequence_subject.as_ref() {
+                headers.insert(
+                    header::NATS_EXPECTED_LAST_SUBJECT_SEQUENCE_SUBJECT,
+                    v.to_string(),
+                );
+            }
+
+            if let Some(v) = options.rollup {
+                headers.insert(header::NATS_ROLLUP, v.as_str().to_string());
+            }
+
             Some(headers)
         } else {
             maybe_headers.cloned()
         };
 
         let maybe_timeout = maybe_options.and_then(|options| options.timeout);
+        let retries = maybe_options.map_or(0, |options| options.retries);
+        let backoff = maybe_options.map_or(Duration::ZERO, |options| options.retry_backoff);
 
-        let res_msg = self.connection.request_with_headers_or_timeout(
-            subject,
-            maybe_headers.as_ref(),
-            maybe_timeout,
-            msg,
-        )?;
+        let has_id = maybe_headers
+            .as_ref()
+            .map_or(false, |headers| headers.get(header::NATS_MSG_ID).is_some());
+        if retries > 0 && !has_id {
+            log::warn!(
+                "retrying publishes to {} without a message id may store duplicates",
+                subject
+            );
+        }
+
+        let mut attempt = 0;
+        let res_msg = loop {
+            match self.connection.request_with_headers_or_timeout(
+                subject,
+                maybe_headers.as_ref(),
+                maybe_timeout,
+                msg.as_ref(),
+            ) {
+                Err(err)
+                    if attempt < retries
+                        && matches!(err.kind(), ErrorKind::NotFound | ErrorKind::TimedOut) =>
+                {
+                    attempt += 1;
+                    log::debug!(
+                        "retrying publish to {} ({}/{}): {}",
+                        subject,
+                        attempt,
+                        retries,
+                        err
+                    );
+                    thread::sleep(backoff);
+                }
+                res => break res?,
+            }
+        };
 
         let res: ApiResponse<PublishAck> = serde_json::de::from_slice(&res_msg.data)?;
         match res {
@@ -863,6 +1028,95 @@ impl JetStream {
         self.do_push_subscribe(subject, Some(queue), Some(options))
     }
 
+    /// Subscribes to an existing durable push consumer without creating or
+    /// modifying it, for consumers provisioned separately from the
+    /// applications reading from them. Only permission to look up the
+    /// consumer is needed, not to create one.
+    ///
+    /// Returns an error if the consumer doesn't exist or is a pull consumer.
+    /// Consumers with a deliver group are joined as a member of that group.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let client = nats::connect("demo.nats.io")?;
+    /// # let context = nats::jetstream::new(client);
+    /// let subscription = context.bind_consumer("orders", "processor")?;
+    /// for message in subscription.iter() {
+    ///     message.ack()?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn bind_consumer(&self, stream: &str, consumer: &str) -> io::Result<PushSubscription> {
+        self.do_bind_consumer(stream, consumer, None)
+    }
+
+    /// Joins the deliver group `queue` of an existing durable push consumer,
+    /// so that messages are load balanced between all subscriptions made
+    /// this way, across any number of application instances.
+    ///
+    /// Unlike [`JetStream::bind_consumer`], this returns an error if the
+    /// consumer was not created with `queue` as its deliver group.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let client = nats::connect("demo.nats.io")?;
+    /// # let context = nats::jetstream::new(client);
+    /// # use nats::jetstream::ConsumerConfig;
+    /// context.add_consumer(
+    ///     "orders",
+    ///     ConsumerConfig {
+    ///         durable_name: Some("processor".to_string()),
+    ///         deliver_subject: Some("deliver.orders".to_string()),
+    ///         ..Default::default()
+    ///     }
+    ///     .deliver_group("processors"),
+    /// )?;
+    ///
+    /// let subscription = context.bind_queue_consumer("orders", "processor", "processors")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn bind_queue_consumer(
+        &self,
+        stream: &str,
+        consumer: &str,
+        queue: &str,
+    ) -> io::Result<PushSubscription> {
+        self.do_bind_consumer(stream, consumer, Some(queue))
+    }
+
+    /// Binds to an existing push consumer, joining `maybe_queue` or, if
+    /// that's not given, the consumer's own deliver group.
+    fn do_bind_consumer(
+        &self,
+        stream: &str,
+        consumer: &str,
+        maybe_queue: Option<&str>,
+    ) -> io::Result<PushSubscription> {
+        let info = self.consumer_info(stream, consumer)?;
+        if info.config.deliver_subject.is_none() {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                format!("consumer {consumer} is not push based"),
+            ));
+        }
+
+        // The deliver group is checked against the queue by `do_push_subscribe`.
+        self.do_push_subscribe(
+            &info.config.filter_subject,
+            maybe_queue.or(info.config.deliver_group.as_deref()),
+            Some(&SubscribeOptions::bind(
+                stream.to_string(),
+                consumer.to_string(),
+            )),
+        )
+    }
+
     fn do_push_subscribe(
         &self,
         subject: &str,
@@ -1202,6 +1456,7 @@ impl JetStream {
                     flow_control: options.flow_control.unwrap_or_default(),
                     headers_only: options.headers_only.unwrap_or_default(),
                     idle_heartbeat: options.idle_heartbeat.unwrap_or_default(),
+                    inactive_threshold: options.inactive_threshold.unwrap_or_default(),
                     max_ack_pending: options.max_ack_pending.unwrap_or_default(),
                     max_deliver: options.max_deliver.unwrap_or_default(),
                     max_waiting: options.max_waiting.unwrap_or_default(),
@@ -1390,6 +1645,7 @@ impl JetStream {
             &deliver_subject,
             maybe_queue,
             Box::new(preprocessor.clone()),
+            false,
         )?;
 
         // If we don't have a consumer yet we try to create one here.
@@ -1439,6 +1695,7 @@ impl JetStream {
                             deliver_subject,
                             maybe_queue,
                             Box::new(preprocessor),
+                            false,
                         )?;
 
                     sid = new_sid;
@@ -1478,6 +1735,11 @@ impl JetStream {
     }
 
     /// Update a `JetStream` stream.
+    ///
+    /// If the server rejects the update, the new configuration is compared
+    /// against the current one, and an error of kind `InvalidInput` wrapping
+    /// a [`StreamConfigError::ImmutableField`] is returned if it changes a
+    /// field that can't be updated.
     pub fn update_stream(&self, config: &StreamConfig) -> io::Result<StreamInfo> {
         if config.name.is_empty() {
             return Err(io::Error::new(
@@ -1487,7 +1749,20 @@ impl JetStream {
         }
         let subject: String = format!("{}STREAM.UPDATE.{}", self.api_prefix(), config.name);
         let req = serde_json::ser::to_vec(&config)?;
-        self.js_request(&subject, &req)
+        self.js_request(&subject, &req).or_else(|err| {
+            // Only a rejected update is worth looking up the current config for.
+            if !err.get_ref().map_or(false, |inner| inner.is::<Error>()) {
+                return Err(err);
+            }
+            let current = self.stream_info(&config.name)?.config;
+            match current.immutable_change(config) {
+                Some(field) => Err(io::Error::new(
+                    ErrorKind::InvalidInput,
+                    StreamConfigError::ImmutableField(field),
+                )),
+                None => Err(err),
+            }
+        })
     }
 
     /// List all `JetStream` stream names. If you also want stream information,
@@ -1657,11 +1932,33 @@ impl JetStream {
         Ok(message)
     }
 
-    /// Delete message in a `JetStream` stream.
+    /// Delete a single message with the given sequence number from a `JetStream` stream.
+    ///
+    /// When `erase` is set, the server overwrites the message in storage with random data
+    /// before removing it, so it can't be recovered from the stream's files. This is slower,
+    /// and is meant for removing individual records that must not be kept, such as personal
+    /// data. Otherwise the message is only marked as deleted.
+    ///
+    /// Unlike [`JetStream::purge_stream`] and [`JetStream::purge_stream_subject`], which
+    /// remove every message in the stream or on a subject, this removes exactly one message
+    /// and leaves the rest of the stream untouched. Fails with [`ErrorCode::StreamMsgDeleteFailedF`] if there is no
+    /// message with that sequence number.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// # let js = nats::jetstream::new(nc);
+    /// let ack = js.publish("events", "data")?;
+    /// js.delete_message(&ack.stream, ack.sequence, true)?;
+    /// # Ok(())
+    /// # }
+    /// ```
     pub fn delete_message<S: AsRef<str>>(
         &self,
         stream: S,
         sequence_number: u64,
+        erase: bool,
     ) -> io::Result<bool> {
         let stream: &str = stream.as_ref();
         if stream.is_empty() {
@@ -1673,6 +1970,7 @@ impl JetStream {
 
         let req = serde_json::ser::to_vec(&DeleteRequest {
             seq: sequence_number,
+            no_erase: !erase,
         })
         .unwrap();
 
@@ -1704,6 +2002,7 @@ impl JetStream {
         ConsumerConfig: From<C>,
     {
         let config = ConsumerConfig::from(config);
+        config.validate()?;
         let stream = stream.as_ref();
         if stream.is_empty() {
             return Err(io::Error::new(
@@ -1723,13 +2022,29 @@ impl JetStream {
             format!("{}CONSUMER.CREATE.{}", self.api_prefix(), stream)
         };
 
+        let replay_policy = config.replay_policy;
         let req = CreateConsumerRequest {
             stream_name: stream.into(),
             config,
         };
 
         let ser_req = serde_json::ser::to_vec(&req)?;
-        self.js_request(&subject, &ser_req)
+        let info: ConsumerInfo = self.js_request(&subject, &ser_req)?;
+
+        // Servers that do not know about a replay policy fall back to their default.
+        // The consumer doesn't behave as requested, so don't leave it behind.
+        if info.config.replay_policy != replay_policy {
+            self.delete_consumer(stream, &info.name).ok();
+            return Err(io::Error::new(
+                ErrorKind::Other,
+                format!(
+                    "server does not support replay policy {:?}, consumer was created with {:?}",
+                    replay_policy, info.config.replay_policy
+                ),
+            ));
+        }
+
+        Ok(info)
     }
 
     /// Delete a `JetStream` consumer.
@@ -1811,6 +2126,16 @@ impl JetStream {
     fn api_prefix(&self) -> &str {
         &self.options.api_prefix
     }
+
+    /// Starts tracking acknowledgement of the message if
+    /// `JetStreamOptions::warn_on_unacked_drop` is enabled.
+    pub(crate) fn track_ack(&self, message: Message) -> Message {
+        if self.options.warn_on_unacked_drop {
+            message.track_ack()
+        } else {
+            message
+        }
+    }
 }
 
 /// Creates a new `JetStream` context using the given `Connection` and default options.
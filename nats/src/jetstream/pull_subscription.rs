@@ -11,7 +11,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::error;
+use std::fmt;
 use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
@@ -21,6 +24,47 @@ use crate::Message;
 use super::{AckPolicy, BatchOptions};
 use crossbeam_channel as channel;
 
+/// How often a fetch collecting a batch checks whether the connection was lost.
+const DISCONNECT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Error returned by [`PullSubscription::fetch_messages`] when the batch
+/// couldn't be completed.
+///
+/// It is returned wrapped in an `io::Error` and can be recovered with
+/// `into_inner` and `downcast`.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum FetchError {
+    /// The connection was lost while the batch was being received, so the
+    /// rest of it will not arrive. Contains the messages received until then,
+    /// which can still be acknowledged once reconnected before fetching again.
+    Disconnected {
+        /// Messages of the batch received before the disconnect.
+        received: Vec<Message>,
+    },
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FetchError::Disconnected { received } => write!(
+                f,
+                "disconnected while fetching, after {} messages",
+                received.len()
+            ),
+        }
+    }
+}
+
+impl error::Error for FetchError {}
+
+/// Why no message of a batch could be received.
+enum RecvError {
+    Timeout,
+    Unsubscribed,
+    Disconnected,
+}
+
 #[derive(Debug)]
 pub(crate) struct Inner {
     pid: u64,
@@ -40,6 +84,9 @@ pub(crate) struct Inner {
     /// Indicates if we own the consumer and are responsible for deleting it or not.
     pub(crate) consumer_ownership: ConsumerOwnership,
 
+    /// Set once no more messages were pending when the last one was delivered.
+    pub(crate) caught_up: AtomicBool,
+
     /// Client associated with subscription.
     pub(crate) context: JetStream,
 }
@@ -79,6 +126,7 @@ impl PullSubscription {
             messages,
             consumer_ownership,
             consumer_ack_policy: consumer_info.config.ack_policy,
+            caught_up: AtomicBool::new(consumer_info.num_pending == 0),
             info: consumer_info,
             context,
         }))
@@ -124,10 +172,12 @@ impl PullSubscription {
     /// ```
     pub fn fetch<I: Into<BatchOptions>>(&self, batch: I) -> io::Result<BatchIter<'_>> {
         let batch_options = batch.into();
+        let disconnects = self.0.context.connection.0.client.disconnects();
         self.request_batch(batch_options)?;
         Ok(BatchIter {
             batch_size: batch_options.batch,
             processed: 0,
+            disconnects,
             subscription: self,
         })
     }
@@ -183,15 +233,128 @@ impl PullSubscription {
         timeout: Duration,
     ) -> io::Result<TimeoutBatchIter<'_>> {
         let batch_options = batch.into();
+        let disconnects = self.0.context.connection.0.client.disconnects();
         self.request_batch(batch_options)?;
         Ok(TimeoutBatchIter {
             timeout,
             batch_size: batch_options.batch,
             processed: 0,
+            disconnects,
             subscription: self,
         })
     }
 
+    /// Fetch up to `batch` messages that are already available on the Consumer, without
+    /// waiting for the batch to fill up, and return them.
+    ///
+    /// The request is sent with `no_wait`, so the server replies right away with whatever is
+    /// pending, followed by its `no messages` status if that is less than `batch`. The status
+    /// ends the batch, and an empty `Vec` is returned if nothing was pending. Unlike
+    /// [`PullSubscription::timeout_fetch`] this never waits for new messages to be published,
+    /// which suits workers that process whatever is there and move on. It is a shorthand for
+    /// collecting [`PullSubscription::fetch`] with `no_wait` set, so on a disconnect it
+    /// returns the messages received until then.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let client = nats::connect("demo.nats.io")?;
+    /// # let context = nats::jetstream::new(client);
+    /// #
+    /// # context.add_stream("fetch_no_wait")?;
+    /// let consumer = context.pull_subscribe("fetch_no_wait")?;
+    ///
+    /// loop {
+    ///     let messages = consumer.fetch_no_wait(10)?;
+    ///     if messages.is_empty() {
+    ///         break;
+    ///     }
+    ///     for message in messages {
+    ///         message.ack()?;
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn fetch_no_wait(&self, batch: usize) -> io::Result<Vec<Message>> {
+        let messages = self.fetch(BatchOptions {
+            batch,
+            expires: None,
+            no_wait: true,
+        })?;
+        Ok(messages.collect())
+    }
+
+    /// Fetch a batch of messages for `PullSubscription` and return them once the batch is
+    /// complete, the server reports that there are no more messages or that the request
+    /// expired, or `timeout` elapses.
+    ///
+    /// If the connection is lost in the meantime, the rest of the batch won't arrive and this
+    /// returns right away with a [`FetchError::Disconnected`], of kind `ConnectionReset`,
+    /// carrying the messages received so far.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use nats::jetstream::pull_subscription::FetchError;
+    /// # fn main() -> std::io::Result<()> {
+    /// # let client = nats::connect("demo.nats.io")?;
+    /// # let context = nats::jetstream::new(client);
+    /// #
+    /// # context.add_stream("fetch_messages")?;
+    /// let consumer = context.pull_subscribe("fetch_messages")?;
+    ///
+    /// let messages = match consumer.fetch_messages(10, Duration::from_secs(1)) {
+    ///     Ok(messages) => messages,
+    ///     Err(err) if err.kind() == std::io::ErrorKind::ConnectionReset => {
+    ///         match *err.into_inner().unwrap().downcast::<FetchError>().unwrap() {
+    ///             FetchError::Disconnected { received } => received,
+    ///             _ => unreachable!(),
+    ///         }
+    ///     }
+    ///     Err(err) => return Err(err),
+    /// };
+    /// for message in messages {
+    ///     message.ack()?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn fetch_messages<I: Into<BatchOptions>>(
+        &self,
+        batch: I,
+        timeout: Duration,
+    ) -> io::Result<Vec<Message>> {
+        let batch_options = batch.into();
+        let deadline = Instant::now() + timeout;
+        let disconnects = self.0.context.connection.0.client.disconnects();
+        self.request_batch(batch_options)?;
+
+        let mut received = Vec::with_capacity(batch_options.batch);
+        while received.len() < batch_options.batch {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            match self.recv_batch_message(Some(timeout), Some(disconnects)) {
+                Ok(message) => {
+                    if message.is_no_messages() || message.is_request_timeout() {
+                        break;
+                    }
+                    received.push(self.deliver(message));
+                }
+                Err(RecvError::Timeout) => break,
+                Err(RecvError::Unsubscribed) => {
+                    return Err(io::Error::new(io::ErrorKind::Other, "fetch: unsubscribed"))
+                }
+                Err(RecvError::Disconnected) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::ConnectionReset,
+                        FetchError::Disconnected { received },
+                    ))
+                }
+            }
+        }
+        Ok(received)
+    }
+
     /// High level method that fetches given set of messages, processes them in user-provider
     /// closure and acks them automatically according to `Consumer` `AckPolicy`.
     ///
@@ -321,10 +484,20 @@ impl PullSubscription {
     /// # Ok(())
     /// # }
     /// ```
-    pub fn next_timeout(&self, mut timeout: Duration) -> io::Result<Message> {
+    pub fn next_timeout(&self, timeout: Duration) -> io::Result<Message> {
+        self.next_batch_message_timeout(timeout, None)
+    }
+
+    /// Implements [`PullSubscription::next_timeout`], also giving up once the connection was
+    /// lost if `disconnects` is set, see [`PullSubscription::recv_batch_message`].
+    fn next_batch_message_timeout(
+        &self,
+        mut timeout: Duration,
+        disconnects: Option<usize>,
+    ) -> io::Result<Message> {
         loop {
             let start = Instant::now();
-            return match self.0.messages.recv_timeout(timeout) {
+            return match self.recv_batch_message(Some(timeout), disconnects) {
                 Ok(message) => {
                     if message.is_no_messages() {
                         timeout = timeout.saturating_sub(start.elapsed());
@@ -336,20 +509,110 @@ impl PullSubscription {
                             "next_timeout: Pull Request timed out",
                         ));
                     }
-                    Ok(message)
+                    Ok(self.deliver(message))
                 }
-                Err(channel::RecvTimeoutError::Timeout) => Err(io::Error::new(
+                Err(RecvError::Timeout) => Err(io::Error::new(
                     io::ErrorKind::TimedOut,
                     "next_timeout: timed out",
                 )),
-                Err(channel::RecvTimeoutError::Disconnected) => Err(io::Error::new(
+                Err(RecvError::Unsubscribed) => Err(io::Error::new(
                     io::ErrorKind::Other,
                     "next_timeout: unsubscribed",
                 )),
+                Err(RecvError::Disconnected) => Err(io::Error::new(
+                    io::ErrorKind::ConnectionReset,
+                    "next_timeout: disconnected",
+                )),
+            };
+        }
+    }
+
+    /// Receives the next message of a batch, status messages included, waiting for up to
+    /// `timeout` or indefinitely if it is `None`.
+    ///
+    /// `disconnects` is the count returned by `Client::disconnects` before the batch was
+    /// requested. If it is set and the connection was lost since, the rest of the batch won't
+    /// arrive, so this fails once the messages received before that were returned instead of
+    /// waiting for the timeout.
+    fn recv_batch_message(
+        &self,
+        timeout: Option<Duration>,
+        disconnects: Option<usize>,
+    ) -> Result<Message, RecvError> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        let client = &self.0.context.connection.0.client;
+        loop {
+            if let Some(disconnects) = disconnects {
+                if client.disconnects() != disconnects {
+                    return self
+                        .0
+                        .messages
+                        .try_recv()
+                        .map_err(|_| RecvError::Disconnected);
+                }
+            }
+
+            let wait = match deadline {
+                Some(deadline) => {
+                    let left = deadline.saturating_duration_since(Instant::now());
+                    if disconnects.is_some() {
+                        left.min(DISCONNECT_POLL_INTERVAL)
+                    } else {
+                        left
+                    }
+                }
+                None => DISCONNECT_POLL_INTERVAL,
             };
+            match self.0.messages.recv_timeout(wait) {
+                Ok(message) => return Ok(message),
+                Err(channel::RecvTimeoutError::Timeout) => {
+                    if deadline.map_or(false, |deadline| Instant::now() >= deadline) {
+                        return Err(RecvError::Timeout);
+                    }
+                }
+                Err(channel::RecvTimeoutError::Disconnected) => {
+                    return Err(RecvError::Unsubscribed)
+                }
+            }
         }
     }
 
+    /// Returns true if no more messages were pending on the consumer when the
+    /// last message was received, so another batch would only return messages
+    /// published after that.
+    ///
+    /// A full batch returned by [`PullSubscription::fetch`] does not tell
+    /// whether the stream has been read to the end; this does. The state is
+    /// taken from the pending count the server attaches to every message, the
+    /// same counter reported as `num_pending` in the consumer info. It is true
+    /// right away if nothing was pending when the subscription was created.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use nats::jetstream::BatchOptions;
+    /// # fn main() -> std::io::Result<()> {
+    /// # let client = nats::connect("demo.nats.io")?;
+    /// # let context = nats::jetstream::new(client);
+    /// #
+    /// # context.add_stream("is_caught_up")?;
+    /// let consumer = context.pull_subscribe("is_caught_up")?;
+    /// while !consumer.is_caught_up() {
+    ///     let batch = BatchOptions {
+    ///         expires: None,
+    ///         no_wait: true,
+    ///         batch: 10,
+    ///     };
+    ///     for message in consumer.fetch(batch)? {
+    ///         message.ack()?;
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn is_caught_up(&self) -> bool {
+        self.0.caught_up.load(Ordering::Acquire)
+    }
+
     /// Sends request for another set of messages to Pull Consumer.
     /// This method does not return any messages. It can be used
     /// to have more granular control of how many request and when are sent.
@@ -445,10 +708,24 @@ impl PullSubscription {
             if message.is_request_timeout() {
                 return None;
             }
-            return Some(message);
+            return Some(self.deliver(message));
         }
         message
     }
+
+    /// Records whether the consumer has caught up and attaches an ack tracker
+    /// to the message if enabled on the context.
+    fn deliver(&self, message: Message) -> Message {
+        if let Some(info) = message.jetstream_message_info() {
+            self.0.caught_up.store(info.pending == 0, Ordering::Release);
+        }
+
+        if self.0.consumer_ack_policy == AckPolicy::None {
+            return message;
+        }
+
+        self.0.context.track_ack(message)
+    }
 }
 
 /// Iterator that will endlessly wait for messages, unless `no messages` or `request timeout` is encountered.
@@ -477,9 +754,13 @@ impl<'a> Iterator for TimeoutIter<'a> {
 
 /// Iterator for handling batches of messages. Works like `Iter` except stopping after
 /// reading number of messages defined in `batch_size`.
+///
+/// If the connection is lost, the rest of the batch won't arrive, so it stops once the messages
+/// received until then were returned.
 pub struct BatchIter<'a> {
     batch_size: usize,
     processed: usize,
+    disconnects: usize,
     subscription: &'a PullSubscription,
 }
 
@@ -490,7 +771,10 @@ impl<'a> Iterator for BatchIter<'a> {
             None
         } else {
             self.processed += 1;
-            self.subscription.next()
+            let message = self
+                .subscription
+                .recv_batch_message(None, Some(self.disconnects));
+            self.subscription.preprocess(message.ok())
         }
     }
 }
@@ -521,10 +805,14 @@ impl<'a> Iterator for TryBatchIter<'a> {
 
 /// Iterator for handling batches of messages. Works like `TimeoutIter` except stopping after
 /// reading number of messages defined in `batch_size`.
+///
+/// If the connection is lost, the rest of the batch won't arrive, so once the messages received
+/// until then were returned it yields an error of kind `ConnectionReset` and stops.
 pub struct TimeoutBatchIter<'a> {
     batch_size: usize,
     processed: usize,
     timeout: Duration,
+    disconnects: usize,
     subscription: &'a PullSubscription,
 }
 
@@ -535,7 +823,13 @@ impl<'a> Iterator for TimeoutBatchIter<'a> {
             None
         } else {
             self.processed += 1;
-            Some(self.subscription.next_timeout(self.timeout))
+            let message = self
+                .subscription
+                .next_batch_message_timeout(self.timeout, Some(self.disconnects));
+            if matches!(&message, Err(err) if err.kind() == io::ErrorKind::ConnectionReset) {
+                self.processed = self.batch_size;
+            }
+            Some(message)
         }
     }
 }
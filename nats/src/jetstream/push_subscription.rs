@@ -13,7 +13,7 @@
 
 use portable_atomic::AtomicU64;
 use std::io;
-use std::sync::atomic::Ordering;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::thread;
 use std::time::{Duration, Instant};
@@ -46,6 +46,9 @@ pub(crate) struct Inner {
     /// Indicates if we own the consumer and are responsible for deleting it or not.
     pub(crate) consumer_ownership: ConsumerOwnership,
 
+    /// Set once no more messages were pending when the last one was delivered.
+    pub(crate) caught_up: AtomicBool,
+
     /// Client associated with subscription.
     pub(crate) context: JetStream,
 }
@@ -89,6 +92,7 @@ impl PushSubscription {
             consumer_ack_policy: consumer_info.config.ack_policy,
             num_pending: consumer_info.num_pending,
             consumer_ownership,
+            caught_up: AtomicBool::new(consumer_info.num_pending == 0),
             messages,
             context,
         }))
@@ -110,6 +114,20 @@ impl PushSubscription {
         false
     }
 
+    /// Records whether the consumer has caught up and attaches an ack tracker
+    /// to the message if enabled on the context.
+    fn deliver(&self, message: Message) -> Message {
+        if let Some(info) = message.jetstream_message_info() {
+            self.0.caught_up.store(info.pending == 0, Ordering::Release);
+        }
+
+        if self.0.consumer_ack_policy == AckPolicy::None {
+            return message;
+        }
+
+        self.0.context.track_ack(message)
+    }
+
     /// Get the next message non-protocol message, or None if the subscription has been
     /// unsubscribed or the connection closed.
     ///
@@ -138,7 +156,7 @@ impl PushSubscription {
                         continue;
                     }
 
-                    Some(message)
+                    Some(self.deliver(message))
                 }
                 None => None,
             };
@@ -172,7 +190,7 @@ impl PushSubscription {
                         continue;
                     }
 
-                    Some(message)
+                    Some(self.deliver(message))
                 }
                 None => None,
             };
@@ -206,7 +224,7 @@ impl PushSubscription {
                         continue;
                     }
 
-                    Ok(message)
+                    Ok(self.deliver(message))
                 }
                 Err(channel::RecvTimeoutError::Timeout) => Err(io::Error::new(
                     io::ErrorKind::TimedOut,
@@ -507,6 +525,38 @@ impl PushSubscription {
             .consumer_info(&self.0.stream, &self.0.consumer)
     }
 
+    /// Returns true if no more messages were pending on the consumer when the
+    /// last message was received, i.e. the initial backlog has been delivered
+    /// and further messages are live ones.
+    ///
+    /// This is useful for filling a cache from a stream, for example using
+    /// `DeliverPolicy::LastPerSubject`, before serving requests from it. The
+    /// state is taken from the pending count the server attaches to every
+    /// message, which is the same counter reported as `num_pending` by
+    /// [`PushSubscription::consumer_info`]. It is true right away if nothing
+    /// was pending when the subscription was created, and becomes false again
+    /// if the consumer falls behind.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let client = nats::connect("demo.nats.io")?;
+    /// # let context = nats::jetstream::new(client);
+    /// # context.add_stream("is_caught_up")?;
+    /// let subscription = context.subscribe("is_caught_up")?;
+    /// while !subscription.is_caught_up() {
+    ///     if let Some(message) = subscription.next() {
+    ///         message.ack()?;
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn is_caught_up(&self) -> bool {
+        self.0.caught_up.load(Ordering::Acquire)
+    }
+
     /// Unsubscribe a subscription immediately without draining.
     /// Use `drain` instead if you want any pending messages
     /// to be processed by a handler, if one is configured.
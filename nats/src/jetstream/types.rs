@@ -114,6 +114,8 @@ impl TryFrom<RawStreamMessage> for StreamMessage {
 #[derive(Serialize)]
 pub(crate) struct DeleteRequest {
     pub seq: u64,
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub no_erase: bool,
 }
 
 #[derive(Deserialize)]
@@ -218,7 +220,12 @@ pub struct ConsumerConfig {
     /// When consuming from a Stream with many subjects, or wildcards, this selects only specific incoming subjects. Supports wildcards.
     #[serde(default, skip_serializing_if = "is_default")]
     pub filter_subject: String,
-    /// Whether messages are sent as quickly as possible or at the rate of receipt
+    /// Whether messages are sent as quickly as possible or at the rate of receipt.
+    ///
+    /// With `ReplayPolicy::Original` the server throttles delivery so that the gaps
+    /// between messages match the gaps between their stored publish timestamps.
+    /// This field is always sent to the server, so `Instant` is requested explicitly
+    /// rather than relying on the server default.
     pub replay_policy: ReplayPolicy,
     /// The rate of message delivery in bits per second
     #[serde(default, skip_serializing_if = "is_default")]
@@ -254,7 +261,8 @@ pub struct ConsumerConfig {
     /// Maximum value for request expiration
     #[serde(default, with = "serde_nanos", skip_serializing_if = "is_default")]
     pub max_expires: Duration,
-    /// Threshold for ephemeral consumer inactivity
+    /// Threshold for ephemeral consumer inactivity, after which the server
+    /// deletes the consumer. Zero leaves it to the server default.
     #[serde(default, with = "serde_nanos", skip_serializing_if = "is_default")]
     pub inactive_threshold: Duration,
 }
@@ -285,6 +293,57 @@ pub(crate) enum ConsumerKind {
 
 // TODO: validate consumer
 impl ConsumerConfig {
+    /// Sets the deliver group of a push consumer. Subscriptions joining this
+    /// queue group share the consumer's messages instead of each receiving
+    /// all of them.
+    pub fn deliver_group(mut self, group: &str) -> Self {
+        self.deliver_group = Some(group.to_string());
+        self
+    }
+
+    /// Sets how long the consumer may go without any interest, such as a
+    /// subscription on its deliver subject or pull requests, before the
+    /// server deletes it.
+    ///
+    /// This keeps orphaned ephemeral consumers, left behind by clients that
+    /// crashed or lost their connection without unsubscribing, from
+    /// accumulating on the server. The threshold has to be longer than the
+    /// idle heartbeat, if one is set.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use nats::jetstream::ConsumerConfig;
+    /// let config = ConsumerConfig::default().inactive_threshold(Duration::from_secs(60));
+    /// assert_eq!(config.inactive_threshold, Duration::from_secs(60));
+    /// ```
+    pub fn inactive_threshold(mut self, threshold: Duration) -> Self {
+        self.inactive_threshold = threshold;
+        self
+    }
+
+    /// Checks the parts of the configuration the server would otherwise
+    /// accept but can't work as intended.
+    pub(crate) fn validate(&self) -> io::Result<()> {
+        if self.inactive_threshold.is_zero() {
+            return Ok(());
+        }
+        if self.inactive_threshold.as_nanos() > i64::MAX as u128 {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                "inactive threshold is too long",
+            ));
+        }
+        if !self.idle_heartbeat.is_zero() && self.inactive_threshold <= self.idle_heartbeat {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                "inactive threshold must be longer than the idle heartbeat",
+            ));
+        }
+        Ok(())
+    }
+
     pub(crate) fn validate_for(&self, kind: &ConsumerKind) -> io::Result<()> {
         match kind {
             ConsumerKind::Pull => {
@@ -526,6 +585,9 @@ pub enum ReplayPolicy {
     /// Sends messages to a consumer in a rate-limited fashion based on the rate of receipt. This
     /// is useful for replaying traffic in a testing or staging environment based on production
     /// traffic patterns.
+    ///
+    /// Delivery is throttled using the timestamps stored with each message, so replaying an
+    /// hour of traffic takes about an hour.
     #[serde(rename = "original")]
     Original = 1,
 }
@@ -833,6 +895,7 @@ pub struct SubscribeOptions {
     pub(crate) durable_name: Option<String>,
     pub(crate) sample_frequency: Option<u8>,
     pub(crate) idle_heartbeat: Option<Duration>,
+    pub(crate) inactive_threshold: Option<Duration>,
     pub(crate) max_ack_pending: Option<i64>,
     pub(crate) max_deliver: Option<i64>,
     pub(crate) max_waiting: Option<i64>,
@@ -968,6 +1031,9 @@ impl SubscribeOptions {
     }
 
     /// Replays the messages at the original speed.
+    ///
+    /// Delivery is throttled to match the timing with which the messages were originally
+    /// published. When binding to an existing consumer, its replay policy has to match.
     pub fn replay_original(mut self) -> Self {
         self.replay_policy = Some(ReplayPolicy::Original);
         self
@@ -1013,6 +1079,16 @@ impl SubscribeOptions {
         self.idle_heartbeat = Some(interval);
         self
     }
+
+    /// Sets how long a consumer created for the subscription may go without
+    /// interest before the server deletes it, see
+    /// [`ConsumerConfig::inactive_threshold`].
+    ///
+    /// If it's not set, the server default applies.
+    pub fn inactive_threshold(mut self, threshold: Duration) -> Self {
+        self.inactive_threshold = Some(threshold);
+        self
+    }
 }
 
 /// Options for publishing
@@ -1030,6 +1106,117 @@ pub struct PublishOptions {
     pub expected_last_sequence: Option<u64>,
     /// Expected last subject sequence
     pub expected_last_subject_sequence: Option<u64>,
+    /// Subject, possibly with wildcards, whose last sequence is compared with
+    /// `expected_last_subject_sequence` instead of the published subject
+    pub expected_last_subject_sequence_subject: Option<String>,
+    /// Purge prior messages once this one is stored
+    pub rollup: Option<Rollup>,
+    /// How many times to retry when no stream responds or the ack times out
+    pub retries: usize,
+    /// Delay between retries
+    pub retry_backoff: Duration,
+}
+
+impl PublishOptions {
+    /// Makes the published message replace all prior messages of its subject,
+    /// or of the whole stream. The stream must have `allow_rollup` set,
+    /// otherwise the server rejects the message with
+    /// `ErrorCode::StreamRollupFailed`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use nats::jetstream::{PublishOptions, Rollup};
+    /// let options = PublishOptions::default().rollup(Rollup::Subject);
+    /// ```
+    pub fn rollup(mut self, rollup: Rollup) -> Self {
+        self.rollup = Some(rollup);
+        self
+    }
+
+    /// Retries the publish up to `retries` more times when there are no
+    /// responders for the subject or the ack doesn't arrive within the
+    /// timeout. Both happen briefly while a stream elects a new leader, for
+    /// example during a cluster upgrade. Defaults to no retries.
+    ///
+    /// A timed out publish may still have been stored, so retrying it can
+    /// store the message twice. Set a message [`id`](PublishOptions::id) so
+    /// the stream drops the duplicate within its duplicate window; a warning
+    /// is logged when retrying without one.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use nats::jetstream::PublishOptions;
+    /// let options = PublishOptions {
+    ///     id: Some("order-42".to_string()),
+    ///     ..Default::default()
+    /// }
+    /// .retries(5)
+    /// .retry_backoff(Duration::from_millis(250));
+    /// ```
+    pub fn retries(mut self, retries: usize) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Sets how long to wait before each retry. Defaults to retrying right
+    /// away.
+    pub fn retry_backoff(mut self, backoff: Duration) -> Self {
+        self.retry_backoff = backoff;
+        self
+    }
+
+    /// Only stores the message if the last message stored for its subject has
+    /// the sequence `sequence`, or if there is none for a `sequence` of 0.
+    /// Otherwise the publish fails with an error of code
+    /// `ErrorCode::StreamWrongLastSequence`, which carries the actual sequence
+    /// in [`Error::wrong_last_sequence`](crate::jetstream::Error::wrong_last_sequence).
+    ///
+    /// This provides optimistic concurrency per subject, even when many
+    /// subjects share a stream.
+    pub fn expected_last_subject_sequence(mut self, sequence: u64) -> Self {
+        self.expected_last_subject_sequence = Some(sequence);
+        self
+    }
+
+    /// Checks [`expected_last_subject_sequence`](PublishOptions::expected_last_subject_sequence)
+    /// against the last message of `subject` instead of the published subject.
+    /// With a wildcard, the last message matching any of its subjects counts,
+    /// so a whole group of subjects can be guarded at once.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use nats::jetstream::PublishOptions;
+    /// // Append to an order only if none of its events changed meanwhile.
+    /// let options = PublishOptions::default()
+    ///     .expected_last_subject_sequence(41)
+    ///     .expected_last_subject_sequence_subject("orders.42.*");
+    /// ```
+    pub fn expected_last_subject_sequence_subject(mut self, subject: &str) -> Self {
+        self.expected_last_subject_sequence_subject = Some(subject.to_string());
+        self
+    }
+}
+
+/// `Rollup` determines which prior messages are purged by a rollup message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rollup {
+    /// Purge all prior messages with the same subject.
+    Subject,
+    /// Purge all prior messages in the stream.
+    All,
+}
+
+impl Rollup {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Rollup::Subject => "sub",
+            Rollup::All => "all",
+        }
+    }
 }
 
 /// contains info about the `JetStream` usage from the current account.
@@ -291,6 +291,32 @@ impl JetStream {
 
         Ok(())
     }
+
+    /// Lists the names of all key-value store buckets.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let client = nats::connect("demo.nats.io")?;
+    /// # let context = nats::jetstream::new(client);
+    /// #
+    /// for bucket in context.key_value_stores()? {
+    ///     println!("bucket: {}", bucket);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn key_value_stores(&self) -> io::Result<Vec<String>> {
+        let mut buckets = Vec::new();
+        for name in self.stream_names() {
+            if let Some(bucket) = name?.strip_prefix("KV_") {
+                buckets.push(bucket.to_string());
+            }
+        }
+
+        Ok(buckets)
+    }
 }
 
 /// An entry in a key-value bucket.
@@ -324,6 +350,26 @@ pub struct Store {
 
 impl Store {
     /// Returns the status of the bucket
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use nats::kv::Config;
+    /// # fn main() -> std::io::Result<()> {
+    /// # let client = nats::connect("demo.nats.io")?;
+    /// # let context = nats::jetstream::new(client);
+    /// #
+    /// # let bucket = context.create_key_value(&Config {
+    /// #  bucket: "status_bucket".to_string(),
+    /// #  ..Default::default()
+    /// # })?;
+    /// #
+    /// let status = bucket.status()?;
+    /// println!("{} values using {} bytes", status.values(), status.bytes());
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
     pub fn status(&self) -> io::Result<BucketStatus> {
         let info = self.context.stream_info(&self.stream_name)?;
 
@@ -930,4 +976,14 @@ impl BucketStatus {
     pub fn max_age(&self) -> Duration {
         self.info.config.max_age
     }
+
+    /// How many bytes the bucket uses, including historical values
+    pub fn bytes(&self) -> u64 {
+        self.info.state.bytes
+    }
+
+    /// Information about the stream backing the bucket
+    pub fn stream_info(&self) -> &StreamInfo {
+        &self.info
+    }
 }
@@ -197,13 +197,16 @@
 pub mod asynk;
 
 mod auth_utils;
+mod certificate;
 mod client;
 mod connect;
 mod connector;
 mod message;
 mod options;
 mod proto;
+mod request;
 mod secure_wipe;
+mod srv;
 mod subscription;
 
 /// Header constants and types.
@@ -218,6 +221,14 @@ pub mod kv;
 #[cfg_attr(docsrs, doc(cfg(feature = "unstable")))]
 pub mod object_store;
 
+#[cfg(feature = "unstable-testing")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable-testing")))]
+pub mod testing;
+
+#[cfg(feature = "unstable-system-events")]
+#[cfg_attr(docsrs, doc(cfg(feature = "unstable-system-events")))]
+pub mod system;
+
 #[cfg(feature = "fault_injection")]
 mod fault_injection;
 
@@ -248,19 +259,27 @@ pub type Headers = HeaderMap;
 pub use header::HeaderMap;
 
 use std::{
+    convert::TryFrom,
     io::{self, Error, ErrorKind},
     sync::Arc,
+    thread,
     time::{Duration, Instant},
 };
 
 use lazy_static::lazy_static;
 use regex::Regex;
 
-pub use connector::{IntoServerList, ServerAddress};
+pub use certificate::Certificate;
+pub use connector::{ConnectError, IntoServerList, ServerAddress};
 pub use jetstream::JetStreamOptions;
-pub use message::Message;
-pub use options::Options;
-pub use subscription::{Handler, Subscription};
+pub use message::{AckStats, Message, PublishBuilder};
+pub use options::{Options, OutboundPolicy};
+pub use proto::ValidationError;
+pub use request::{RequestHandle, RequestManyOptions, Responses, ServiceError};
+pub use srv::{SrvRecord, SrvResolver};
+pub use subscription::{
+    DetachedSubscription, DispatchStrategy, Handler, MessageSizeStats, Subscription,
+};
 
 /// A re-export of the `rustls` crate used in this crate,
 /// for use in cases where manual client configurations
@@ -277,6 +296,9 @@ use secure_wipe::{SecureString, SecureVec};
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 const LANG: &str = "rust";
 const DEFAULT_FLUSH_TIMEOUT: Duration = Duration::from_secs(10);
+/// Base delay before resending a request that had no responders, multiplied
+/// by the attempt number.
+const REQUEST_RETRY_DELAY: Duration = Duration::from_millis(100);
 
 lazy_static! {
     static ref VERSION_RE: Regex = Regex::new(r#"\Av?([0-9]+)\.?([0-9]+)?\.?([0-9]+)?"#).unwrap();
@@ -425,7 +447,16 @@ impl Connection {
         I: IntoServerList,
     {
         let urls = urls.into_server_list()?;
-        let client = Client::connect(urls, options)?;
+        let client = Client::connect(urls, None, options)?;
+        client.flush(DEFAULT_FLUSH_TIMEOUT)?;
+        Ok(Connection(Arc::new(Inner { client })))
+    }
+
+    pub(crate) fn connect_with_stream(
+        stream: connector::NatsStream,
+        options: Options,
+    ) -> io::Result<Connection> {
+        let client = Client::connect(Vec::new(), Some(stream), options)?;
         client.flush(DEFAULT_FLUSH_TIMEOUT)?;
         Ok(Connection(Arc::new(Inner { client })))
     }
@@ -444,6 +475,36 @@ impl Connection {
         self.do_subscribe(subject, None)
     }
 
+    /// Subscribe to system events of the given kind across all accounts and
+    /// servers, parsing each one into a [`system::SystemEvent`].
+    ///
+    /// System events are only delivered to users of the system account, so
+    /// the connection has to authenticate as one, for example with the
+    /// credentials of the operator's system account user. Otherwise the
+    /// server rejects the subscription with a permissions violation that is
+    /// reported to the error callback, and no events arrive.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// use nats::system::{SystemEvent, SystemEventKind};
+    ///
+    /// let nc = nats::Options::with_credentials("sys.creds").connect("demo.nats.io")?;
+    /// for event in nc.subscribe_sys(SystemEventKind::Disconnect)? {
+    ///     if let SystemEvent::Disconnect(event) = event? {
+    ///         println!("{} left: {}", event.client.account, event.reason);
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "unstable-system-events")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "unstable-system-events")))]
+    pub fn subscribe_sys(&self, kind: system::SystemEventKind) -> io::Result<system::SystemEvents> {
+        let subscription = self.subscribe(kind.subject())?;
+        Ok(system::SystemEvents::new(kind, subscription))
+    }
+
     /// Create a queue subscription for the given NATS connection.
     ///
     /// # Example
@@ -458,6 +519,57 @@ impl Connection {
         self.do_subscribe(subject, Some(queue))
     }
 
+    /// Create a queue subscription whose messages are processed by `handler`
+    /// on a pool of `workers` threads.
+    ///
+    /// The pool shares a single server-side subscription, and `strategy`
+    /// decides which worker each of its messages goes to. This only balances
+    /// the load within this process: the server still distributes the
+    /// queue group's messages across all of its members, including those in
+    /// other processes, without regard to the size of their pools.
+    ///
+    /// Returns an error if `workers` is zero.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// use nats::DispatchStrategy;
+    ///
+    /// let handler = nc.queue_subscribe_pool(
+    ///     "jobs",
+    ///     "workers",
+    ///     4,
+    ///     DispatchStrategy::LeastBusy,
+    ///     |msg| {
+    ///         println!("processing {}", msg);
+    ///         Ok(())
+    ///     },
+    /// )?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn queue_subscribe_pool<F>(
+        &self,
+        subject: &str,
+        queue: &str,
+        workers: usize,
+        strategy: DispatchStrategy,
+        handler: F,
+    ) -> io::Result<Handler>
+    where
+        F: Fn(Message) -> io::Result<()> + Send + Sync + 'static,
+    {
+        if workers == 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "a subscription pool needs at least one worker",
+            ));
+        }
+        let sub = self.do_subscribe(subject, Some(queue))?;
+        Ok(sub.with_handler_pool(workers, strategy, handler))
+    }
+
     /// Publish a message on the given subject.
     ///
     /// # Example
@@ -472,6 +584,53 @@ impl Connection {
         self.publish_with_reply_or_headers(subject, None, None, msg)
     }
 
+    /// Starts building a message to publish on the given subject, bound
+    /// to this connection.
+    ///
+    /// Unlike [`Connection::publish`], the payload can be streamed from a
+    /// reader or a sequence of chunks, so large messages don't have to be
+    /// held in memory. See [`PublishBuilder`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// let file = std::fs::File::open("backup.tar")?;
+    /// let len = file.metadata()?.len() as usize;
+    /// nc.publish_builder("backups")
+    ///     .reply("backups.done")
+    ///     .header("X-Origin", "host-1")
+    ///     .publish_from_reader(file, len)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn publish_builder(&self, subject: impl Into<String>) -> PublishBuilder {
+        PublishBuilder::bound(self.clone(), subject.into())
+    }
+
+    /// Publish `value` serialized as JSON on the given subject.
+    ///
+    /// Fails with an error of kind `InvalidInput` wrapping the
+    /// `serde_json::Error` if `value` cannot be serialized.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// nc.publish_json("orders", &serde_json::json!({ "id": 1 }))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "json")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+    pub fn publish_json<T: serde::Serialize + ?Sized>(
+        &self,
+        subject: &str,
+        value: &T,
+    ) -> io::Result<()> {
+        self.publish(subject, message::to_json(value)?)
+    }
+
     /// Publish a message on the given subject with a reply subject for
     /// responses.
     ///
@@ -514,6 +673,9 @@ impl Connection {
     /// Publish a message on the given subject as a request and receive the
     /// response.
     ///
+    /// This waits for the timeout set with
+    /// [`Options::with_request_timeout`], or indefinitely if there is none.
+    ///
     /// # Example
     /// ```no_run
     /// # fn main() -> std::io::Result<()> {
@@ -527,10 +689,90 @@ impl Connection {
         self.request_with_headers_or_timeout(subject, None, None, msg)
     }
 
+    /// Publish a message on the given subject as a request to a service and
+    /// return the payload of the response.
+    ///
+    /// Responses with the `Nats-Service-Error` and `Nats-Service-Error-Code`
+    /// headers used by the NATS service framework fail with an error of kind
+    /// `Other` wrapping a [`ServiceError`]. Other errors are returned as with
+    /// [`Connection::request`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// match nc.request_full("products.get", "1") {
+    ///     Ok(payload) => println!("product: {:?}", payload),
+    ///     Err(err) => match err.get_ref().and_then(|err| err.downcast_ref::<nats::ServiceError>()) {
+    ///         Some(err) => println!("service error {}", err.code),
+    ///         None => return Err(err),
+    ///     },
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn request_full(&self, subject: &str, msg: impl AsRef<[u8]>) -> io::Result<Vec<u8>> {
+        let message = self.request(subject, msg)?;
+        if let Some(err) = message
+            .headers
+            .as_ref()
+            .and_then(ServiceError::from_headers)
+        {
+            return Err(Error::new(ErrorKind::Other, err));
+        }
+        Ok(message.data)
+    }
+
+    /// Publish `value` serialized as JSON as a request, and deserialize the
+    /// JSON response, as with [`Connection::request`] and [`Message::json`].
+    ///
+    /// Errors sending the request or receiving the response are returned as
+    /// with [`Connection::request`]. A response that is not valid JSON for
+    /// `R` fails with an error of kind `InvalidData` wrapping the
+    /// `serde_json::Error`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// #[derive(serde::Serialize)]
+    /// struct Quote {
+    ///     symbol: String,
+    /// }
+    ///
+    /// #[derive(serde::Deserialize)]
+    /// struct Price {
+    ///     cents: u64,
+    /// }
+    ///
+    /// let price: Price = nc.request_json(
+    ///     "prices",
+    ///     &Quote {
+    ///         symbol: "NATS".to_string(),
+    ///     },
+    /// )?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "json")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+    pub fn request_json<T, R>(&self, subject: &str, value: &T) -> io::Result<R>
+    where
+        T: serde::Serialize + ?Sized,
+        R: serde::de::DeserializeOwned,
+    {
+        self.request(subject, message::to_json(value)?)?.json()
+    }
+
     /// Publish a message on the given subject as a request and receive the
     /// response. This call will return after the timeout duration if no
     /// response is received.
     ///
+    /// Returns an error of kind `TimedOut` if the timeout elapsed, and
+    /// `NotFound` if there were no responders, after the retries set with
+    /// [`Options::with_request_retries`]. Either way the reply inbox is
+    /// unsubscribed from.
+    ///
     /// # Example
     /// ```no_run
     /// # fn main() -> std::io::Result<()> {
@@ -604,28 +846,199 @@ impl Connection {
         maybe_timeout: Option<Duration>,
         msg: impl AsRef<[u8]>,
     ) -> io::Result<Message> {
-        // Publish a request.
         let reply = self.new_inbox();
-        let sub = self.subscribe(&reply)?;
-        self.publish_with_reply_or_headers(subject, Some(reply.as_str()), maybe_headers, msg)?;
-
-        // Wait for the response
-        let result = if let Some(timeout) = maybe_timeout {
-            sub.next_timeout(timeout)
-        } else if let Some(msg) = sub.next() {
-            Ok(msg)
-        } else {
-            Err(ErrorKind::ConnectionReset.into())
-        };
-
-        // Check for no responder status.
-        if let Ok(msg) = result.as_ref() {
-            if msg.is_no_responders() {
+        self.request_on_inbox(subject, &reply, maybe_headers, maybe_timeout, msg)
+    }
+
+    /// Publish a message on the given subject as a request, using a caller
+    /// supplied inbox as the reply subject, and receive the response. This
+    /// call will return after the timeout duration if no response is
+    /// received.
+    ///
+    /// This is the primitive beneath [`Connection::request`]. It is useful
+    /// when the reply subject has to carry meaning of its own, for example a
+    /// tenant prefix that the server routes or authorizes on. The client
+    /// subscribes to the inbox for the duration of the request and
+    /// unsubscribes once it completes, successfully or not.
+    ///
+    /// The inbox must be a valid subject without wildcards. Unlike the inboxes
+    /// generated by [`Connection::new_inbox`], it is not guaranteed to be
+    /// unique: any other subscriber to the same subject, in this or another
+    /// client, may receive the reply, and any message published to it while
+    /// the request is pending is taken as the reply. Include enough
+    /// randomness in the inbox to make collisions unlikely.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// # nc.subscribe("foo")?.with_handler(move |m| { m.respond("ans=42")?; Ok(()) });
+    /// let inbox = format!("_INBOX.tenant-a.{}", nuid::next());
+    /// let resp = nc.request_with_inbox("foo", &inbox, "Help me?", Duration::from_secs(2))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn request_with_inbox(
+        &self,
+        subject: &str,
+        inbox: &str,
+        msg: impl AsRef<[u8]>,
+        timeout: Duration,
+    ) -> io::Result<Message> {
+        if inbox.split('.').any(|token| token == "*" || token == ">") {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "request inbox must not contain wildcards",
+            ));
+        }
+        self.request_on_inbox(subject, inbox, None, Some(timeout), msg)
+    }
+
+    fn request_on_inbox(
+        &self,
+        subject: &str,
+        reply: &str,
+        maybe_headers: Option<&HeaderMap>,
+        maybe_timeout: Option<Duration>,
+        msg: impl AsRef<[u8]>,
+    ) -> io::Result<Message> {
+        let options = &self.0.client.options;
+        let deadline = maybe_timeout
+            .or(options.request_timeout)
+            .map(|timeout| Instant::now() + timeout);
+
+        // The subscription is dropped, and so unsubscribed from, on return.
+        let sub = self.subscribe(reply)?;
+        let mut retries = 0;
+        loop {
+            // Publish a request.
+            self.publish_with_reply_or_headers(subject, Some(reply), maybe_headers, msg.as_ref())?;
+
+            // Wait for the response
+            let response = if let Some(deadline) = deadline {
+                sub.next_timeout(deadline.saturating_duration_since(Instant::now()))?
+            } else if let Some(msg) = sub.next() {
+                msg
+            } else {
+                return Err(ErrorKind::ConnectionReset.into());
+            };
+
+            // Check for no responder status.
+            if !response.is_no_responders() {
+                return Ok(response);
+            }
+            let delay =
+                REQUEST_RETRY_DELAY * u32::try_from(retries).unwrap_or(u32::MAX).saturating_add(1);
+            let out_of_time = deadline.map_or(false, |deadline| Instant::now() + delay >= deadline);
+            if retries == options.request_retries || out_of_time {
                 return Err(Error::new(ErrorKind::NotFound, "no responders"));
             }
+            retries += 1;
+            thread::sleep(delay);
         }
+    }
 
-        result
+    /// Publish a message on the given subject as a request and receive a
+    /// response sent in several chunks, as with [`Message::respond_chunked`],
+    /// returning the reassembled payload. This allows responses larger than
+    /// the server's max payload.
+    ///
+    /// Chunks are expected in order, numbered from 0 in the `Nats-Chunk-Seq`
+    /// header, until one is marked with `Nats-Chunk-Last`. A missing or
+    /// out of order chunk fails with an error of kind `InvalidData`. The
+    /// `timeout` covers receiving the whole response. Without one, the
+    /// timeout set with [`Options::with_request_timeout`] applies, if any.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// # nc.subscribe("report")?.with_handler(move |m| m.respond_chunked(vec![0; 1 << 24], 1 << 20));
+    /// let report = nc.request_streamed("report", "2024", Some(Duration::from_secs(10)))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn request_streamed(
+        &self,
+        subject: &str,
+        msg: impl AsRef<[u8]>,
+        maybe_timeout: Option<Duration>,
+    ) -> io::Result<Vec<u8>> {
+        let deadline = maybe_timeout
+            .or(self.0.client.options.request_timeout)
+            .map(|timeout| Instant::now() + timeout);
+        let sub = self.send_request(subject, msg)?;
+
+        let mut payload = Vec::new();
+        for expected in 0.. {
+            let chunk = match deadline {
+                Some(deadline) => sub.recv_deadline(deadline)?,
+                None => sub.next().ok_or(ErrorKind::ConnectionReset)?,
+            };
+            if chunk.is_no_responders() {
+                return Err(Error::new(ErrorKind::NotFound, "no responders"));
+            }
+            let headers = chunk.headers.as_ref();
+            let sequence = headers
+                .and_then(|headers| headers.get(header::NATS_CHUNK_SEQUENCE))
+                .and_then(|sequence| sequence.parse::<u64>().ok())
+                .ok_or_else(|| Error::new(ErrorKind::InvalidData, "missing chunk sequence"))?;
+            if sequence != expected {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("expected chunk {expected}, got chunk {sequence}"),
+                ));
+            }
+            payload.extend_from_slice(&chunk.data);
+
+            let last = headers
+                .and_then(|headers| headers.get(header::NATS_CHUNK_LAST))
+                .map_or(false, |last| last == "true");
+            if last {
+                break;
+            }
+        }
+        Ok(payload)
+    }
+
+    /// Publish a message on the given subject as a request without waiting
+    /// for the response, returning a handle to wait for it or to cancel the
+    /// request. Cancelling unsubscribes from the reply inbox immediately,
+    /// which is useful when the operation the request is part of gets
+    /// aborted.
+    ///
+    /// [`RequestHandle::wait`] gives up once the timeout set with
+    /// [`Options::with_request_timeout`] has passed since this call.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// let request = nc.request_handle("foo", "Help me?")?;
+    /// let waiter = request.clone();
+    /// let response = std::thread::spawn(move || waiter.wait());
+    ///
+    /// // The caller changed its mind.
+    /// request.cancel()?;
+    /// assert!(response.join().unwrap().is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn request_handle(
+        &self,
+        subject: &str,
+        msg: impl AsRef<[u8]>,
+    ) -> io::Result<RequestHandle> {
+        let deadline = self
+            .0
+            .client
+            .options
+            .request_timeout
+            .map(|timeout| Instant::now() + timeout);
+        let sub = self.send_request(subject, msg)?;
+        Ok(RequestHandle::new(sub, deadline))
     }
 
     /// Publish a message on the given subject as a request and allow multiple
@@ -641,12 +1054,74 @@ impl Connection {
     /// # }
     /// ```
     pub fn request_multi(&self, subject: &str, msg: impl AsRef<[u8]>) -> io::Result<Subscription> {
-        // Publish a request.
+        self.send_request(subject, msg)
+    }
+
+    /// Checks whether anything responds to requests on the given subject, by
+    /// sending an empty request and waiting for the first reply.
+    ///
+    /// Returns `false` if the server reports that there are no responders,
+    /// and `true` on any other reply, whose payload is discarded. At most one
+    /// reply is consumed, so a responder answering more than once is only
+    /// seen once, and requests are not retried as set with
+    /// [`Options::with_request_retries`]. Fails with `TimedOut` if nothing
+    /// replies within the timeout, as a responder may still be busy.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// if !nc.has_responders("orders.health", Duration::from_secs(1))? {
+    ///     println!("the orders service is down");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn has_responders(&self, subject: &str, timeout: Duration) -> io::Result<bool> {
+        let sub = self.send_request(subject, b"")?;
+
+        let response = sub.next_timeout(timeout)?;
+        Ok(!response.is_no_responders())
+    }
+
+    /// Publish a message on the given subject as a request and collect the
+    /// responses of every responder, rather than only the first one, until
+    /// the `options` say to stop.
+    ///
+    /// Members of a queue group share requests instead of all receiving
+    /// them, so at most one response comes from each queue group.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// let options = nats::RequestManyOptions::new()
+    ///     .timeout(Duration::from_secs(2))
+    ///     .stall_timeout(Duration::from_millis(100));
+    /// for response in nc.request_many("workers.status", "", options)? {
+    ///     println!("{}", response);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn request_many(
+        &self,
+        subject: &str,
+        msg: impl AsRef<[u8]>,
+        options: RequestManyOptions,
+    ) -> io::Result<Responses> {
+        let sub = self.send_request(subject, msg)?;
+        Ok(Responses::new(sub, options))
+    }
+
+    /// Publishes a request with a new inbox as the reply subject, returning
+    /// the subscription to the inbox that responses arrive on.
+    fn send_request(&self, subject: &str, msg: impl AsRef<[u8]>) -> io::Result<Subscription> {
         let reply = self.new_inbox();
         let sub = self.subscribe(&reply)?;
         self.publish_with_reply_or_headers(subject, Some(reply.as_str()), None, msg)?;
-
-        // Return the subscription.
         Ok(sub)
     }
 
@@ -793,6 +1268,55 @@ impl Connection {
         }
     }
 
+    /// Returns the aggregated latencies of `JetStream` acks confirmed by the
+    /// server, as measured by [`Message::ack_sync_timed`] and
+    /// [`Message::double_ack`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// let stats = nc.ack_stats();
+    /// println!("{} acks, max latency {:?}", stats.count, stats.max);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn ack_stats(&self) -> AckStats {
+        *self.0.client.ack_stats.lock()
+    }
+
+    /// Returns the payload sizes of the messages received on all
+    /// subscriptions of this connection since it was created or
+    /// [`Connection::reset_size_stats`] was last called.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// let stats = nc.size_stats();
+    /// println!("average size: {:?}, max size: {}", stats.average(), stats.max);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn size_stats(&self) -> MessageSizeStats {
+        self.0.client.state.read.lock().size_stats
+    }
+
+    /// Resets the connection wide message size statistics. Statistics of the
+    /// individual subscriptions are kept.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// nc.reset_size_stats();
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn reset_size_stats(&self) {
+        self.0.client.state.read.lock().size_stats = MessageSizeStats::default();
+    }
+
     /// Returns the client ID as known by the most recently connected server.
     ///
     /// # Example
@@ -890,6 +1414,76 @@ impl Connection {
         self.0.client.server_info.lock().max_payload
     }
 
+    /// Returns the `INFO` sent by the most recently connected server.
+    ///
+    /// The info is populated by the time `connect` returns, so it can be
+    /// used to check server capabilities before publishing anything.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// let nc = nats::connect("demo.nats.io")?;
+    /// let info = nc.server_info();
+    /// if !info.headers {
+    ///     eprintln!("server {} does not support headers", info.server_id);
+    /// }
+    /// println!("max payload: {}", info.max_payload);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn server_info(&self) -> ServerInfo {
+        self.0.client.server_info()
+    }
+
+    /// Returns the certificate the most recently connected server presented
+    /// during the TLS handshake, or `None` if the connection doesn't use TLS.
+    ///
+    /// The certificate has already been verified against the configured root
+    /// certificates. Pinning it, or otherwise checking it beyond that, is up
+    /// to the caller, and has to be repeated after reconnects since the
+    /// client may have connected to a different server.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// let nc = nats::connect("tls://demo.nats.io")?;
+    /// if let Some(cert) = nc.peer_certificate() {
+    ///     println!("connected to {} issued by {}", cert.subject(), cert.issuer());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn peer_certificate(&self) -> Option<Certificate> {
+        self.0.client.peer_certificate()
+    }
+
+    /// Replaces the list of servers used for reconnecting.
+    ///
+    /// The new list takes effect on the next reconnect; the current
+    /// connection is left untouched. Servers discovered through the
+    /// cluster are kept in the pool alongside the given ones.
+    ///
+    /// Returns an error if the list is empty or contains an invalid URL.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// let nc = nats::connect("demo.nats.io")?;
+    /// nc.update_servers(&["nats://10.0.0.1:4222", "nats://10.0.0.2:4222"])?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn update_servers<I: IntoServerList>(&self, nats_urls: I) -> io::Result<()> {
+        let urls = nats_urls.into_server_list()?;
+        if urls.is_empty() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "server list must not be empty",
+            ));
+        }
+        self.0.client.update_servers(urls)
+    }
+
     fn do_subscribe(&self, subject: &str, queue: Option<&str>) -> io::Result<Subscription> {
         let (sid, receiver) = self.0.client.subscribe(subject, queue)?;
         Ok(Subscription::new(
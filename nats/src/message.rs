@@ -12,11 +12,13 @@
 // limitations under the License.
 
 use std::{
+    convert::TryFrom,
     fmt, io,
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc,
     },
+    time::{Duration, Instant},
 };
 
 use crate::{
@@ -27,6 +29,67 @@ use crate::{
 use time::OffsetDateTime;
 
 pub(crate) const MESSAGE_NOT_BOUND: &str = "message not bound to a connection";
+const BUILDER_NOT_BOUND: &str = "publish builder not bound to a connection";
+
+/// Aggregated latencies of `JetStream` acks confirmed by the server.
+///
+/// Only acks sent with [`Message::ack_sync_timed`] or [`Message::double_ack`]
+/// are measured, as regular acks are never confirmed.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct AckStats {
+    /// Number of confirmed acks.
+    pub count: u64,
+
+    /// Sum of all measured latencies.
+    pub total: Duration,
+
+    /// Highest measured latency.
+    pub max: Duration,
+}
+
+impl AckStats {
+    /// Returns the average ack latency, or `None` if no ack was measured yet
+    /// or the average overflows `u64` nanoseconds.
+    pub fn average(&self) -> Option<Duration> {
+        if self.count == 0 {
+            return None;
+        }
+        let nanos = self.total.as_nanos() / u128::from(self.count);
+        u64::try_from(nanos).ok().map(Duration::from_nanos)
+    }
+
+    pub(crate) fn record(&mut self, latency: Duration) {
+        self.count += 1;
+        self.total += latency;
+        self.max = self.max.max(latency);
+    }
+}
+
+/// Shared by all clones of a tracked `JetStream` message. Logs a warning
+/// when the last clone is dropped before the message was acknowledged.
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct AckTracker {
+    reply: String,
+    acked: AtomicBool,
+}
+
+impl AckTracker {
+    pub(crate) fn mark_acked(&self) {
+        self.acked.store(true, Ordering::Release);
+    }
+}
+
+impl Drop for AckTracker {
+    fn drop(&mut self) {
+        if !self.acked.load(Ordering::Acquire) {
+            log::warn!(
+                "JetStream message dropped without being acknowledged, it will be redelivered: {}",
+                self.reply
+            );
+        }
+    }
+}
 
 /// A message received on a subject.
 #[derive(Clone)]
@@ -52,6 +115,11 @@ pub struct Message {
     /// using `JetStream`.
     #[doc(hidden)]
     pub double_acked: Arc<AtomicBool>,
+
+    /// Set if a warning should be logged when this message is dropped
+    /// without being acknowledged.
+    #[doc(hidden)]
+    pub ack_tracker: Option<Arc<AckTracker>>,
 }
 
 impl From<crate::asynk::Message> for Message {
@@ -63,6 +131,7 @@ impl From<crate::asynk::Message> for Message {
             headers: asynk.headers,
             client: asynk.client,
             double_acked: asynk.double_acked,
+            ack_tracker: asynk.ack_tracker,
         }
     }
 }
@@ -100,6 +169,96 @@ impl Message {
         Ok(())
     }
 
+    /// Respond to a request message with `value` serialized as JSON.
+    ///
+    /// Fails with an error of kind `InvalidInput` wrapping the
+    /// `serde_json::Error` if `value` cannot be serialized.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// let sub = nc.subscribe("api.status")?;
+    /// for msg in sub.messages() {
+    ///     msg.respond_json(&serde_json::json!({ "healthy": true }))?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "json")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+    pub fn respond_json<T: serde::Serialize + ?Sized>(&self, value: &T) -> io::Result<()> {
+        self.respond(to_json(value)?)
+    }
+
+    /// Deserializes the message payload from JSON.
+    ///
+    /// A payload that is not valid JSON for `R` fails with an error of kind
+    /// `InvalidData` wrapping the `serde_json::Error`, which tells it apart
+    /// from the I/O errors of the connection.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// #[derive(serde::Deserialize)]
+    /// struct Order {
+    ///     id: u64,
+    /// }
+    ///
+    /// let sub = nc.subscribe("orders")?;
+    /// for msg in sub.messages() {
+    ///     let order: Order = msg.json()?;
+    ///     println!("received order {}", order.id);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "json")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+    pub fn json<R: serde::de::DeserializeOwned>(&self) -> io::Result<R> {
+        serde_json::from_slice(&self.data)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    /// Respond to a request message with `msg` split into chunks of at most
+    /// `chunk_size` bytes, for requesters using
+    /// [`Connection::request_streamed`](crate::Connection::request_streamed).
+    /// An empty `msg` is sent as a single empty chunk.
+    ///
+    /// Each chunk carries its position in the `Nats-Chunk-Seq` header, and
+    /// the last one is marked with `Nats-Chunk-Last`.
+    pub fn respond_chunked(&self, msg: impl AsRef<[u8]>, chunk_size: usize) -> io::Result<()> {
+        if chunk_size == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "chunk size must be greater than 0",
+            ));
+        }
+        let reply = self.reply.as_ref().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "No reply subject to reply to")
+        })?;
+        let client = self
+            .client
+            .as_ref()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotConnected, MESSAGE_NOT_BOUND))?;
+
+        let mut chunks: Vec<&[u8]> = msg.as_ref().chunks(chunk_size).collect();
+        if chunks.is_empty() {
+            chunks.push(&[]);
+        }
+        let last = chunks.len() - 1;
+        for (sequence, chunk) in chunks.into_iter().enumerate() {
+            let mut headers = HeaderMap::new();
+            headers.insert(header::NATS_CHUNK_SEQUENCE, sequence.to_string());
+            if sequence == last {
+                headers.insert(header::NATS_CHUNK_LAST, "true".to_string());
+            }
+            client.publish(reply.as_str(), None, Some(&headers), chunk)?;
+        }
+        Ok(())
+    }
+
     /// Determine if the message is a no responders response from the server.
     pub fn is_no_responders(&self) -> bool {
         if !self.data.is_empty() {
@@ -190,7 +349,9 @@ impl Message {
         if self.double_acked.load(Ordering::Acquire) {
             return Ok(());
         }
-        self.respond(b"")
+        self.respond(b"")?;
+        self.mark_acked();
+        Ok(())
     }
 
     /// Acknowledge a `JetStream` message. See `AckKind` documentation for
@@ -199,7 +360,32 @@ impl Message {
     ///
     /// Does not check whether this message has already been double-acked.
     pub fn ack_kind(&self, ack_kind: crate::jetstream::AckKind) -> io::Result<()> {
-        self.respond(ack_kind)
+        self.respond(ack_kind)?;
+        // Progress only extends the ack deadline, an answer is still required.
+        if !matches!(ack_kind, crate::jetstream::AckKind::Progress) {
+            self.mark_acked();
+        }
+        Ok(())
+    }
+
+    /// Starts tracking whether this `JetStream` message gets acknowledged
+    /// before being dropped.
+    pub(crate) fn track_ack(mut self) -> Message {
+        if let Some(reply) = self.reply.as_ref() {
+            if reply.starts_with("$JS.ACK.") {
+                self.ack_tracker = Some(Arc::new(AckTracker {
+                    reply: reply.clone(),
+                    acked: AtomicBool::new(false),
+                }));
+            }
+        }
+        self
+    }
+
+    fn mark_acked(&self) {
+        if let Some(tracker) = self.ack_tracker.as_ref() {
+            tracker.mark_acked();
+        }
     }
 
     /// Acknowledge a `JetStream` message and wait for acknowledgment from the server
@@ -208,8 +394,39 @@ impl Message {
     ///
     /// Returns immediately if this message has already been double-acked.
     pub fn double_ack(&self, ack_kind: crate::jetstream::AckKind) -> io::Result<()> {
+        self.double_ack_timed(ack_kind).map(|_| ())
+    }
+
+    /// Acknowledge a `JetStream` message like `double_ack` with `AckKind::Ack`,
+    /// and return how long the server took to confirm the ack.
+    ///
+    /// The latency is measured from publishing the ack that got confirmed
+    /// until the confirmation arrived, and is also added to the
+    /// connection wide [`AckStats`]. Returns `Duration::ZERO` if this message
+    /// has already been double-acked.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// # let js = nats::jetstream::new(nc.clone());
+    /// # js.add_stream("ack_sync_timed")?;
+    /// let sub = js.subscribe("ack_sync_timed")?;
+    /// if let Some(message) = sub.next() {
+    ///     let latency = message.ack_sync_timed()?;
+    ///     println!("ack confirmed after {:?}", latency);
+    /// }
+    /// println!("average ack latency: {:?}", nc.ack_stats().average());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn ack_sync_timed(&self) -> io::Result<Duration> {
+        self.double_ack_timed(crate::jetstream::AckKind::Ack)
+    }
+
+    fn double_ack_timed(&self, ack_kind: crate::jetstream::AckKind) -> io::Result<Duration> {
         if self.double_acked.load(Ordering::Acquire) {
-            return Ok(());
+            return Ok(Duration::ZERO);
         }
         let original_reply = match self.reply.as_ref() {
             None => {
@@ -234,24 +451,25 @@ impl Message {
             let ack_reply = format!("_INBOX.{}", nuid::next());
             let sub_ret = client.subscribe(&ack_reply, None);
             if sub_ret.is_err() {
-                std::thread::sleep(std::time::Duration::from_millis(100));
+                std::thread::sleep(Duration::from_millis(100));
                 continue;
             }
             let (sid, receiver) = sub_ret?;
             let sub =
                 crate::Subscription::new(sid, ack_reply.to_string(), receiver, client.clone());
 
+            let start = Instant::now();
             let pub_ret = client.publish(original_reply, Some(&ack_reply), None, ack_kind.as_ref());
             if pub_ret.is_err() {
-                std::thread::sleep(std::time::Duration::from_millis(100));
+                std::thread::sleep(Duration::from_millis(100));
                 continue;
             }
-            if sub
-                .next_timeout(std::time::Duration::from_millis(100))
-                .is_ok()
-            {
+            if sub.next_timeout(Duration::from_millis(100)).is_ok() {
+                let latency = start.elapsed();
                 self.double_acked.store(true, Ordering::Release);
-                return Ok(());
+                self.mark_acked();
+                client.ack_stats.lock().record(latency);
+                return Ok(latency);
             }
         }
     }
@@ -384,6 +602,311 @@ impl Message {
             None
         }
     }
+
+    /// Returns `true` if this `JetStream` message was delivered before,
+    /// for example because it wasn't acknowledged in time.
+    /// Returns `false` for first deliveries and for messages that
+    /// didn't come from `JetStream`.
+    pub fn is_redelivery(&self) -> bool {
+        self.jetstream_message_info()
+            .map_or(false, |info| info.delivered > 1)
+    }
+
+    /// Returns the `Nats-Msg-Id` header, the id `JetStream` uses to drop
+    /// duplicates of a published message.
+    pub fn msg_id(&self) -> Option<&str> {
+        self.headers
+            .as_ref()?
+            .get(header::NATS_MSG_ID)
+            .map(String::as_str)
+    }
+
+    /// Starts building a message to publish.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// nats::Message::builder()
+    ///     .subject("orders")
+    ///     .reply("orders.replies")
+    ///     .content_type("application/json")
+    ///     .payload(r#"{"id":1}"#)
+    ///     .send(&nc)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn builder() -> PublishBuilder {
+        PublishBuilder::default()
+    }
+}
+
+/// A builder for messages to publish, created with [`Message::builder`] or
+/// bound to a connection with [`crate::Connection::publish_builder`].
+///
+/// Messages with headers are sent as `HPUB`, all others as plain `PUB`.
+#[derive(Debug, Default, Clone)]
+pub struct PublishBuilder {
+    subject: String,
+    reply: Option<String>,
+    headers: Option<HeaderMap>,
+    payload: Vec<u8>,
+    connection: Option<crate::Connection>,
+}
+
+impl PublishBuilder {
+    pub(crate) fn bound(connection: crate::Connection, subject: String) -> PublishBuilder {
+        PublishBuilder {
+            subject,
+            connection: Some(connection),
+            ..Default::default()
+        }
+    }
+
+    /// Sets the subject to publish on.
+    pub fn subject(mut self, subject: impl Into<String>) -> PublishBuilder {
+        self.subject = subject.into();
+        self
+    }
+
+    /// Sets the reply subject.
+    pub fn reply(mut self, reply: impl Into<String>) -> PublishBuilder {
+        self.reply = Some(reply.into());
+        self
+    }
+
+    /// Adds a header value, keeping any values already set for `key`.
+    pub fn header(mut self, key: impl Into<String>, value: impl Into<String>) -> PublishBuilder {
+        self.headers
+            .get_or_insert_with(HeaderMap::default)
+            .append(key, value);
+        self
+    }
+
+    /// Replaces all headers.
+    pub fn headers(mut self, headers: HeaderMap) -> PublishBuilder {
+        self.headers = Some(headers);
+        self
+    }
+
+    /// Sets the `Content-Type` header.
+    pub fn content_type(mut self, content_type: impl Into<String>) -> PublishBuilder {
+        self.headers
+            .get_or_insert_with(HeaderMap::default)
+            .insert(header::CONTENT_TYPE, content_type);
+        self
+    }
+
+    /// Sets the message payload.
+    pub fn payload(mut self, payload: impl AsRef<[u8]>) -> PublishBuilder {
+        self.payload = payload.as_ref().to_vec();
+        self
+    }
+
+    /// Builds the message without sending it.
+    pub fn build(self) -> Message {
+        Message {
+            subject: self.subject,
+            reply: self.reply,
+            data: self.payload,
+            headers: self.headers,
+            ..Default::default()
+        }
+    }
+
+    /// Publishes the message on the given connection.
+    pub fn send(&self, nc: &crate::Connection) -> io::Result<()> {
+        nc.publish_with_reply_or_headers(
+            &self.subject,
+            self.reply.as_deref(),
+            self.headers.as_ref(),
+            &self.payload,
+        )
+    }
+
+    /// Publishes the message on the given connection, with a payload of
+    /// `len` bytes read from `payload` instead of the one set with
+    /// [`PublishBuilder::payload`]. The payload is copied to the server in
+    /// bounded chunks, so large messages don't have to be held in memory.
+    ///
+    /// The size has to be known up front, as it is declared before the
+    /// payload. Messages whose headers and payload exceed
+    /// [`crate::Connection::max_payload`] fail with a
+    /// [`crate::ValidationError::PayloadTooLarge`] before anything is sent.
+    /// Reading stops after `len` bytes. If `payload` fails or ends before,
+    /// the message cannot be completed and the connection is re-established.
+    ///
+    /// Other publishes on the connection wait until the whole payload has
+    /// been copied, so a slow source holds them up.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// let file = std::fs::File::open("backup.tar")?;
+    /// let len = file.metadata()?.len() as usize;
+    /// nats::Message::builder()
+    ///     .subject("backups")
+    ///     .content_type("application/x-tar")
+    ///     .send_from_reader(&nc, file, len)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn send_from_reader(
+        &self,
+        nc: &crate::Connection,
+        mut payload: impl io::Read,
+        len: usize,
+    ) -> io::Result<()> {
+        nc.0.client.publish_streamed(
+            &self.subject,
+            self.reply.as_deref(),
+            self.headers.as_ref(),
+            &mut payload,
+            len,
+        )
+    }
+
+    /// Publishes the message on the given connection, with a payload of
+    /// `len` bytes taken from `chunks` in order. Works like
+    /// [`PublishBuilder::send_from_reader`], so the chunks can be produced
+    /// lazily and don't have to be in memory all at once.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// let chunks = vec!["hello", " ", "world"];
+    /// nats::Message::builder()
+    ///     .subject("greetings")
+    ///     .send_chunks(&nc, chunks, 11)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn send_chunks<I>(&self, nc: &crate::Connection, chunks: I, len: usize) -> io::Result<()>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        self.send_from_reader(nc, ChunkReader::new(chunks.into_iter()), len)
+    }
+
+    /// Publishes the message on the connection the builder was created
+    /// from, see [`PublishBuilder::send`].
+    ///
+    /// Fails with `NotConnected` if the builder came from
+    /// [`Message::builder`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// nc.publish_builder("orders")
+    ///     .reply("orders.replies")
+    ///     .payload("hello")
+    ///     .publish()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn publish(&self) -> io::Result<()> {
+        self.send(self.connection()?)
+    }
+
+    /// Publishes the message on the connection the builder was created
+    /// from, with a payload read from `payload`, see
+    /// [`PublishBuilder::send_from_reader`].
+    ///
+    /// Fails with `NotConnected` if the builder came from
+    /// [`Message::builder`].
+    pub fn publish_from_reader(&self, payload: impl io::Read, len: usize) -> io::Result<()> {
+        self.send_from_reader(self.connection()?, payload, len)
+    }
+
+    /// Publishes the message on the connection the builder was created
+    /// from, with a payload taken from `chunks`, see
+    /// [`PublishBuilder::send_chunks`].
+    ///
+    /// Fails with `NotConnected` if the builder came from
+    /// [`Message::builder`].
+    pub fn publish_chunks<I>(&self, chunks: I, len: usize) -> io::Result<()>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        self.send_chunks(self.connection()?, chunks, len)
+    }
+
+    fn connection(&self) -> io::Result<&crate::Connection> {
+        self.connection
+            .as_ref()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotConnected, BUILDER_NOT_BOUND))
+    }
+
+    /// Publishes the message to a `JetStream` stream and waits for the
+    /// stream to acknowledge it. The reply subject is not used, as
+    /// `JetStream` replies with the ack.
+    pub fn send_jetstream(
+        &self,
+        js: &crate::jetstream::JetStream,
+        options: Option<&crate::jetstream::PublishOptions>,
+    ) -> io::Result<crate::jetstream::PublishAck> {
+        js.publish_with_options_or_headers(
+            &self.subject,
+            options,
+            self.headers.as_ref(),
+            &self.payload,
+        )
+    }
+}
+
+/// Reads the bytes of a sequence of chunks.
+struct ChunkReader<I: Iterator> {
+    chunks: I,
+    current: Option<I::Item>,
+    offset: usize,
+}
+
+impl<I: Iterator> ChunkReader<I> {
+    fn new(chunks: I) -> ChunkReader<I> {
+        ChunkReader {
+            chunks,
+            current: None,
+            offset: 0,
+        }
+    }
+}
+
+impl<I> io::Read for ChunkReader<I>
+where
+    I: Iterator,
+    I::Item: AsRef<[u8]>,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            if let Some(chunk) = &self.current {
+                let rest = &chunk.as_ref()[self.offset..];
+                if !rest.is_empty() {
+                    let n = rest.len().min(buf.len());
+                    buf[..n].copy_from_slice(&rest[..n]);
+                    self.offset += n;
+                    return Ok(n);
+                }
+            }
+            match self.chunks.next() {
+                Some(chunk) => {
+                    self.current = Some(chunk);
+                    self.offset = 0;
+                }
+                None => return Ok(0),
+            }
+        }
+    }
+}
+
+/// Serializes a payload as JSON, failing with `InvalidInput` if it can't be.
+#[cfg(feature = "json")]
+pub(crate) fn to_json<T: serde::Serialize + ?Sized>(value: &T) -> io::Result<Vec<u8>> {
+    serde_json::to_vec(value).map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))
 }
 
 impl Default for Message {
@@ -395,6 +918,7 @@ impl Default for Message {
             headers: None,
             client: None,
             double_acked: Arc::new(AtomicBool::new(false)),
+            ack_tracker: None,
         }
     }
 }
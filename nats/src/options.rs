@@ -25,8 +25,51 @@ use crate::secure_wipe::SecureString;
 use crate::Client;
 use crate::Connection;
 use crate::IntoServerList;
+use crate::SrvResolver;
+
+/// What publishing does while the client is disconnected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutboundPolicy {
+    /// Queue messages in the reconnect buffer, sized with
+    /// `Options::reconnect_buffer_size`, and send them once reconnected.
+    /// Publishing fails once the buffer is full. This is the default.
+    Buffer,
+    /// Fail every publish with an error of kind `NotConnected`.
+    ErrorImmediately,
+    /// Wait for the client to reconnect, failing with an error of kind
+    /// `TimedOut` if it hasn't within the given duration.
+    Block(Duration),
+}
 
 /// Connect options.
+///
+/// # Reconnecting
+///
+/// A lost connection is re-established in the background: the CONNECT
+/// handshake is replayed with the configured credentials and all active
+/// subscriptions are restored. How this happens is set with:
+///
+/// * [`Options::max_reconnects`] and [`Options::reconnect_delay_callback`]
+///   for the number of attempts per server and the backoff between them,
+/// * [`Options::disconnect_callback`], [`Options::reconnect_callback`] and
+///   [`Options::close_callback`] to be notified along the way,
+/// * [`Options::outbound_policy`] and [`Options::reconnect_buffer_size`]
+///   to buffer publishes made while disconnected, or fail them.
+///
+/// ```no_run
+/// # use std::time::Duration;
+/// # fn main() -> std::io::Result<()> {
+/// let nc = nats::Options::new()
+///     .max_reconnects(10)
+///     .reconnect_delay_callback(|attempts| Duration::from_millis(100 << attempts.min(6)))
+///     .disconnect_callback(|| println!("disconnected"))
+///     .reconnect_callback(|| println!("reconnected"))
+///     .close_callback(|| println!("closed"))
+///     .outbound_policy(nats::OutboundPolicy::ErrorImmediately)
+///     .connect("demo.nats.io")?;
+/// # Ok(())
+/// # }
+/// ```
 pub struct Options {
     pub(crate) auth: AuthStyle,
     pub(crate) name: Option<String>,
@@ -34,11 +77,23 @@ pub struct Options {
     pub(crate) retry_on_failed_connect: bool,
     pub(crate) max_reconnects: Option<usize>,
     pub(crate) reconnect_buffer_size: usize,
+    pub(crate) outbound_policy: OutboundPolicy,
     pub(crate) tls_required: bool,
     pub(crate) certificates: Vec<PathBuf>,
     pub(crate) client_cert: Option<PathBuf>,
     pub(crate) client_key: Option<PathBuf>,
     pub(crate) tls_client_config: Option<crate::rustls::ClientConfig>,
+    pub(crate) srv_discovery: bool,
+    pub(crate) srv_resolver: Option<Arc<dyn SrvResolver>>,
+    pub(crate) no_failover: bool,
+    pub(crate) tcp_keepalive: Option<Duration>,
+    pub(crate) local_subscription_mux: bool,
+    pub(crate) publish_permission_cache: bool,
+    pub(crate) auto_flush_interval: Option<Duration>,
+    pub(crate) request_timeout: Option<Duration>,
+    pub(crate) request_retries: usize,
+    #[cfg(feature = "unstable-testing")]
+    pub(crate) testing: crate::testing::TestingOptions,
 
     pub(crate) error_callback: ErrorCallback,
     pub(crate) disconnect_callback: Callback,
@@ -50,25 +105,44 @@ pub struct Options {
 
 impl fmt::Debug for Options {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
-        f.debug_map()
-            .entry(&"auth", &self.auth)
+        let mut map = f.debug_map();
+        map.entry(&"auth", &self.auth)
             .entry(&"name", &self.name)
             .entry(&"no_echo", &self.no_echo)
             .entry(&"retry_on_failed_connect", &self.retry_on_failed_connect)
             .entry(&"reconnect_buffer_size", &self.reconnect_buffer_size)
+            .entry(&"outbound_policy", &self.outbound_policy)
             .entry(&"max_reconnects", &self.max_reconnects)
             .entry(&"tls_required", &self.tls_required)
             .entry(&"certificates", &self.certificates)
             .entry(&"client_cert", &self.client_cert)
             .entry(&"client_key", &self.client_key)
             .entry(&"tls_client_config", &"XXXXXXXX")
+            .entry(&"srv_discovery", &self.srv_discovery)
+            .entry(
+                &"srv_resolver",
+                if self.srv_resolver.is_some() {
+                    &"set"
+                } else {
+                    &"unset"
+                },
+            )
+            .entry(&"no_failover", &self.no_failover)
+            .entry(&"tcp_keepalive", &self.tcp_keepalive)
+            .entry(&"local_subscription_mux", &self.local_subscription_mux)
+            .entry(&"publish_permission_cache", &self.publish_permission_cache)
+            .entry(&"auto_flush_interval", &self.auto_flush_interval)
+            .entry(&"request_timeout", &self.request_timeout)
+            .entry(&"request_retries", &self.request_retries)
             .entry(&"error_callback", &self.error_callback)
             .entry(&"disconnect_callback", &self.disconnect_callback)
             .entry(&"reconnect_callback", &self.reconnect_callback)
             .entry(&"reconnect_delay_callback", &"set")
             .entry(&"close_callback", &self.close_callback)
-            .entry(&"lame_duck_callback", &self.lame_duck_callback)
-            .finish()
+            .entry(&"lame_duck_callback", &self.lame_duck_callback);
+        #[cfg(feature = "unstable-testing")]
+        map.entry(&"testing", &self.testing);
+        map.finish()
     }
 }
 
@@ -80,6 +154,7 @@ impl Default for Options {
             no_echo: false,
             retry_on_failed_connect: false,
             reconnect_buffer_size: 8 * 1024 * 1024,
+            outbound_policy: OutboundPolicy::Buffer,
             max_reconnects: Some(60),
             tls_required: false,
             certificates: Vec::new(),
@@ -92,6 +167,17 @@ impl Default for Options {
             close_callback: Callback(None),
             lame_duck_callback: Callback(None),
             tls_client_config: None,
+            srv_discovery: false,
+            srv_resolver: None,
+            no_failover: false,
+            tcp_keepalive: None,
+            local_subscription_mux: false,
+            publish_permission_cache: false,
+            auto_flush_interval: None,
+            request_timeout: None,
+            request_retries: 0,
+            #[cfg(feature = "unstable-testing")]
+            testing: Default::default(),
         }
     }
 }
@@ -453,6 +539,331 @@ impl Options {
         self
     }
 
+    /// Set what publishing does while the client is disconnected and
+    /// trying to reconnect.
+    ///
+    /// The default is `OutboundPolicy::Buffer`, which queues messages in
+    /// the reconnect buffer.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::time::Duration;
+    /// # fn main() -> std::io::Result<()> {
+    /// let nc = nats::Options::new()
+    ///     .outbound_policy(nats::OutboundPolicy::Block(Duration::from_secs(5)))
+    ///     .connect("demo.nats.io")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn outbound_policy(mut self, outbound_policy: OutboundPolicy) -> Options {
+        self.outbound_policy = outbound_policy;
+        self
+    }
+
+    /// Enable TCP keepalive on the connection to the server, sending the
+    /// first probe after the socket has been idle for the given duration and
+    /// repeating it at the same interval. `None` leaves the operating system
+    /// defaults untouched, which is the default.
+    ///
+    /// The client also sends a PING every two minutes and gives up on the
+    /// connection after two unanswered ones, so it may take several minutes
+    /// to notice a dead peer. Keepalive probes are handled by the kernel and
+    /// can detect that much sooner, and they keep idle connections from being
+    /// dropped by NAT gateways or firewalls with short timeouts. The setting is
+    /// applied to every socket, including those opened when reconnecting.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::time::Duration;
+    /// # fn main() -> std::io::Result<()> {
+    /// let nc = nats::Options::new()
+    ///     .with_tcp_keepalive(Some(Duration::from_secs(30)))
+    ///     .connect("demo.nats.io")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_tcp_keepalive(mut self, keepalive: Option<Duration>) -> Options {
+        self.tcp_keepalive = keepalive;
+        self
+    }
+
+    /// Share one server subscription between local subscribers to the same
+    /// subject and queue group.
+    ///
+    /// By default every call to `subscribe` or `queue_subscribe` registers a
+    /// separate subscription with the server, so a message matching several
+    /// of them crosses the wire once per subscription. With multiplexing
+    /// enabled, the first subscriber to a subject and queue group pair sends
+    /// the SUB and later ones attach to it locally. Each incoming message is
+    /// then copied to every attached subscriber. The server subscription is
+    /// removed once the last local subscriber unsubscribes or is dropped.
+    ///
+    /// Subscribers sharing a server subscription see messages in the order
+    /// the server delivered them, and each one is handed a message before the
+    /// next message is dispatched. Pending limits are still enforced per
+    /// local subscriber: a slow consumer only drops its own copies and is
+    /// reported through the error callback without affecting the others.
+    ///
+    /// Queue subscribers that share a server subscription take turns, so
+    /// each message delivered to this client goes to one of them, the same
+    /// as without multiplexing. Subscriptions created by `JetStream` are never
+    /// multiplexed. Defaults to `false`.
+    ///
+    /// # Example
+    /// ```
+    /// # fn main() -> std::io::Result<()> {
+    /// let nc = nats::Options::new()
+    ///     .with_local_subscription_mux(true)
+    ///     .connect("demo.nats.io")?;
+    /// let first = nc.subscribe("foo")?;
+    /// let second = nc.subscribe("foo")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_local_subscription_mux(mut self, mux: bool) -> Options {
+        self.local_subscription_mux = mux;
+        self
+    }
+
+    /// Remember subjects the server refused to let this connection publish
+    /// to, and fail further publishes to them right away with an error of
+    /// kind `PermissionDenied` instead of sending them.
+    ///
+    /// A subject is remembered once the server reports a permissions
+    /// violation for it, so the publish that triggered it still succeeds
+    /// locally and the violation is reported through the error callback as
+    /// usual. The remembered subjects are forgotten on reconnect, since the
+    /// permissions may have changed in the meantime. Defaults to `false`.
+    ///
+    /// # Example
+    /// ```
+    /// # fn main() -> std::io::Result<()> {
+    /// let nc = nats::Options::new()
+    ///     .with_publish_permission_cache(true)
+    ///     .connect("demo.nats.io")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_publish_permission_cache(mut self, cache: bool) -> Options {
+        self.publish_permission_cache = cache;
+        self
+    }
+
+    /// Flush the write buffer on a timer, in addition to after publishing.
+    ///
+    /// The write buffer is flushed by a background thread shortly after every
+    /// publish, which stays the case with an interval set. The timer is a
+    /// backstop on top of that: every `interval` the thread also writes out
+    /// whatever is still buffered, so no message sits unsent for longer than
+    /// `interval`, however the publishes that came before it were coalesced.
+    /// `None`, or a zero interval, disables the timer, which is the default.
+    ///
+    /// [`Connection::flush`](crate::Connection::flush) doesn't wait for the
+    /// timer. It writes out everything buffered right away and then waits for
+    /// the server to respond, after which the next tick finds nothing left to
+    /// flush.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::time::Duration;
+    /// # fn main() -> std::io::Result<()> {
+    /// let nc = nats::Options::new()
+    ///     .with_auto_flush_interval(Some(Duration::from_millis(10)))
+    ///     .connect("demo.nats.io")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_auto_flush_interval(mut self, interval: Option<Duration>) -> Options {
+        self.auto_flush_interval = interval.filter(|interval| !interval.is_zero());
+        self
+    }
+
+    /// Set the timeout for requests that don't specify one, such as
+    /// [`Connection::request`](crate::Connection::request). Once it elapses
+    /// without a response the request fails with an error of kind `TimedOut`.
+    ///
+    /// By default, or with `None`, those requests wait for a response until
+    /// the connection is closed.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::time::Duration;
+    /// # fn main() -> std::io::Result<()> {
+    /// let nc = nats::Options::new()
+    ///     .with_request_timeout(Some(Duration::from_secs(5)))
+    ///     .connect("demo.nats.io")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_request_timeout(mut self, timeout: Option<Duration>) -> Options {
+        self.request_timeout = timeout;
+        self
+    }
+
+    /// Set how many times a request is sent again after the server reported
+    /// that there were no responders for it, for example while a service is
+    /// restarting. Retries back off a little more each time and stay within
+    /// the request's timeout. Once all of them are used up the request fails
+    /// with an error of kind `NotFound`.
+    ///
+    /// Defaults to 0, failing on the first no responders status.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::time::Duration;
+    /// # fn main() -> std::io::Result<()> {
+    /// let nc = nats::Options::new()
+    ///     .with_request_timeout(Some(Duration::from_secs(5)))
+    ///     .with_request_retries(3)
+    ///     .connect("demo.nats.io")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_request_retries(mut self, retries: usize) -> Options {
+        self.request_retries = retries;
+        self
+    }
+
+    /// Set how long the connection may be idle before the client sends a PING
+    /// to check that the server is still alive. After two unanswered PINGs
+    /// the connection is considered stale and the client reconnects.
+    ///
+    /// Defaults to two minutes. **Not for production use**, this is only
+    /// meant to make stale connection detection fast enough for tests, see
+    /// the [`crate::testing`] module.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::time::Duration;
+    /// # fn main() -> std::io::Result<()> {
+    /// let nc = nats::Options::new()
+    ///     .with_ping_interval(Duration::from_millis(100))
+    ///     .connect("demo.nats.io")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "unstable-testing")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "unstable-testing")))]
+    pub fn with_ping_interval(mut self, ping_interval: Duration) -> Options {
+        self.testing.ping_interval = Some(ping_interval);
+        self
+    }
+
+    /// Set a hook that is called for every PONG sent or received by the
+    /// client, and decides whether it is handled normally, delayed or
+    /// dropped.
+    ///
+    /// Dropping received PONGs, combined with a short
+    /// [`Options::with_ping_interval`], simulates a dead server and makes the
+    /// client disconnect and reconnect. Dropping sent PONGs makes the server
+    /// consider this client stale instead. **Not for production use**, see
+    /// the [`crate::testing`] module.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::time::Duration;
+    /// use nats::testing::{PongAction, PongEvent};
+    /// # fn main() -> std::io::Result<()> {
+    /// let nc = nats::Options::new()
+    ///     .with_ping_interval(Duration::from_millis(100))
+    ///     .with_pong_hook(|event| match event {
+    ///         PongEvent::Received => PongAction::Drop,
+    ///         PongEvent::Sent => PongAction::Continue,
+    ///     })
+    ///     .connect("demo.nats.io")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "unstable-testing")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "unstable-testing")))]
+    pub fn with_pong_hook<F>(mut self, hook: F) -> Options
+    where
+        F: Fn(crate::testing::PongEvent) -> crate::testing::PongAction + Send + Sync + 'static,
+    {
+        self.testing.pong_hook = Some(Arc::new(hook));
+        self
+    }
+
+    /// Resolve server URLs whose host is a DNS SRV name, such as
+    /// `nats://_nats._tcp.example.com`, into the targets published in DNS.
+    ///
+    /// Targets are tried in order of SRV priority, with a weighted random
+    /// choice among targets of equal priority. The records are resolved again
+    /// on every reconnect, so changes to the cluster are picked up without
+    /// restarting the client. The port of the URL is ignored.
+    ///
+    /// Lookups use the system DNS configuration if the `hickory-resolver`
+    /// feature is enabled. Otherwise a resolver has to be set with
+    /// [`Options::with_srv_resolver`], or connecting fails with an error of
+    /// kind `Unsupported`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// let nc = nats::Options::new()
+    ///     .with_srv_discovery(true)
+    ///     .connect("nats://_nats._tcp.example.com")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_srv_discovery(mut self, srv_discovery: bool) -> Options {
+        self.srv_discovery = srv_discovery;
+        self
+    }
+
+    /// Set the resolver used for SRV lookups when [`Options::with_srv_discovery`]
+    /// is enabled, instead of the system resolver of the `hickory-resolver` feature.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// use nats::{SrvRecord, SrvResolver};
+    ///
+    /// struct Static;
+    ///
+    /// impl SrvResolver for Static {
+    ///     fn resolve_srv(&self, _name: &str) -> std::io::Result<Vec<SrvRecord>> {
+    ///         Ok(vec![SrvRecord {
+    ///             priority: 10,
+    ///             weight: 1,
+    ///             port: 4222,
+    ///             target: "demo.nats.io".to_string(),
+    ///         }])
+    ///     }
+    /// }
+    ///
+    /// let nc = nats::Options::new()
+    ///     .with_srv_discovery(true)
+    ///     .with_srv_resolver(Static)
+    ///     .connect("nats://_nats._tcp.example.com")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_srv_resolver(mut self, resolver: impl SrvResolver + 'static) -> Options {
+        self.srv_resolver = Some(Arc::new(resolver));
+        self
+    }
+
+    /// Keep the client on the servers it was configured with, ignoring the
+    /// other servers of the cluster advertised by the server it is connected
+    /// to. This is for setups where only specific endpoints are allowed to
+    /// be reached. Servers set later with [`Connection::update_servers`] are
+    /// still used.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// let nc = nats::Options::new()
+    ///     .with_no_failover(true)
+    ///     .connect("nats://gateway.example.com:4222")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_no_failover(mut self, no_failover: bool) -> Options {
+        self.no_failover = no_failover;
+        self
+    }
+
     /// Establish a `Connection` with one or more NATS servers.
     ///
     /// To pass more than one URL check out the the documentation of [`crate::connect()`].
@@ -482,6 +893,42 @@ impl Options {
         Connection::connect_with_options(nats_urls, self)
     }
 
+    /// Establish a `Connection` over an already connected stream, instead of
+    /// opening a TCP connection to a server URL. This can be used to tunnel
+    /// the connection through a custom transport, or to talk to an in-process
+    /// test server.
+    ///
+    /// The client performs the NATS handshake over the stream as is. It is
+    /// never upgraded to TLS, so for servers requiring TLS the stream must
+    /// already be secured and the server configured for a TLS first
+    /// handshake. The stream has to support reading and writing concurrently
+    /// through shared references, like `TcpStream` or `UnixStream` do.
+    /// Transports that can only be read and written concurrently through
+    /// separate handles, such as ones split with `try_clone`, are not
+    /// supported.
+    ///
+    /// **Note:** The connection cannot be re-established once the stream is
+    /// lost, since there is no way to create a new one. The connection is
+    /// closed instead, and servers discovered through INFO messages are not
+    /// used.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// let stream = std::net::TcpStream::connect("demo.nats.io:4222")?;
+    /// let nc = nats::Options::new().connect_with_stream(stream)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn connect_with_stream<S>(self, stream: S) -> io::Result<Connection>
+    where
+        S: Send + Sync + 'static,
+        for<'a> &'a S: io::Read + io::Write,
+    {
+        Connection::connect_with_stream(crate::connector::NatsStream::custom(stream), self)
+    }
+
     /// Set a callback to be executed when an async error from
     /// a server has been received.
     ///
@@ -643,6 +1090,8 @@ impl Options {
     /// Adds a root certificate file.
     ///
     /// The file must be PEM encoded. All certificates in the file will be used.
+    /// Without any root certificate, the platform's are used, or the bundled
+    /// webpki roots if none can be loaded.
     ///
     /// # Examples
     /// ```no_run
@@ -291,6 +291,80 @@ pub(crate) fn decode(mut stream: impl BufRead) -> io::Result<Option<ServerOp>> {
     Ok(Some(ServerOp::Unknown(line.to_owned())))
 }
 
+/// Error returned for input that would corrupt the protocol stream if it was
+/// written to the server, such as a subject containing `\r\n`.
+///
+/// It is returned wrapped in an `io::Error` of kind `InvalidInput` and can be
+/// recovered with `into_inner` and `downcast`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ValidationError {
+    /// A subject, reply subject or queue group is empty or contains whitespace.
+    InvalidSubject(String),
+
+    /// A header name is empty or contains `:`, whitespace or control
+    /// characters, or a header value contains `\r` or `\n`.
+    InvalidHeader(String),
+
+    /// The headers and payload of a message together exceed the max payload
+    /// of the server, which would close the connection on receiving it.
+    PayloadTooLarge {
+        /// Size of the headers and payload in bytes.
+        size: usize,
+        /// Max payload the server announced in its INFO.
+        max_payload: usize,
+    },
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValidationError::InvalidSubject(subject) => write!(f, "invalid subject: {subject:?}"),
+            ValidationError::InvalidHeader(header) => write!(f, "invalid header: {header:?}"),
+            ValidationError::PayloadTooLarge { size, max_payload } => write!(
+                f,
+                "payload of {size} bytes exceeds the max payload of {max_payload} bytes"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// Checks that a subject, reply subject or queue group can be written as a
+/// single protocol token.
+pub(crate) fn validate_subject(subject: &str) -> io::Result<()> {
+    if subject.is_empty() || subject.bytes().any(|b| b.is_ascii_whitespace()) {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            ValidationError::InvalidSubject(subject.to_string()),
+        ));
+    }
+    Ok(())
+}
+
+/// Checks that all header names and values can be written without breaking
+/// the header block framing.
+pub(crate) fn validate_headers(headers: &HeaderMap) -> io::Result<()> {
+    for (name, values) in headers.iter() {
+        // Names and values are trimmed when encoded.
+        let trimmed = name.trim();
+        if trimmed.is_empty() || trimmed.bytes().any(|b| b == b':' || !b.is_ascii_graphic()) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                ValidationError::InvalidHeader(name.clone()),
+            ));
+        }
+        if let Some(value) = values.iter().find(|value| value.contains(['\r', '\n'])) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                ValidationError::InvalidHeader(format!("{name}: {value}")),
+            ));
+        }
+    }
+    Ok(())
+}
+
 /// A protocol operation sent by the client.
 #[derive(Clone, Copy, Debug)]
 pub(crate) enum ClientOp<'a> {
@@ -348,18 +422,7 @@ pub(crate) fn encode(mut stream: impl Write, op: ClientOp<'_>) -> io::Result<()>
             reply_to,
             payload,
         } => {
-            stream.write_all(b"PUB ")?;
-            stream.write_all(subject.as_bytes())?;
-            stream.write_all(b" ")?;
-
-            if let Some(reply_to) = reply_to {
-                stream.write_all(reply_to.as_bytes())?;
-                stream.write_all(b" ")?;
-            }
-
-            let mut buf = itoa::Buffer::new();
-            stream.write_all(buf.format(payload.len()).as_bytes())?;
-            stream.write_all(b"\r\n")?;
+            encode_pub_line(&mut stream, subject, *reply_to, None, payload.len())?;
 
             stream.write_all(payload)?;
             stream.write_all(b"\r\n")?;
@@ -371,29 +434,14 @@ pub(crate) fn encode(mut stream: impl Write, op: ClientOp<'_>) -> io::Result<()>
             headers,
             payload,
         } => {
-            stream.write_all(b"HPUB ")?;
-            stream.write_all(subject.as_bytes())?;
-            stream.write_all(b" ")?;
-
-            if let Some(reply_to) = reply_to {
-                stream.write_all(reply_to.as_bytes())?;
-                stream.write_all(b" ")?;
-            }
-
             let header_bytes = headers.to_bytes();
-
-            let header_len = header_bytes.len();
-            let total_len = header_len + payload.len();
-
-            let mut hlen_buf = itoa::Buffer::new();
-            stream.write_all(hlen_buf.format(header_len).as_bytes())?;
-
-            stream.write_all(b" ")?;
-
-            let mut tlen_buf = itoa::Buffer::new();
-            stream.write_all(tlen_buf.format(total_len).as_bytes())?;
-
-            stream.write_all(b"\r\n")?;
+            encode_pub_line(
+                &mut stream,
+                subject,
+                *reply_to,
+                Some(&header_bytes),
+                payload.len(),
+            )?;
 
             stream.write_all(&header_bytes)?;
             stream.write_all(payload)?;
@@ -433,3 +481,148 @@ pub(crate) fn encode(mut stream: impl Write, op: ClientOp<'_>) -> io::Result<()>
 
     Ok(())
 }
+
+/// Encodes a `PUB`, or an `HPUB` if there are headers, whose payload of `len`
+/// bytes is copied from `payload` instead of being held in memory.
+///
+/// Fails with `UnexpectedEof` if `payload` ends before `len` bytes, leaving a
+/// partial message in the stream.
+pub(crate) fn encode_streamed(
+    mut stream: impl Write,
+    subject: &str,
+    reply_to: Option<&str>,
+    headers: Option<&HeaderMap>,
+    payload: &mut dyn Read,
+    len: usize,
+) -> io::Result<()> {
+    let header_bytes = headers.map(HeaderMap::to_bytes);
+    encode_pub_line(&mut stream, subject, reply_to, header_bytes.as_deref(), len)?;
+    if let Some(header_bytes) = &header_bytes {
+        stream.write_all(header_bytes)?;
+    }
+
+    let copied = io::copy(&mut payload.take(len as u64), &mut stream)?;
+    if copied < len as u64 {
+        return Err(short_payload(len, copied));
+    }
+    stream.write_all(b"\r\n")
+}
+
+/// The error for a streamed payload ending before its declared length.
+pub(crate) fn short_payload(len: usize, read: u64) -> Error {
+    Error::new(
+        ErrorKind::UnexpectedEof,
+        format!("payload ended after {read} of {len} bytes"),
+    )
+}
+
+/// Encodes the protocol line of a `PUB`, or of an `HPUB` if there are
+/// headers, declaring the size of the message that follows it.
+fn encode_pub_line(
+    mut stream: impl Write,
+    subject: &str,
+    reply_to: Option<&str>,
+    header_bytes: Option<&[u8]>,
+    payload_len: usize,
+) -> io::Result<()> {
+    stream.write_all(if header_bytes.is_some() {
+        b"HPUB "
+    } else {
+        b"PUB "
+    })?;
+    stream.write_all(subject.as_bytes())?;
+    stream.write_all(b" ")?;
+
+    if let Some(reply_to) = reply_to {
+        stream.write_all(reply_to.as_bytes())?;
+        stream.write_all(b" ")?;
+    }
+
+    if let Some(header_bytes) = header_bytes {
+        let mut hlen_buf = itoa::Buffer::new();
+        stream.write_all(hlen_buf.format(header_bytes.len()).as_bytes())?;
+        stream.write_all(b" ")?;
+    }
+
+    let total_len = header_bytes.map_or(0, <[u8]>::len) + payload_len;
+    let mut tlen_buf = itoa::Buffer::new();
+    stream.write_all(tlen_buf.format(total_len).as_bytes())?;
+    stream.write_all(b"\r\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validation_error(err: io::Error) -> ValidationError {
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+        *err.into_inner().unwrap().downcast().unwrap()
+    }
+
+    #[test]
+    fn subject_with_crlf() {
+        let err = validate_subject("foo\r\nPUB bar 0").unwrap_err();
+        assert_eq!(
+            validation_error(err),
+            ValidationError::InvalidSubject("foo\r\nPUB bar 0".to_string())
+        );
+        assert!(validate_subject("foo bar").is_err());
+        assert!(validate_subject("").is_err());
+        assert!(validate_subject("foo.*.>").is_ok());
+    }
+
+    #[test]
+    fn header_with_crlf() {
+        let mut headers = HeaderMap::new();
+        headers.insert("Nats-Msg-Id", "1");
+        assert!(validate_headers(&headers).is_ok());
+
+        headers.insert("Injected", "value\r\n\r\nPUB bar 0");
+        let err = validate_headers(&headers).unwrap_err();
+        assert_eq!(
+            validation_error(err),
+            ValidationError::InvalidHeader("Injected: value\r\n\r\nPUB bar 0".to_string())
+        );
+
+        let mut headers = HeaderMap::new();
+        headers.insert("Bad\r\nName", "value");
+        assert!(validate_headers(&headers).is_err());
+
+        let mut headers = HeaderMap::new();
+        headers.insert("Bad:Name", "value");
+        assert!(validate_headers(&headers).is_err());
+    }
+
+    #[test]
+    fn streamed_matches_encoded() {
+        let mut headers = HeaderMap::new();
+        headers.insert("Nats-Msg-Id", "1");
+
+        for headers in [None, Some(&headers)] {
+            let mut encoded = Vec::new();
+            let op = match headers {
+                Some(headers) => ClientOp::Hpub {
+                    subject: "foo",
+                    reply_to: Some("bar"),
+                    headers,
+                    payload: b"hello",
+                },
+                None => ClientOp::Pub {
+                    subject: "foo",
+                    reply_to: Some("bar"),
+                    payload: b"hello",
+                },
+            };
+            encode(&mut encoded, op).unwrap();
+
+            let mut streamed = Vec::new();
+            let mut payload: &[u8] = b"hello world";
+            encode_streamed(&mut streamed, "foo", Some("bar"), headers, &mut payload, 5).unwrap();
+            assert_eq!(streamed, encoded);
+        }
+
+        let mut payload: &[u8] = b"hi";
+        let err = encode_streamed(Vec::new(), "foo", None, None, &mut payload, 5).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    }
+}
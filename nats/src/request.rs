@@ -0,0 +1,309 @@
+// Copyright 2020-2022 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::error;
+use std::fmt;
+use std::io::{self, Error, ErrorKind};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::header::HeaderMap;
+use crate::message::Message;
+use crate::subscription::Subscription;
+
+/// Header carrying the description of an error a service responded with.
+const SERVICE_ERROR: &str = "Nats-Service-Error";
+
+/// Header carrying the code of an error a service responded with.
+const SERVICE_ERROR_CODE: &str = "Nats-Service-Error-Code";
+
+/// An error a service responded with, following the NATS service framework
+/// convention of `Nats-Service-Error` and `Nats-Service-Error-Code` headers.
+///
+/// [`Connection::request_full`](crate::Connection::request_full) returns it
+/// wrapped in an `io::Error`, from which it can be recovered with
+/// `into_inner` and `downcast`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ServiceError {
+    /// The error code, such as 404 or 500.
+    pub code: u16,
+    /// The description of the error.
+    pub description: String,
+}
+
+impl ServiceError {
+    /// Parses the error a service responded with from the reply headers.
+    /// Returns `None` for replies without service error headers.
+    pub fn from_headers(headers: &HeaderMap) -> Option<ServiceError> {
+        let description = headers.get(SERVICE_ERROR);
+        let code = headers.get(SERVICE_ERROR_CODE);
+        if description.is_none() && code.is_none() {
+            return None;
+        }
+        Some(ServiceError {
+            code: code.and_then(|code| code.parse().ok()).unwrap_or_default(),
+            description: description.cloned().unwrap_or_default(),
+        })
+    }
+}
+
+impl fmt::Display for ServiceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "service error {}: {}", self.code, self.description)
+    }
+}
+
+impl error::Error for ServiceError {}
+
+/// A request that was sent and whose response is still to be received,
+/// created with [`Connection::request_handle`](crate::Connection::request_handle).
+///
+/// Clones refer to the same request, so one thread can wait for the response
+/// while another one cancels it.
+#[derive(Clone, Debug)]
+pub struct RequestHandle {
+    sub: Subscription,
+    cancelled: Arc<AtomicBool>,
+    /// When the default request timeout of the connection runs out.
+    deadline: Option<Instant>,
+}
+
+impl RequestHandle {
+    pub(crate) fn new(sub: Subscription, deadline: Option<Instant>) -> RequestHandle {
+        RequestHandle {
+            sub,
+            cancelled: Arc::new(AtomicBool::new(false)),
+            deadline,
+        }
+    }
+
+    /// Blocks until the response arrives, or until the timeout set with
+    /// [`Options::with_request_timeout`](crate::Options::with_request_timeout)
+    /// runs out.
+    ///
+    /// Returns an error of kind `Interrupted` if the request was cancelled,
+    /// before or while waiting, and `NotFound` if there were no responders.
+    /// On timeout the request stays pending, as with
+    /// [`RequestHandle::wait_timeout`].
+    pub fn wait(&self) -> io::Result<Message> {
+        if let Some(deadline) = self.deadline {
+            return self.wait_timeout(deadline.saturating_duration_since(Instant::now()));
+        }
+        let result = self
+            .sub
+            .next()
+            .ok_or_else(|| Error::from(ErrorKind::ConnectionReset));
+        self.finish(result)
+    }
+
+    /// Blocks until the response arrives or `timeout` elapses.
+    ///
+    /// On timeout the request stays pending, so it can be waited for again or
+    /// cancelled.
+    pub fn wait_timeout(&self, timeout: Duration) -> io::Result<Message> {
+        let result = self.sub.next_timeout(timeout);
+        if matches!(&result, Err(err) if err.kind() == ErrorKind::TimedOut) {
+            return result;
+        }
+        self.finish(result)
+    }
+
+    /// Gives up on the response, unsubscribing from the reply inbox right
+    /// away instead of when the handle is dropped. Any thread blocked in
+    /// [`RequestHandle::wait`] returns an error of kind `Interrupted`.
+    pub fn cancel(&self) -> io::Result<()> {
+        self.cancelled.store(true, Ordering::SeqCst);
+        self.sub.clone().unsubscribe()
+    }
+
+    /// Returns `true` if the request was cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    fn finish(&self, result: io::Result<Message>) -> io::Result<Message> {
+        if self.is_cancelled() {
+            return Err(Error::new(ErrorKind::Interrupted, "request cancelled"));
+        }
+        // Only a single response is expected.
+        self.sub.clone().unsubscribe()?;
+
+        let msg = result?;
+        if msg.is_no_responders() {
+            return Err(Error::new(ErrorKind::NotFound, "no responders"));
+        }
+        Ok(msg)
+    }
+}
+
+/// Decides when [`Connection::request_many`](crate::Connection::request_many)
+/// stops collecting responses.
+///
+/// Collecting ends at the first of these: the `timeout` elapsing since the
+/// request was sent, `max_responses` responses received, nothing arriving
+/// for the `stall_timeout` after a response, or a sentinel if enabled.
+#[derive(Clone, Copy, Debug)]
+pub struct RequestManyOptions {
+    timeout: Duration,
+    stall_timeout: Option<Duration>,
+    max_responses: Option<usize>,
+    sentinel: bool,
+}
+
+impl Default for RequestManyOptions {
+    fn default() -> RequestManyOptions {
+        RequestManyOptions {
+            timeout: Duration::from_secs(1),
+            stall_timeout: None,
+            max_responses: None,
+            sentinel: false,
+        }
+    }
+}
+
+impl RequestManyOptions {
+    /// Options collecting all responses received within a second.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let options = nats::RequestManyOptions::new();
+    /// ```
+    pub fn new() -> RequestManyOptions {
+        RequestManyOptions::default()
+    }
+
+    /// Sets how long to collect responses for in total, one second by
+    /// default.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// let options = nats::RequestManyOptions::new().timeout(Duration::from_secs(5));
+    /// ```
+    pub fn timeout(mut self, timeout: Duration) -> RequestManyOptions {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Stops collecting once nothing arrived for `stall_timeout` since the
+    /// last response. Until the first response, only the overall timeout
+    /// applies, so slow responders are still waited for.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// let options = nats::RequestManyOptions::new().stall_timeout(Duration::from_millis(100));
+    /// ```
+    pub fn stall_timeout(mut self, stall_timeout: Duration) -> RequestManyOptions {
+        self.stall_timeout = Some(stall_timeout);
+        self
+    }
+
+    /// Stops collecting after `max_responses` responses.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let options = nats::RequestManyOptions::new().max_responses(3);
+    /// ```
+    pub fn max_responses(mut self, max_responses: usize) -> RequestManyOptions {
+        self.max_responses = Some(max_responses);
+        self
+    }
+
+    /// Stops collecting on a response with an empty payload and no headers,
+    /// which responders can send to mark the end of their responses. The
+    /// sentinel itself is not yielded.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let options = nats::RequestManyOptions::new().sentinel(true);
+    /// ```
+    pub fn sentinel(mut self, sentinel: bool) -> RequestManyOptions {
+        self.sentinel = sentinel;
+        self
+    }
+}
+
+/// An iterator over the responses to a request, created with
+/// [`Connection::request_many`](crate::Connection::request_many).
+///
+/// It blocks until the next response arrives, and ends as set with
+/// [`RequestManyOptions`] or when there are no responders. The reply inbox
+/// is unsubscribed from once it ends.
+#[derive(Debug)]
+pub struct Responses {
+    sub: Subscription,
+    options: RequestManyOptions,
+    deadline: Instant,
+    received: usize,
+    done: bool,
+}
+
+impl Responses {
+    pub(crate) fn new(sub: Subscription, options: RequestManyOptions) -> Responses {
+        Responses {
+            sub,
+            deadline: Instant::now() + options.timeout,
+            options,
+            received: 0,
+            done: false,
+        }
+    }
+
+    fn finish(&mut self) -> Option<Message> {
+        self.done = true;
+        self.sub.clone().unsubscribe().ok();
+        None
+    }
+}
+
+impl Iterator for Responses {
+    type Item = Message;
+
+    fn next(&mut self) -> Option<Message> {
+        if self.done {
+            return None;
+        }
+        if self.options.max_responses == Some(self.received) {
+            return self.finish();
+        }
+
+        let mut wait_until = self.deadline;
+        if let Some(stall_timeout) = self.options.stall_timeout {
+            if self.received > 0 {
+                wait_until = wait_until.min(Instant::now() + stall_timeout);
+            }
+        }
+
+        let msg = match self
+            .sub
+            .next_timeout(wait_until.saturating_duration_since(Instant::now()))
+        {
+            Ok(msg) => msg,
+            Err(_) => return self.finish(),
+        };
+        let is_sentinel = self.options.sentinel && msg.data.is_empty() && msg.headers.is_none();
+        if msg.is_no_responders() || is_sentinel {
+            return self.finish();
+        }
+
+        self.received += 1;
+        Some(msg)
+    }
+}
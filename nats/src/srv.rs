@@ -0,0 +1,153 @@
+// Copyright 2020-2022 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! DNS SRV lookups used for server discovery.
+
+use std::io;
+
+#[cfg(feature = "hickory-resolver")]
+use hickory_resolver::Resolver;
+#[cfg(feature = "hickory-resolver")]
+use once_cell::sync::OnceCell;
+
+/// A single DNS SRV record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SrvRecord {
+    /// Priority of the target. Lower values are tried first.
+    pub priority: u16,
+
+    /// Relative weight among targets with the same priority.
+    pub weight: u16,
+
+    /// Port the target is listening on.
+    pub port: u16,
+
+    /// Host name of the target.
+    pub target: String,
+}
+
+/// Resolves DNS SRV records for server discovery.
+///
+/// With the `hickory-resolver` feature, the default resolver uses the system
+/// DNS configuration through the `hickory-resolver` crate. Without it there is
+/// no default, and SRV discovery needs a resolver set with
+/// [`crate::Options::with_srv_resolver`]. A custom implementation can also be
+/// used to pick a different DNS library or to return fixed records in tests.
+pub trait SrvResolver: Send + Sync {
+    /// Returns the SRV records published for `name`, e.g.
+    /// `_nats._tcp.example.com`. The order of the records does not matter.
+    fn resolve_srv(&self, name: &str) -> io::Result<Vec<SrvRecord>>;
+}
+
+/// Returns true if the host looks like an SRV name, i.e. `_service._proto.domain`.
+pub(crate) fn is_srv_name(host: &str) -> bool {
+    host.starts_with('_')
+}
+
+/// Orders records according to RFC 2782: ascending priority, and a weighted
+/// random selection among records sharing the same priority.
+pub(crate) fn order(mut records: Vec<SrvRecord>) -> Vec<SrvRecord> {
+    records.sort_by_key(|record| record.priority);
+
+    let mut ordered = Vec::with_capacity(records.len());
+    let mut rest = records.as_slice();
+    while let Some(first) = rest.first() {
+        let end = rest
+            .iter()
+            .position(|record| record.priority != first.priority)
+            .unwrap_or(rest.len());
+        let (same, tail) = rest.split_at(end);
+
+        // Zero weight records go first so they get a small chance of being picked early.
+        let mut group: Vec<_> = same.iter().filter(|r| r.weight == 0).cloned().collect();
+        group.extend(same.iter().filter(|r| r.weight != 0).cloned());
+
+        while !group.is_empty() {
+            let total: u32 = group.iter().map(|r| u32::from(r.weight)).sum();
+            let pick = fastrand::u32(0..=total);
+            let mut sum = 0;
+            let index = group
+                .iter()
+                .position(|record| {
+                    sum += u32::from(record.weight);
+                    sum >= pick
+                })
+                .unwrap_or(0);
+            ordered.push(group.remove(index));
+        }
+
+        rest = tail;
+    }
+
+    ordered
+}
+
+/// Resolver used when none was set with [`crate::Options::with_srv_resolver`].
+pub(crate) struct SystemResolver;
+
+#[cfg(feature = "hickory-resolver")]
+impl SrvResolver for SystemResolver {
+    fn resolve_srv(&self, name: &str) -> io::Result<Vec<SrvRecord>> {
+        // Shared so that its cache and runtime are reused across reconnects.
+        static RESOLVER: OnceCell<Resolver> = OnceCell::new();
+        let resolver = RESOLVER.get_or_try_init(Resolver::from_system_conf)?;
+
+        Ok(resolver
+            .srv_lookup(name)?
+            .iter()
+            .map(|srv| SrvRecord {
+                priority: srv.priority(),
+                weight: srv.weight(),
+                port: srv.port(),
+                target: srv.target().to_utf8(),
+            })
+            .collect())
+    }
+}
+
+#[cfg(not(feature = "hickory-resolver"))]
+impl SrvResolver for SystemResolver {
+    fn resolve_srv(&self, _name: &str) -> io::Result<Vec<SrvRecord>> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "SRV discovery needs the hickory-resolver feature or a resolver set with Options::with_srv_resolver",
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(priority: u16, weight: u16, target: &str) -> SrvRecord {
+        SrvRecord {
+            priority,
+            weight,
+            port: 4222,
+            target: target.to_string(),
+        }
+    }
+
+    #[test]
+    fn order_by_priority() {
+        let ordered = order(vec![
+            record(20, 10, "c"),
+            record(10, 0, "a"),
+            record(30, 5, "d"),
+            record(10, 0, "b"),
+        ]);
+        let priorities: Vec<_> = ordered.iter().map(|r| r.priority).collect();
+        assert_eq!(priorities, vec![10, 10, 20, 30]);
+        assert_eq!(ordered.len(), 4);
+    }
+}
@@ -11,12 +11,15 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::convert::TryFrom;
 use std::io;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use crossbeam_channel as channel;
+use parking_lot::Mutex;
 
 use crate::client::Client;
 use crate::message::Message;
@@ -34,11 +37,101 @@ struct Inner {
 
     /// Client associated with subscription.
     pub(crate) client: Client,
+
+    /// Throttles delivery, if set with `with_rate_limit`.
+    rate_limiter: Mutex<Option<RateLimiter>>,
+
+    /// Set by `into_detached` to keep the subscription alive after drop.
+    detached: AtomicBool,
+}
+
+/// Credit a [`RateLimiter`] takes for each delivered message.
+const NANOS_PER_MESSAGE: u128 = 1_000_000_000;
+
+/// A token bucket allowing bursts of up to one second worth of messages.
+///
+/// Tokens are tracked as nanoseconds of credit scaled by the rate, so that
+/// delivering a message costs one second of credit and every nanosecond that
+/// passes adds `rate` nanoseconds of it.
+#[derive(Debug)]
+struct RateLimiter {
+    rate: u128,
+    credit: u128,
+    refilled: Instant,
+}
+
+impl RateLimiter {
+    fn new(messages_per_sec: u32) -> RateLimiter {
+        let rate = u128::from(messages_per_sec);
+        RateLimiter {
+            rate,
+            credit: rate * NANOS_PER_MESSAGE,
+            refilled: Instant::now(),
+        }
+    }
+
+    /// Returns how long to wait until the next message may be delivered.
+    fn delay(&mut self) -> Duration {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.refilled).as_nanos();
+        self.credit = self
+            .credit
+            .saturating_add(elapsed.saturating_mul(self.rate))
+            .min(self.rate * NANOS_PER_MESSAGE);
+        self.refilled = now;
+
+        if self.credit >= NANOS_PER_MESSAGE {
+            Duration::ZERO
+        } else {
+            // Round up so that the credit is sufficient once the delay passed.
+            let missing = NANOS_PER_MESSAGE - self.credit;
+            let nanos = (missing + self.rate - 1) / self.rate;
+            Duration::from_nanos(u64::try_from(nanos).unwrap_or(u64::MAX))
+        }
+    }
+
+    /// Takes the credit of one delivered message.
+    fn take(&mut self) {
+        self.credit = self.credit.saturating_sub(NANOS_PER_MESSAGE);
+    }
 }
 
 impl Drop for Inner {
     fn drop(&mut self) {
-        self.client.unsubscribe(self.sid).ok();
+        if !*self.detached.get_mut() {
+            self.client.unsubscribe(self.sid).ok();
+        }
+    }
+}
+
+/// Payload sizes of received messages, tracked per subscription and for the
+/// whole connection.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MessageSizeStats {
+    /// Number of received messages.
+    pub count: u64,
+
+    /// Sum of all payload sizes in bytes.
+    pub total_bytes: u64,
+
+    /// Largest payload size in bytes.
+    pub max: usize,
+}
+
+impl MessageSizeStats {
+    /// Returns the average payload size in bytes, or `None` if no message was
+    /// received yet.
+    pub fn average(&self) -> Option<u64> {
+        if self.count == 0 {
+            return None;
+        }
+        Some(self.total_bytes / self.count)
+    }
+
+    pub(crate) fn record(&mut self, size: usize) {
+        self.count += 1;
+        self.total_bytes += size as u64;
+        self.max = self.max.max(size);
     }
 }
 
@@ -60,9 +153,103 @@ impl Subscription {
             subject,
             messages,
             client,
+            rate_limiter: Mutex::new(None),
+            detached: AtomicBool::new(false),
         }))
     }
 
+    /// Turns the subscription into one that stays active when dropped,
+    /// receiving messages until [`DetachedSubscription::unsubscribe`] is
+    /// called or the connection is closed. This also applies to any clones
+    /// of this subscription.
+    ///
+    /// Dropping every handle of a detached subscription leaks it: the server
+    /// keeps sending its messages, which are buffered until the message
+    /// limits are hit and then dropped as for a slow consumer. Clones of
+    /// [`DetachedSubscription::receiver`] keep receiving them.
+    ///
+    /// # Example
+    /// ```
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// let detached = nc.subscribe("foo")?.into_detached();
+    /// let receiver = detached.receiver().clone();
+    /// std::thread::spawn(move || {
+    ///     for msg in receiver.iter() {
+    ///         println!("Received {}", msg);
+    ///     }
+    /// });
+    /// // ...
+    /// detached.unsubscribe()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn into_detached(self) -> DetachedSubscription {
+        self.0.detached.store(true, Ordering::SeqCst);
+        DetachedSubscription {
+            sid: self.0.sid,
+            subject: self.0.subject.clone(),
+            messages: self.0.messages.clone(),
+            client: self.0.client.clone(),
+        }
+    }
+
+    /// Limits how many messages per second are delivered by this
+    /// subscription and all of its clones, allowing bursts of up to one
+    /// second worth of messages. Passing 0 removes the limit.
+    ///
+    /// Throttled messages wait in the subscription's queue, so they count
+    /// towards the limit set with `set_message_limits`, and messages arriving
+    /// faster than the rate for too long are dropped as for any other slow
+    /// consumer. Messages taken directly from `receiver` are not throttled.
+    ///
+    /// # Example
+    /// ```
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// let sub = nc.subscribe("foo")?.with_rate_limit(100);
+    /// for msg in sub.iter() {
+    ///     println!("Received {}", msg);
+    /// #   break;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_rate_limit(self, messages_per_sec: u32) -> Subscription {
+        *self.0.rate_limiter.lock() = if messages_per_sec == 0 {
+            None
+        } else {
+            Some(RateLimiter::new(messages_per_sec))
+        };
+        self
+    }
+
+    /// Waits until the rate limit allows delivering another message, or
+    /// returns false if that would take longer than `deadline`.
+    fn throttle(&self, deadline: Option<Instant>) -> bool {
+        loop {
+            let delay = match self.0.rate_limiter.lock().as_mut() {
+                Some(limiter) => limiter.delay(),
+                None => return true,
+            };
+            if delay.is_zero() {
+                return true;
+            }
+            match deadline {
+                Some(deadline) if Instant::now() + delay > deadline => return false,
+                _ => thread::sleep(delay),
+            }
+        }
+    }
+
+    /// Accounts for a message delivered under the rate limit.
+    fn delivered(&self, msg: Message) -> Message {
+        if let Some(limiter) = self.0.rate_limiter.lock().as_mut() {
+            limiter.take();
+        }
+        msg
+    }
+
     /// Get a crossbeam Receiver for subscription messages.
     /// Useful for `crossbeam_channel::select` macro
     ///
@@ -105,7 +292,8 @@ impl Subscription {
     /// # }
     /// ```
     pub fn next(&self) -> Option<Message> {
-        self.0.messages.recv().ok()
+        self.throttle(None);
+        self.0.messages.recv().ok().map(|msg| self.delivered(msg))
     }
 
     /// Try to get the next message, or None if no messages
@@ -124,7 +312,46 @@ impl Subscription {
     /// # }
     /// ```
     pub fn try_next(&self) -> Option<Message> {
-        self.0.messages.try_recv().ok()
+        if !self.throttle(Some(Instant::now())) {
+            return None;
+        }
+        self.0
+            .messages
+            .try_recv()
+            .ok()
+            .map(|msg| self.delivered(msg))
+    }
+
+    /// Takes all messages that are buffered locally right now, without
+    /// waiting for more. The subscription stays active.
+    ///
+    /// This is a snapshot: messages arriving while draining are left for
+    /// the next call, and more may be buffered right after it returns.
+    /// Unlike looping over `try_iter`, it therefore returns even if messages
+    /// keep flowing in. A rate limit set with `with_rate_limit` still
+    /// applies, so fewer messages may be returned.
+    ///
+    /// # Example
+    /// ```
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// # let sub = nc.subscribe("foo")?;
+    /// for msg in sub.drain_buffered() {
+    ///     println!("Received {}", msg);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn drain_buffered(&self) -> Vec<Message> {
+        let buffered = self.0.messages.len();
+        let mut messages = Vec::with_capacity(buffered);
+        for _ in 0..buffered {
+            match self.try_next() {
+                Some(msg) => messages.push(msg),
+                None => break,
+            }
+        }
+        messages
     }
 
     /// Get the next message, or a timeout error
@@ -140,15 +367,54 @@ impl Subscription {
     /// # }
     /// ```
     pub fn next_timeout(&self, timeout: Duration) -> io::Result<Message> {
-        match self.0.messages.recv_timeout(timeout) {
-            Ok(msg) => Ok(msg),
+        match Instant::now().checked_add(timeout) {
+            Some(deadline) => self.wait_until(deadline, "next_timeout"),
+            None => self
+                .next()
+                .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "next_timeout: unsubscribed")),
+        }
+    }
+
+    /// Get the next message, or a timeout error if no messages
+    /// arrive before the given deadline.
+    ///
+    /// Unlike `next_timeout`, this takes an absolute point in time,
+    /// which is convenient when a single time budget is spread over
+    /// several receives.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::time::{Duration, Instant};
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// # let sub = nc.subscribe("foo")?;
+    /// let deadline = Instant::now() + Duration::from_secs(1);
+    /// while let Ok(msg) = sub.recv_deadline(deadline) {
+    ///     println!("Received {}", msg);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn recv_deadline(&self, deadline: Instant) -> io::Result<Message> {
+        self.wait_until(deadline, "recv_deadline")
+    }
+
+    fn wait_until(&self, deadline: Instant, op: &str) -> io::Result<Message> {
+        if !self.throttle(Some(deadline)) {
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                format!("{}: timed out", op),
+            ));
+        }
+        match self.0.messages.recv_deadline(deadline) {
+            Ok(msg) => Ok(self.delivered(msg)),
             Err(channel::RecvTimeoutError::Timeout) => Err(io::Error::new(
                 io::ErrorKind::TimedOut,
-                "next_timeout: timed out",
+                format!("{}: timed out", op),
             )),
             Err(channel::RecvTimeoutError::Disconnected) => Err(io::Error::new(
                 io::ErrorKind::Other,
-                "next_timeout: unsubscribed",
+                format!("{}: unsubscribed", op),
             )),
         }
     }
@@ -243,9 +509,12 @@ impl Subscription {
         // This will allow us to not have to capture the return. When it is
         // dropped it will not unsubscribe from the server.
         let sub = self.clone();
+        // Disconnects once the thread is done with all messages.
+        let (done, finished) = channel::bounded::<()>(0);
         thread::Builder::new()
             .name(format!("nats_subscriber_{}_{}", self.0.sid, self.0.subject))
             .spawn(move || {
+                let _done = done;
                 for m in &sub {
                     if let Err(e) = handler(m) {
                         // TODO(dlc) - Capture for last error?
@@ -254,7 +523,79 @@ impl Subscription {
                 }
             })
             .expect("threads should be spawnable");
-        Handler { sub: self }
+        Handler {
+            sub: self,
+            finished,
+        }
+    }
+
+    /// Like `with_handler`, but runs `handler` on `workers` threads, with
+    /// messages handed out to them according to `strategy`.
+    pub(crate) fn with_handler_pool<F>(
+        self,
+        workers: usize,
+        strategy: DispatchStrategy,
+        handler: F,
+    ) -> Handler
+    where
+        F: Fn(Message) -> io::Result<()> + Send + Sync + 'static,
+    {
+        let handler = Arc::new(handler);
+        let (done, finished) = channel::bounded::<()>(0);
+
+        let mut queues = Vec::with_capacity(workers);
+        for i in 0..workers {
+            let (sender, receiver) = channel::unbounded::<Message>();
+            let in_flight = Arc::new(AtomicUsize::new(0));
+            queues.push((sender, in_flight.clone()));
+
+            let handler = handler.clone();
+            let done = done.clone();
+            thread::Builder::new()
+                .name(format!(
+                    "nats_subscriber_{}_{}_worker_{}",
+                    self.0.sid, self.0.subject, i
+                ))
+                .spawn(move || {
+                    let _done = done;
+                    for m in receiver {
+                        if let Err(e) = handler(m) {
+                            log::error!("Error in callback! {:?}", e);
+                        }
+                        in_flight.fetch_sub(1, Ordering::AcqRel);
+                    }
+                })
+                .expect("threads should be spawnable");
+        }
+
+        // The dispatcher only holds the worker queues, so the workers stop
+        // once it has handed out every buffered message.
+        let sub = self.clone();
+        thread::Builder::new()
+            .name(format!("nats_subscriber_{}_{}", self.0.sid, self.0.subject))
+            .spawn(move || {
+                let mut next = 0;
+                for m in &sub {
+                    let i = match strategy {
+                        DispatchStrategy::RoundRobin => {
+                            let i = next;
+                            next = (next + 1) % queues.len();
+                            i
+                        }
+                        DispatchStrategy::LeastBusy => (0..queues.len())
+                            .min_by_key(|&i| queues[i].1.load(Ordering::Acquire))
+                            .unwrap_or(0),
+                    };
+                    queues[i].1.fetch_add(1, Ordering::AcqRel);
+                    queues[i].0.send(m).ok();
+                }
+            })
+            .expect("threads should be spawnable");
+
+        Handler {
+            sub: self,
+            finished,
+        }
     }
 
     /// Sets limit of how many messages can wait in internal queue.
@@ -308,6 +649,55 @@ impl Subscription {
             .map(|subscription| subscription.dropped_messages)
     }
 
+    /// Returns the payload sizes of the messages received on this
+    /// subscription since it was created or [`Subscription::reset_size_stats`]
+    /// was last called. Messages dropped because of `set_message_limits` are
+    /// included.
+    ///
+    /// # Example
+    /// ```
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// let sub = nc.subscribe("bar")?;
+    /// let stats = sub.size_stats()?;
+    /// println!("average size: {:?}, max size: {}", stats.average(), stats.max);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn size_stats(&self) -> io::Result<MessageSizeStats> {
+        self.0
+            .client
+            .state
+            .read
+            .lock()
+            .subscriptions
+            .get(&self.0.sid)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "subscription not found"))
+            .map(|subscription| subscription.size_stats)
+    }
+
+    /// Resets the message size statistics of this subscription.
+    ///
+    /// # Example
+    /// ```
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// let sub = nc.subscribe("bar")?;
+    /// sub.reset_size_stats();
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn reset_size_stats(&self) {
+        self.0
+            .client
+            .state
+            .read
+            .lock()
+            .subscriptions
+            .entry(self.0.sid)
+            .and_modify(|sub| sub.size_stats = MessageSizeStats::default());
+    }
+
     /// Unsubscribe a subscription immediately without draining.
     /// Use `drain` instead if you want any pending messages
     /// to be processed by a handler, if one is configured.
@@ -409,9 +799,55 @@ impl<'a> IntoIterator for &'a Subscription {
     }
 }
 
+/// How a subscription pool hands out messages to its worker threads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DispatchStrategy {
+    /// Hand each message to the next worker in turn.
+    RoundRobin,
+    /// Hand each message to the worker with the fewest messages queued or
+    /// being processed.
+    LeastBusy,
+}
+
+/// A subscription that is not unsubscribed when dropped, created with
+/// [`Subscription::into_detached`].
+#[derive(Debug)]
+pub struct DetachedSubscription {
+    sid: u64,
+    subject: String,
+    messages: channel::Receiver<Message>,
+    client: Client,
+}
+
+impl DetachedSubscription {
+    /// Returns the subject of the subscription.
+    pub fn subject(&self) -> &str {
+        &self.subject
+    }
+
+    /// Returns the channel the subscription's messages are delivered to.
+    pub fn receiver(&self) -> &channel::Receiver<Message> {
+        &self.messages
+    }
+
+    /// Turns this back into a regular `Subscription`, which unsubscribes
+    /// once it and all of its clones are dropped.
+    pub fn attach(self) -> Subscription {
+        Subscription::new(self.sid, self.subject, self.messages, self.client)
+    }
+
+    /// Unsubscribes immediately, discarding any buffered messages.
+    pub fn unsubscribe(self) -> io::Result<()> {
+        self.client.unsubscribe(self.sid)?;
+        while self.messages.try_recv().is_ok() {}
+        Ok(())
+    }
+}
+
 /// A `Handler` may be used to unsubscribe a handler thread.
 pub struct Handler {
     sub: Subscription,
+    finished: channel::Receiver<()>,
 }
 
 impl Handler {
@@ -432,6 +868,38 @@ impl Handler {
     pub fn unsubscribe(self) -> io::Result<()> {
         self.sub.drain()
     }
+
+    /// Stops delivery of new messages and waits until the handler has
+    /// processed all messages that were already received.
+    ///
+    /// Returns an error of kind `TimedOut` if the handler is still busy
+    /// after `timeout`, in which case it keeps working through the remaining
+    /// messages in the background. Must not be called from within the
+    /// handler itself, as it would wait for its own return.
+    ///
+    /// # Example
+    /// ```
+    /// # use std::time::Duration;
+    /// # fn main() -> std::io::Result<()> {
+    /// # let nc = nats::connect("demo.nats.io")?;
+    /// let handler = nc.subscribe("foo")?.with_handler(move |msg| {
+    ///     println!("Received {}", &msg);
+    ///     Ok(())
+    /// });
+    /// handler.drain(Duration::from_secs(5))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn drain(self, timeout: Duration) -> io::Result<()> {
+        self.sub.drain()?;
+        match self.finished.recv_timeout(timeout) {
+            Err(channel::RecvTimeoutError::Timeout) => Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "handler did not finish processing buffered messages in time",
+            )),
+            _ => Ok(()),
+        }
+    }
 }
 
 /// A non-blocking iterator over messages from a `Subscription`
@@ -0,0 +1,306 @@
+// Copyright 2020-2022 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Typed system account events.
+//!
+//! Servers publish events about connections, accounts and their own health
+//! on `$SYS` subjects. These are only visible to connections authenticated as
+//! a user of the system account, see
+//! [`Connection::subscribe_sys`](crate::Connection::subscribe_sys).
+
+use std::io;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::jetstream::DateTime;
+use crate::{Message, Subscription};
+use time::serde::rfc3339;
+
+/// Kinds of system events that can be subscribed to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SystemEventKind {
+    /// A client connected to any account, published on
+    /// `$SYS.ACCOUNT.<account>.CONNECT`.
+    Connect,
+    /// A client disconnected from any account, published on
+    /// `$SYS.ACCOUNT.<account>.DISCONNECT`.
+    Disconnect,
+    /// Connection counts and traffic of an account on a server, published on
+    /// `$SYS.ACCOUNT.<account>.SERVER.CONNS`.
+    AccountConnections,
+    /// Periodic server statistics, published on `$SYS.SERVER.<id>.STATSZ`.
+    ServerStats,
+}
+
+impl SystemEventKind {
+    pub(crate) fn subject(self) -> &'static str {
+        match self {
+            SystemEventKind::Connect => "$SYS.ACCOUNT.*.CONNECT",
+            SystemEventKind::Disconnect => "$SYS.ACCOUNT.*.DISCONNECT",
+            SystemEventKind::AccountConnections => "$SYS.ACCOUNT.*.SERVER.CONNS",
+            SystemEventKind::ServerStats => "$SYS.SERVER.*.STATSZ",
+        }
+    }
+}
+
+/// A parsed system event.
+#[derive(Debug, Clone)]
+pub enum SystemEvent {
+    /// A client connected.
+    Connect(ConnectEvent),
+    /// A client disconnected.
+    Disconnect(DisconnectEvent),
+    /// Connection counts of an account.
+    AccountConnections(AccountConnections),
+    /// Statistics of a server.
+    ServerStats(ServerStatsEvent),
+}
+
+/// The server that published an event.
+#[derive(Debug, Default, Clone, Deserialize)]
+pub struct EventServer {
+    /// Server name.
+    #[serde(default)]
+    pub name: String,
+    /// Host the server runs on.
+    #[serde(default)]
+    pub host: String,
+    /// Server id.
+    #[serde(default)]
+    pub id: String,
+    /// Cluster the server is part of, if any.
+    #[serde(default)]
+    pub cluster: Option<String>,
+    /// Server version.
+    #[serde(default, rename = "ver")]
+    pub version: String,
+    /// Sequence number of the event, per server.
+    #[serde(default, rename = "seq")]
+    pub sequence: u64,
+}
+
+/// The client an event is about.
+#[derive(Debug, Default, Clone, Deserialize)]
+pub struct EventClient {
+    /// Client id, unique per server.
+    #[serde(default)]
+    pub id: u64,
+    /// Address the client connected from.
+    #[serde(default)]
+    pub host: Option<String>,
+    /// Account of the client.
+    #[serde(default, rename = "acc")]
+    pub account: String,
+    /// User the client authenticated as.
+    #[serde(default)]
+    pub user: Option<String>,
+    /// Name set by the client.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Client library language.
+    #[serde(default)]
+    pub lang: Option<String>,
+    /// Client library version.
+    #[serde(default, rename = "ver")]
+    pub version: Option<String>,
+    /// Measured round trip time.
+    #[serde(default, with = "serde_nanos")]
+    pub rtt: Option<Duration>,
+}
+
+/// Message and byte counters.
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+pub struct DataStats {
+    /// Number of messages.
+    #[serde(default)]
+    pub msgs: u64,
+    /// Number of bytes.
+    #[serde(default)]
+    pub bytes: u64,
+}
+
+/// A client connected to the server.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ConnectEvent {
+    /// Event id.
+    pub id: String,
+    /// When the event happened.
+    #[serde(with = "rfc3339")]
+    pub timestamp: DateTime,
+    /// The server the client connected to.
+    pub server: EventServer,
+    /// The client that connected.
+    pub client: EventClient,
+}
+
+/// A client disconnected from the server.
+#[derive(Debug, Clone, Deserialize)]
+pub struct DisconnectEvent {
+    /// Event id.
+    pub id: String,
+    /// When the event happened.
+    #[serde(with = "rfc3339")]
+    pub timestamp: DateTime,
+    /// The server the client was connected to.
+    pub server: EventServer,
+    /// The client that disconnected.
+    pub client: EventClient,
+    /// Traffic sent to the client.
+    #[serde(default)]
+    pub sent: DataStats,
+    /// Traffic received from the client.
+    #[serde(default)]
+    pub received: DataStats,
+    /// Why the client disconnected.
+    #[serde(default)]
+    pub reason: String,
+}
+
+/// Connection counts and traffic of an account on one server.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AccountConnections {
+    /// Event id.
+    pub id: String,
+    /// When the event happened.
+    #[serde(with = "rfc3339")]
+    pub timestamp: DateTime,
+    /// The server reporting the counts.
+    pub server: EventServer,
+    /// The account.
+    #[serde(rename = "acc")]
+    pub account: String,
+    /// Client connections of the account on this server.
+    #[serde(default)]
+    pub conns: u64,
+    /// Leaf node connections of the account on this server.
+    #[serde(default)]
+    pub leafnodes: u64,
+    /// All connections of the account on this server.
+    #[serde(default)]
+    pub total_conns: u64,
+    /// Traffic sent to the account's connections.
+    #[serde(default)]
+    pub sent: DataStats,
+    /// Traffic received from the account's connections.
+    #[serde(default)]
+    pub received: DataStats,
+    /// Number of slow consumers in the account.
+    #[serde(default)]
+    pub slow_consumers: u64,
+}
+
+/// Periodic server statistics.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ServerStatsEvent {
+    /// The server reporting its statistics.
+    pub server: EventServer,
+    /// The statistics.
+    pub statsz: ServerStats,
+}
+
+/// Statistics of a server.
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct ServerStats {
+    /// When the server started.
+    #[serde(with = "rfc3339")]
+    pub start: DateTime,
+    /// Resident memory in bytes.
+    #[serde(default)]
+    pub mem: u64,
+    /// Number of CPU cores.
+    #[serde(default)]
+    pub cores: u64,
+    /// CPU usage in percent.
+    #[serde(default)]
+    pub cpu: f64,
+    /// Current client connections.
+    #[serde(default)]
+    pub connections: u64,
+    /// Client connections since the server started.
+    #[serde(default)]
+    pub total_connections: u64,
+    /// Accounts with at least one connection.
+    #[serde(default)]
+    pub active_accounts: u64,
+    /// Number of subscriptions.
+    #[serde(default)]
+    pub subscriptions: u64,
+    /// Traffic sent by the server.
+    #[serde(default)]
+    pub sent: DataStats,
+    /// Traffic received by the server.
+    #[serde(default)]
+    pub received: DataStats,
+    /// Number of slow consumers.
+    #[serde(default)]
+    pub slow_consumers: u64,
+}
+
+impl SystemEvent {
+    fn parse(kind: SystemEventKind, message: &Message) -> serde_json::Result<SystemEvent> {
+        let event = match kind {
+            SystemEventKind::Connect => {
+                SystemEvent::Connect(serde_json::from_slice(&message.data)?)
+            }
+            SystemEventKind::Disconnect => {
+                SystemEvent::Disconnect(serde_json::from_slice(&message.data)?)
+            }
+            SystemEventKind::AccountConnections => {
+                SystemEvent::AccountConnections(serde_json::from_slice(&message.data)?)
+            }
+            SystemEventKind::ServerStats => {
+                SystemEvent::ServerStats(serde_json::from_slice(&message.data)?)
+            }
+        };
+        Ok(event)
+    }
+}
+
+/// A subscription to system events that yields them parsed.
+#[derive(Debug)]
+pub struct SystemEvents {
+    kind: SystemEventKind,
+    subscription: Subscription,
+}
+
+impl SystemEvents {
+    pub(crate) fn new(kind: SystemEventKind, subscription: Subscription) -> SystemEvents {
+        SystemEvents { kind, subscription }
+    }
+
+    /// Waits up to `timeout` for the next event. Events that fail to parse
+    /// are returned as `InvalidData` errors.
+    pub fn next_timeout(&self, timeout: Duration) -> io::Result<SystemEvent> {
+        let message = self.subscription.next_timeout(timeout)?;
+        SystemEvent::parse(self.kind, &message)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    /// Unsubscribes from the events.
+    pub fn unsubscribe(self) -> io::Result<()> {
+        self.subscription.unsubscribe()
+    }
+}
+
+impl Iterator for SystemEvents {
+    type Item = io::Result<SystemEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let message = self.subscription.next()?;
+        Some(
+            SystemEvent::parse(self.kind, &message)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err)),
+        )
+    }
+}
@@ -0,0 +1,93 @@
+// Copyright 2020-2022 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Hooks for exercising connection failure handling in tests.
+//!
+//! **Not for production use.** This module is only available with the
+//! `unstable-testing` feature and may change or disappear in any release.
+//! Dropping or delaying PONGs makes a healthy connection look dead, which is
+//! exactly what it is meant for: triggering the client's stale connection
+//! detection and the reconnect logic that follows on demand.
+
+use std::fmt;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// A PONG passing through the client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PongEvent {
+    /// The server answered one of our PINGs.
+    Received,
+
+    /// The client is about to answer a PING from the server.
+    Sent,
+}
+
+/// What the client should do with a PONG.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PongAction {
+    /// Handle the PONG normally.
+    Continue,
+
+    /// Wait before handling the PONG. This blocks the thread reading from
+    /// the server for the given duration.
+    Delay(Duration),
+
+    /// Discard the PONG. A dropped received PONG does not count as activity
+    /// on the connection.
+    Drop,
+}
+
+pub(crate) type PongHook = Arc<dyn Fn(PongEvent) -> PongAction + Send + Sync>;
+
+#[derive(Default)]
+pub(crate) struct TestingOptions {
+    pub(crate) ping_interval: Option<Duration>,
+    pub(crate) pong_hook: Option<PongHook>,
+}
+
+impl TestingOptions {
+    /// Runs the PONG hook, returns false if the PONG should be dropped.
+    pub(crate) fn on_pong(&self, event: PongEvent) -> bool {
+        let action = match self.pong_hook.as_ref() {
+            Some(hook) => hook(event),
+            None => return true,
+        };
+
+        match action {
+            PongAction::Continue => true,
+            PongAction::Delay(delay) => {
+                thread::sleep(delay);
+                true
+            }
+            PongAction::Drop => false,
+        }
+    }
+}
+
+impl fmt::Debug for TestingOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        f.debug_map()
+            .entry(&"ping_interval", &self.ping_interval)
+            .entry(
+                &"pong_hook",
+                if self.pong_hook.is_some() {
+                    &"set"
+                } else {
+                    &"unset"
+                },
+            )
+            .finish()
+    }
+}
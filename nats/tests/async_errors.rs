@@ -70,3 +70,28 @@ fn sub_perms() {
     let r = drx.recv_timeout(Duration::from_millis(100));
     assert!(r.is_err(), "we got disconnected on perm violation");
 }
+
+#[test]
+fn pub_perms_cached() {
+    let s = nats_server::run_server("tests/configs/perms.conf");
+
+    let (etx, erx) = bounded(1);
+
+    let nc = nats::Options::with_user_pass("derek", "s3cr3t!")
+        .with_publish_permission_cache(true)
+        .error_callback(move |err| etx.send(err).unwrap())
+        .connect(s.client_url())
+        .expect("could not connect");
+
+    nc.publish("foo", "NOT ALLOWED").unwrap();
+    erx.recv_timeout(Duration::from_secs(1))
+        .expect("expected an error callback, got none");
+
+    let err = nc.publish("foo", "NOT ALLOWED").unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::PermissionDenied);
+
+    // Only the denied subject fails fast, and nothing else reached the server.
+    nc.publish("bar", "ALLOWED").unwrap();
+    nc.flush().unwrap();
+    assert!(erx.recv_timeout(Duration::from_millis(100)).is_err());
+}
@@ -45,6 +45,28 @@ fn basic_tls() {
         .unwrap();
 }
 
+#[test]
+fn tls_handshake_error() {
+    let server = nats_server::run_server("tests/configs/tls.conf");
+
+    // The certificate is not trusted, so the handshake is rejected.
+    let err = nats::connect(server.client_url()).unwrap_err();
+    let inner = err.into_inner().expect("error has a source");
+    assert!(matches!(
+        inner.downcast_ref::<nats::ConnectError>(),
+        Some(nats::ConnectError::TlsHandshake(_))
+    ));
+
+    // A server that cannot be reached fails before any handshake.
+    let port = server.client_port();
+    drop(server);
+    let err = nats::connect(format!("tls://localhost:{port}")).unwrap_err();
+    assert!(err
+        .get_ref()
+        .and_then(|inner| inner.downcast_ref::<nats::ConnectError>())
+        .is_none());
+}
+
 #[test]
 fn ip_basic_tls() {
     let server = nats_server::run_server("tests/configs/ip-tls.conf");
@@ -57,3 +79,30 @@ fn ip_basic_tls() {
         .connect(format!("tls://127.0.0.1:{}", server.client_port()))
         .unwrap();
 }
+
+#[test]
+fn peer_certificate() {
+    let server = nats_server::run_server("tests/configs/tls.conf");
+
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    let nc = nats::Options::with_user_pass("derek", "porkchop")
+        .add_root_certificate(path.join("tests/configs/certs/rootCA.pem"))
+        .client_cert(
+            path.join("tests/configs/certs/client-cert.pem"),
+            path.join("tests/configs/certs/client-key.pem"),
+        )
+        .connect(server.client_url())
+        .unwrap();
+
+    let cert = nc
+        .peer_certificate()
+        .expect("TLS connection has a certificate");
+    assert!(!cert.der().is_empty());
+    assert!(cert.subject().contains("O=mkcert development certificate"));
+    assert!(cert.issuer().contains("CN=mkcert"));
+
+    let server = nats_server::run_basic_server();
+    let nc = nats::connect(server.client_url()).unwrap();
+    assert!(nc.peer_certificate().is_none());
+}
@@ -0,0 +1,70 @@
+// Copyright 2020-2022 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+
+#[test]
+fn auto_flush_interval() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::Options::new()
+        .with_auto_flush_interval(Some(Duration::from_millis(50)))
+        .connect(s.client_url())
+        .unwrap();
+
+    let sub = nc.subscribe("foo").unwrap();
+    nc.flush().unwrap();
+
+    // A single publish goes out without an explicit flush.
+    nc.publish("foo", "hello").unwrap();
+    let msg = sub.next_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(msg.data, b"hello");
+
+    for i in 0..100 {
+        nc.publish("foo", format!("{}", i)).unwrap();
+    }
+    for i in 0..100 {
+        let msg = sub.next_timeout(Duration::from_secs(1)).unwrap();
+        assert_eq!(msg.data, format!("{}", i).as_bytes());
+    }
+}
+
+#[test]
+fn auto_flush_interval_zero() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::Options::new()
+        .with_auto_flush_interval(Some(Duration::ZERO))
+        .connect(s.client_url())
+        .unwrap();
+
+    // A zero interval falls back to flushing every publish right away.
+    let sub = nc.subscribe("foo").unwrap();
+    nc.publish("foo", "hello").unwrap();
+    let msg = sub.next_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(msg.data, b"hello");
+}
+
+#[test]
+fn auto_flush_interval_does_not_delay_publishes() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::Options::new()
+        .with_auto_flush_interval(Some(Duration::from_secs(30)))
+        .connect(s.client_url())
+        .unwrap();
+
+    // The timer is only a backstop, publishes are still flushed right away.
+    let sub = nc.subscribe("foo").unwrap();
+    nc.flush().unwrap();
+    nc.publish("foo", "hello").unwrap();
+    let msg = sub.next_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(msg.data, b"hello");
+}
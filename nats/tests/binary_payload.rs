@@ -0,0 +1,42 @@
+// Copyright 2020-2022 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+
+#[test]
+fn binary_and_empty_payloads() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+    let sub = nc.subscribe("bin").unwrap();
+
+    // Not valid UTF-8, with bytes that look like protocol delimiters.
+    let binary: Vec<u8> = vec![0xff, 0x00, b'\r', b'\n', 0xfe, b'M', b'S', b'G', 0x80];
+    nc.publish("bin", &binary).unwrap();
+    nc.publish("bin", b"").unwrap();
+
+    let mut headers = nats::HeaderMap::new();
+    headers.insert("X-Kind", "empty".to_string());
+    nc.publish_with_reply_or_headers("bin", None, Some(&headers), b"")
+        .unwrap();
+
+    let msg = sub.next_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(msg.data, binary);
+    let msg = sub.next_timeout(Duration::from_secs(1)).unwrap();
+    assert!(msg.data.is_empty());
+    let msg = sub.next_timeout(Duration::from_secs(1)).unwrap();
+    assert!(msg.data.is_empty());
+    assert_eq!(
+        msg.headers.unwrap().get("X-Kind").map(String::as_str),
+        Some("empty")
+    );
+}
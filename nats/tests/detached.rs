@@ -0,0 +1,57 @@
+// Copyright 2020-2022 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+
+use crossbeam_channel::RecvTimeoutError;
+
+#[test]
+fn detached_subscription() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+
+    let detached = nc.subscribe("foo").unwrap().into_detached();
+    assert_eq!(detached.subject(), "foo");
+    let receiver = detached.receiver().clone();
+    drop(detached);
+
+    // Still subscribed after the handle is gone.
+    nc.publish("foo", "after drop").unwrap();
+    let msg = receiver.recv_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(msg.data, b"after drop");
+
+    // Unsubscribing explicitly closes the channel.
+    let detached = nc.subscribe("bar").unwrap().into_detached();
+    let receiver = detached.receiver().clone();
+    detached.unsubscribe().unwrap();
+    nc.publish("bar", "after unsubscribe").unwrap();
+    assert_eq!(
+        receiver.recv_timeout(Duration::from_secs(1)).unwrap_err(),
+        RecvTimeoutError::Disconnected
+    );
+
+    // An attached subscription unsubscribes on drop again.
+    let detached = nc.subscribe("baz").unwrap().into_detached();
+    let receiver = detached.receiver().clone();
+    let sub = detached.attach();
+    nc.publish("baz", "attached").unwrap();
+    assert_eq!(
+        sub.next_timeout(Duration::from_secs(1)).unwrap().data,
+        b"attached"
+    );
+    drop(sub);
+    assert_eq!(
+        receiver.recv_timeout(Duration::from_secs(1)).unwrap_err(),
+        RecvTimeoutError::Disconnected
+    );
+}
@@ -0,0 +1,40 @@
+// Copyright 2020-2022 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+
+#[test]
+fn drain_buffered() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+
+    let sub = nc.subscribe("foo").unwrap();
+    assert!(sub.drain_buffered().is_empty());
+
+    for i in 0..10 {
+        nc.publish("foo", i.to_string()).unwrap();
+    }
+    nc.flush().unwrap();
+
+    let messages = sub.drain_buffered();
+    assert_eq!(messages.len(), 10);
+    for (i, msg) in messages.iter().enumerate() {
+        assert_eq!(msg.data, i.to_string().as_bytes());
+    }
+    assert!(sub.drain_buffered().is_empty());
+
+    // Still subscribed.
+    nc.publish("foo", "more").unwrap();
+    let msg = sub.next_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(msg.data, b"more");
+}
@@ -0,0 +1,65 @@
+// Copyright 2020-2022 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn handler_drain_processes_buffered_messages() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+
+    let processed = Arc::new(AtomicUsize::new(0));
+    let counter = processed.clone();
+    let handler = nc.subscribe("foo").unwrap().with_handler(move |_| {
+        thread::sleep(Duration::from_millis(20));
+        counter.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    });
+
+    for _ in 0..10 {
+        nc.publish("foo", "data").unwrap();
+    }
+    nc.flush().unwrap();
+
+    handler.drain(Duration::from_secs(5)).unwrap();
+    assert_eq!(processed.load(Ordering::SeqCst), 10);
+
+    // No delivery after the drain.
+    nc.publish("foo", "data").unwrap();
+    nc.flush().unwrap();
+    thread::sleep(Duration::from_millis(100));
+    assert_eq!(processed.load(Ordering::SeqCst), 10);
+}
+
+#[test]
+fn handler_drain_times_out() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+
+    let handler = nc.subscribe("foo").unwrap().with_handler(move |_| {
+        thread::sleep(Duration::from_millis(500));
+        Ok(())
+    });
+
+    nc.publish("foo", "data").unwrap();
+    nc.flush().unwrap();
+    thread::sleep(Duration::from_millis(50));
+
+    let err = handler.drain(Duration::from_millis(10)).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::TimedOut);
+}
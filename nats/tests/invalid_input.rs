@@ -0,0 +1,89 @@
+// Copyright 2020-2022 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io;
+use std::time::Duration;
+
+use nats::{HeaderMap, ValidationError};
+
+fn validation_error(err: io::Error) -> ValidationError {
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    *err.into_inner()
+        .expect("should be able to convert error into inner")
+        .downcast::<ValidationError>()
+        .expect("should be able to downcast into error")
+}
+
+#[test]
+fn crlf_in_subject() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+    let sub = nc.subscribe("bar").unwrap();
+
+    let err = nc.publish("foo\r\nPUB bar 5\r\nhello", "").unwrap_err();
+    assert_eq!(
+        validation_error(err),
+        ValidationError::InvalidSubject("foo\r\nPUB bar 5\r\nhello".to_string())
+    );
+
+    let err = nc.subscribe("foo\r\nUNSUB 1").unwrap_err();
+    assert!(matches!(
+        validation_error(err),
+        ValidationError::InvalidSubject(_)
+    ));
+
+    let err = nc.request("foo\nbar", "hello").unwrap_err();
+    assert!(matches!(
+        validation_error(err),
+        ValidationError::InvalidSubject(_)
+    ));
+
+    // Nothing was injected and the connection is still usable.
+    nc.flush().unwrap();
+    assert!(sub.next_timeout(Duration::from_millis(100)).is_err());
+    nc.publish("bar", "hello").unwrap();
+    assert_eq!(
+        sub.next_timeout(Duration::from_secs(1)).unwrap().data,
+        b"hello"
+    );
+}
+
+#[test]
+fn crlf_in_header() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+    let sub = nc.subscribe("foo").unwrap();
+
+    let mut headers = HeaderMap::new();
+    headers.insert("X-Test", "value\r\n\r\nPUB bar 0");
+    let err = nc
+        .publish_with_reply_or_headers("foo", None, Some(&headers), "hello")
+        .unwrap_err();
+    assert!(matches!(
+        validation_error(err),
+        ValidationError::InvalidHeader(_)
+    ));
+
+    let mut headers = HeaderMap::new();
+    headers.insert("X-\r\nTest", "value");
+    let err = nc
+        .publish_with_reply_or_headers("foo", None, Some(&headers), "hello")
+        .unwrap_err();
+    assert!(matches!(
+        validation_error(err),
+        ValidationError::InvalidHeader(_)
+    ));
+
+    nc.flush().unwrap();
+    assert!(sub.next_timeout(Duration::from_millis(100)).is_err());
+}
@@ -11,7 +11,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{io, time::Duration};
+use std::{
+    io,
+    time::{Duration, Instant},
+};
 
 use nats::jetstream::*;
 use nats::{jetstream, Connection};
@@ -244,6 +247,264 @@ fn jetstream_publish() {
     );
 }
 
+#[test]
+fn jetstream_publish_expected_last_subject_sequence() {
+    let (_s, _nc, js) = run_basic_jetstream();
+
+    js.add_stream(&StreamConfig {
+        name: "ORDERS".to_string(),
+        subjects: vec!["orders.>".to_string()],
+        ..Default::default()
+    })
+    .unwrap();
+
+    js.publish("orders.1.created", b"").unwrap();
+    js.publish("orders.2.created", b"").unwrap();
+    js.publish("orders.1.paid", b"").unwrap();
+
+    let wrong_last_sequence = |err: io::Error| {
+        err.into_inner()
+            .expect("should be able to convert error into inner")
+            .downcast::<jetstream::Error>()
+            .expect("should be able to downcast into error")
+            .wrong_last_sequence()
+    };
+
+    // Each subject is checked on its own, regardless of other subjects in the stream.
+    js.publish_with_options(
+        "orders.2.created",
+        b"",
+        &PublishOptions::default().expected_last_subject_sequence(2),
+    )
+    .unwrap();
+    let err = js
+        .publish_with_options(
+            "orders.1.paid",
+            b"",
+            &PublishOptions::default().expected_last_subject_sequence(1),
+        )
+        .unwrap_err();
+    assert_eq!(wrong_last_sequence(err), Some(3));
+
+    // A wildcard subject guards all events of an order.
+    let err = js
+        .publish_with_options(
+            "orders.1.shipped",
+            b"",
+            &PublishOptions::default()
+                .expected_last_subject_sequence(1)
+                .expected_last_subject_sequence_subject("orders.1.*"),
+        )
+        .unwrap_err();
+    assert_eq!(wrong_last_sequence(err), Some(3));
+    let ack = js
+        .publish_with_options(
+            "orders.1.shipped",
+            b"",
+            &PublishOptions::default()
+                .expected_last_subject_sequence(3)
+                .expected_last_subject_sequence_subject("orders.1.*"),
+        )
+        .unwrap();
+    assert_eq!(ack.sequence, 5);
+
+    // Of concurrent updates based on the same sequence only one is stored.
+    let stored: usize = (0..8)
+        .map(|_| {
+            let js = js.clone();
+            std::thread::spawn(move || {
+                js.publish_with_options(
+                    "orders.1.refunded",
+                    b"",
+                    &PublishOptions::default()
+                        .expected_last_subject_sequence(5)
+                        .expected_last_subject_sequence_subject("orders.1.*"),
+                )
+                .is_ok()
+            })
+        })
+        .collect::<Vec<_>>()
+        .into_iter()
+        .map(|handle| handle.join().unwrap() as usize)
+        .sum();
+    assert_eq!(stored, 1);
+    assert_eq!(js.stream_info("ORDERS").unwrap().state.messages, 6);
+}
+
+#[test]
+fn jetstream_publish_dedup() {
+    let (_s, _nc, js) = run_basic_jetstream();
+
+    js.add_stream(&StreamConfig {
+        name: "ORDERS".to_string(),
+        subjects: vec!["orders".to_string()],
+        ..Default::default()
+    })
+    .unwrap();
+
+    let ack = js.publish_dedup("orders", "order-1", b"created").unwrap();
+    assert!(!ack.duplicate);
+    let retry = js.publish_dedup("orders", "order-1", b"created").unwrap();
+    assert!(retry.duplicate);
+    assert_eq!(retry.sequence, ack.sequence);
+    js.publish_dedup("orders", "order-2", b"created").unwrap();
+    js.publish("orders", b"no id").unwrap();
+    assert_eq!(js.stream_info("ORDERS").unwrap().state.messages, 3);
+
+    let message = js.get_message("ORDERS", ack.sequence).unwrap();
+    let headers = message.headers.unwrap();
+    assert_eq!(
+        headers.get(nats::header::NATS_MSG_ID).map(String::as_str),
+        Some("order-1")
+    );
+
+    let sub = js.subscribe("orders").unwrap();
+    let ids: Vec<_> = (0..3)
+        .map(|_| {
+            let message = sub.next_timeout(Duration::from_secs(1)).unwrap();
+            message.msg_id().map(str::to_string)
+        })
+        .collect();
+    assert_eq!(
+        ids,
+        vec![
+            Some("order-1".to_string()),
+            Some("order-2".to_string()),
+            None
+        ]
+    );
+}
+
+#[test]
+fn jetstream_publish_rollup() {
+    let (_s, _nc, js) = run_basic_jetstream();
+
+    js.add_stream(&StreamConfig {
+        name: "TEST".to_string(),
+        subjects: vec!["foo.*".to_string()],
+        allow_rollup: true,
+        ..Default::default()
+    })
+    .unwrap();
+
+    for _ in 0..3 {
+        js.publish("foo.a", b"old").unwrap();
+        js.publish("foo.b", b"old").unwrap();
+    }
+
+    // Rolling up a subject only purges prior messages of that subject.
+    js.publish_with_options(
+        "foo.a",
+        b"new",
+        &PublishOptions::default().rollup(Rollup::Subject),
+    )
+    .unwrap();
+    assert_eq!(js.stream_info("TEST").unwrap().state.messages, 4);
+    let last = js.get_last_message("TEST", "foo.a").unwrap();
+    assert_eq!(last.data, b"new");
+
+    js.publish_with_options(
+        "foo.b",
+        b"all",
+        &PublishOptions::default().rollup(Rollup::All),
+    )
+    .unwrap();
+    assert_eq!(js.stream_info("TEST").unwrap().state.messages, 1);
+
+    // Streams have to opt in to rollups.
+    js.add_stream(&StreamConfig {
+        name: "NOROLLUP".to_string(),
+        subjects: vec!["bar".to_string()],
+        ..Default::default()
+    })
+    .unwrap();
+    js.publish("bar", b"old").unwrap();
+
+    let err = js
+        .publish_with_options(
+            "bar",
+            b"new",
+            &PublishOptions::default().rollup(Rollup::Subject),
+        )
+        .unwrap_err()
+        .into_inner()
+        .expect("should be able to convert error into inner")
+        .downcast::<jetstream::Error>()
+        .expect("should be able to downcast into error");
+
+    assert_eq!(err.error_code(), jetstream::ErrorCode::StreamRollupFailed);
+    assert_eq!(js.stream_info("NOROLLUP").unwrap().state.messages, 1);
+}
+
+#[test]
+fn jetstream_delete_message() {
+    let (_s, _nc, js) = run_basic_jetstream();
+
+    js.add_stream(&StreamConfig {
+        name: "TEST".to_string(),
+        subjects: vec!["foo".to_string()],
+        ..Default::default()
+    })
+    .unwrap();
+
+    let first = js.publish("foo", b"first").unwrap();
+    let second = js.publish("foo", b"second").unwrap();
+    js.publish("foo", b"third").unwrap();
+
+    assert!(js.delete_message("TEST", first.sequence, false).unwrap());
+    assert!(js.delete_message("TEST", second.sequence, true).unwrap());
+
+    let info = js.stream_info("TEST").unwrap();
+    assert_eq!(info.state.messages, 1);
+    assert_eq!(js.get_message("TEST", 3).unwrap().data, b"third".to_vec());
+
+    let err = js
+        .delete_message("TEST", second.sequence, true)
+        .unwrap_err()
+        .into_inner()
+        .expect("should be able to convert error into inner")
+        .downcast::<jetstream::Error>()
+        .expect("should be able to downcast into error");
+    assert_eq!(
+        err.error_code(),
+        jetstream::ErrorCode::StreamMsgDeleteFailedF
+    );
+}
+
+#[test]
+fn jetstream_publish_retries() {
+    let (_s, nc, js) = run_basic_jetstream();
+
+    // Nothing listens on the subject until the stream shows up.
+    let handle = std::thread::spawn({
+        let js = nats::jetstream::new(nc);
+        move || {
+            std::thread::sleep(Duration::from_millis(300));
+            js.add_stream(&StreamConfig {
+                name: "TEST".to_string(),
+                subjects: vec!["foo".to_string()],
+                ..Default::default()
+            })
+            .unwrap();
+        }
+    });
+
+    let err = js
+        .publish_with_options("foo", b"data", &PublishOptions::default())
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::NotFound);
+
+    let options = PublishOptions {
+        id: Some("retried".to_string()),
+        ..Default::default()
+    }
+    .retries(20)
+    .retry_backoff(Duration::from_millis(100));
+    let ack = js.publish_with_options("foo", b"data", &options).unwrap();
+    assert_eq!(ack.sequence, 1);
+    handle.join().unwrap();
+}
+
 #[test]
 fn jetstream_subscribe() {
     let s = nats_server::run_server("tests/configs/jetstream.conf");
@@ -296,6 +557,121 @@ fn jetstream_subscribe() {
     assert!(sub.next().is_none());
 }
 
+#[test]
+fn jetstream_ack_sync_timed() {
+    let (_s, nc, js) = run_basic_jetstream();
+
+    js.add_stream(&StreamConfig {
+        name: "TEST".to_string(),
+        subjects: vec!["foo".to_string()],
+        ..Default::default()
+    })
+    .unwrap();
+
+    let sub = js.subscribe("foo").unwrap();
+    for _ in 0..3 {
+        js.publish("foo", b"hello js").unwrap();
+    }
+
+    for _ in 0..3 {
+        let msg = sub.next().unwrap();
+        let latency = msg.ack_sync_timed().unwrap();
+        assert!(latency > Duration::ZERO);
+        // Already confirmed acks are not measured again.
+        assert_eq!(msg.ack_sync_timed().unwrap(), Duration::ZERO);
+    }
+
+    let stats = nc.ack_stats();
+    assert_eq!(stats.count, 3);
+    assert!(stats.max >= stats.average().unwrap());
+}
+
+#[test]
+fn jetstream_is_redelivery() {
+    let (_s, nc, js) = run_basic_jetstream();
+
+    js.add_stream(&StreamConfig {
+        name: "TEST".to_string(),
+        subjects: vec!["foo".to_string()],
+        ..Default::default()
+    })
+    .unwrap();
+
+    let sub = js.subscribe("foo").unwrap();
+    js.publish("foo", b"hello js").unwrap();
+
+    let msg = sub.next_timeout(Duration::from_secs(1)).unwrap();
+    assert!(!msg.is_redelivery());
+    msg.ack_kind(AckKind::Nak).unwrap();
+
+    let msg = sub.next_timeout(Duration::from_secs(1)).unwrap();
+    assert!(msg.is_redelivery());
+    msg.ack().unwrap();
+
+    let core = nc.subscribe("bar").unwrap();
+    nc.publish("bar", b"hello core").unwrap();
+    assert!(!core
+        .next_timeout(Duration::from_secs(1))
+        .unwrap()
+        .is_redelivery());
+}
+
+#[test]
+fn jetstream_warn_on_unacked_drop() {
+    let (_s, nc, _js) = run_basic_jetstream();
+    let js = JetStream::new(nc, JetStreamOptions::new().warn_on_unacked_drop(true));
+
+    js.add_stream(&StreamConfig {
+        name: "TEST".to_string(),
+        subjects: vec!["foo".to_string()],
+        ..Default::default()
+    })
+    .unwrap();
+
+    let sub = js.subscribe("foo").unwrap();
+    js.publish("foo", b"hello js").unwrap();
+
+    let msg = sub.next().unwrap();
+    assert!(msg.ack_tracker.is_some());
+    msg.ack().unwrap();
+
+    // Messages of consumers which don't require acks are not tracked.
+    let sub = js
+        .subscribe_with_options("foo", &SubscribeOptions::new().ack_none())
+        .unwrap();
+    let msg = sub.next().unwrap();
+    assert!(msg.ack_tracker.is_none());
+}
+
+#[test]
+fn jetstream_subscribe_replay_original() {
+    let (_s, _nc, js) = run_basic_jetstream();
+
+    js.add_stream(&StreamConfig {
+        name: "TEST".to_string(),
+        subjects: vec!["foo".to_string()],
+        ..Default::default()
+    })
+    .unwrap();
+
+    let sub = js
+        .subscribe_with_options("foo", &SubscribeOptions::new().replay_original())
+        .unwrap();
+    let info = sub.consumer_info().unwrap();
+    assert_eq!(info.config.replay_policy, ReplayPolicy::Original);
+
+    let info = js
+        .add_consumer(
+            "TEST",
+            ConsumerConfig {
+                durable_name: Some("instant".to_string()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    assert_eq!(info.config.replay_policy, ReplayPolicy::Instant);
+}
+
 #[test]
 fn jetstream_subscribe_durable() {
     let s = nats_server::run_server("tests/configs/jetstream.conf");
@@ -634,6 +1010,79 @@ fn jetstream_pull_subscribe_fetch() {
     assert_eq!(i, 20);
 }
 
+#[test]
+fn jetstream_pull_subscribe_caught_up() {
+    let (_s, _nc, js) = run_basic_jetstream();
+
+    js.add_stream(&StreamConfig {
+        name: "TEST".to_string(),
+        subjects: vec!["foo".to_string()],
+        ..Default::default()
+    })
+    .unwrap();
+
+    for _ in 0..15 {
+        js.publish("foo", b"lorem").unwrap();
+    }
+
+    let consumer = js.pull_subscribe("foo").unwrap();
+    assert!(!consumer.is_caught_up());
+
+    for message in consumer.fetch(10).unwrap() {
+        message.ack().unwrap();
+    }
+    assert!(!consumer.is_caught_up());
+
+    let mut i = 0;
+    for message in consumer.fetch(10).unwrap() {
+        message.ack().unwrap();
+        i += 1;
+        if i == 5 {
+            break;
+        }
+    }
+    assert!(consumer.is_caught_up());
+}
+
+#[test]
+fn jetstream_subscribe_last_per_subject_caught_up() {
+    let (_s, _nc, js) = run_basic_jetstream();
+
+    js.add_stream(&StreamConfig {
+        name: "TEST".to_string(),
+        subjects: vec!["foo.*".to_string()],
+        ..Default::default()
+    })
+    .unwrap();
+
+    for key in ["a", "b", "c", "a", "b"] {
+        js.publish(&format!("foo.{key}"), key).unwrap();
+    }
+
+    let sub = js
+        .subscribe_with_options("foo.*", &SubscribeOptions::new().deliver_last_per_subject())
+        .unwrap();
+    assert!(!sub.is_caught_up());
+
+    let mut received = 0;
+    while !sub.is_caught_up() {
+        sub.next_timeout(Duration::from_secs(1))
+            .unwrap()
+            .ack()
+            .unwrap();
+        received += 1;
+    }
+    assert_eq!(received, 3);
+
+    // Live messages published afterwards keep it caught up.
+    js.publish("foo.d", "d").unwrap();
+    sub.next_timeout(Duration::from_secs(1))
+        .unwrap()
+        .ack()
+        .unwrap();
+    assert!(sub.is_caught_up());
+}
+
 #[test]
 fn jetstream_pull_subscribe_timeout_fetch() {
     let s = nats_server::run_server("tests/configs/jetstream.conf");
@@ -692,6 +1141,143 @@ fn jetstream_pull_subscribe_timeout_fetch() {
     }
 }
 
+#[test]
+fn jetstream_pull_subscribe_fetch_no_wait() {
+    let s = nats_server::run_server("tests/configs/jetstream.conf");
+    let nc = nats::connect(s.client_url()).unwrap();
+    let js = nats::jetstream::new(nc);
+
+    js.add_stream(&StreamConfig {
+        name: "TEST".to_string(),
+        subjects: vec!["foo".to_string()],
+        ..Default::default()
+    })
+    .unwrap();
+
+    let consumer = js
+        .pull_subscribe_with_options(
+            "foo",
+            &PullSubscribeOptions::new().durable_name("CONSUMER".to_string()),
+        )
+        .unwrap();
+
+    // Nothing pending yet, returns right away.
+    let start = Instant::now();
+    assert!(consumer.fetch_no_wait(10).unwrap().is_empty());
+    assert!(start.elapsed() < Duration::from_secs(1));
+
+    for _ in 0..15 {
+        js.publish("foo", b"lorem").unwrap();
+    }
+
+    let messages = consumer.fetch_no_wait(10).unwrap();
+    assert_eq!(messages.len(), 10);
+    for message in messages {
+        message.ack().unwrap();
+    }
+
+    // Only part of a batch is left.
+    let messages = consumer.fetch_no_wait(10).unwrap();
+    assert_eq!(messages.len(), 5);
+    for message in messages {
+        message.ack().unwrap();
+    }
+
+    assert!(consumer.fetch_no_wait(10).unwrap().is_empty());
+}
+
+#[test]
+fn jetstream_pull_subscribe_fetch_disconnected() {
+    let s = nats_server::run_server("tests/configs/jetstream.conf");
+    let nc = nats::Options::new()
+        .max_reconnects(0)
+        .connect(s.client_url())
+        .unwrap();
+    let js = nats::jetstream::new(nc);
+
+    js.add_stream(&StreamConfig {
+        name: "TEST".to_string(),
+        subjects: vec!["foo".to_string()],
+        ..Default::default()
+    })
+    .unwrap();
+
+    let consumer = js
+        .pull_subscribe_with_options(
+            "foo",
+            &PullSubscribeOptions::new().durable_name("CONSUMER".to_string()),
+        )
+        .unwrap();
+
+    for _ in 0..5 {
+        js.publish("foo", b"lorem").unwrap();
+    }
+
+    // The server goes away while the fetch waits for the rest of the batch.
+    let shutdown = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(500));
+        drop(s);
+    });
+
+    let start = Instant::now();
+    let err = consumer
+        .fetch_messages(10, Duration::from_secs(10))
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::ConnectionReset);
+    assert!(start.elapsed() < Duration::from_secs(5));
+    match *err
+        .into_inner()
+        .unwrap()
+        .downcast::<pull_subscription::FetchError>()
+        .unwrap()
+    {
+        pull_subscription::FetchError::Disconnected { received } => {
+            assert_eq!(received.len(), 5)
+        }
+        other => panic!("unexpected fetch error: {}", other),
+    }
+    shutdown.join().unwrap();
+}
+
+#[test]
+fn jetstream_pull_subscribe_fetch_iter_disconnected() {
+    let s = nats_server::run_server("tests/configs/jetstream.conf");
+    let nc = nats::Options::new()
+        .max_reconnects(0)
+        .connect(s.client_url())
+        .unwrap();
+    let js = nats::jetstream::new(nc);
+
+    js.add_stream(&StreamConfig {
+        name: "TEST".to_string(),
+        subjects: vec!["foo".to_string()],
+        ..Default::default()
+    })
+    .unwrap();
+
+    let consumer = js
+        .pull_subscribe_with_options(
+            "foo",
+            &PullSubscribeOptions::new().durable_name("CONSUMER".to_string()),
+        )
+        .unwrap();
+
+    for _ in 0..5 {
+        js.publish("foo", b"lorem").unwrap();
+    }
+
+    let shutdown = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(500));
+        drop(s);
+    });
+
+    // The iterator ends after the messages received before the disconnect.
+    let start = Instant::now();
+    assert_eq!(consumer.fetch(10).unwrap().count(), 5);
+    assert!(start.elapsed() < Duration::from_secs(5));
+    shutdown.join().unwrap();
+}
+
 #[test]
 fn jetstream_pull_subscribe_fetch_with_handler() {
     let s = nats_server::run_server("tests/configs/jetstream.conf");
@@ -744,6 +1330,59 @@ fn jetstream_pull_subscribe_fetch_with_handler() {
     assert_eq!(10, i);
 }
 
+#[test]
+fn jetstream_inactive_threshold() {
+    let s = nats_server::run_server("tests/configs/jetstream.conf");
+    let nc = nats::connect(s.client_url()).unwrap();
+    let js = nats::jetstream::new(nc);
+
+    js.add_stream(&StreamConfig {
+        name: "TEST".to_string(),
+        subjects: vec!["foo".to_string(), "bar".to_string()],
+        ..Default::default()
+    })
+    .unwrap();
+
+    // Ephemeral push consumers are left to the server default.
+    let sub = js.subscribe("foo").unwrap();
+    let info = sub.consumer_info().unwrap();
+    assert_eq!(info.config.inactive_threshold, Duration::from_secs(5));
+
+    let sub = js
+        .subscribe_with_options(
+            "bar",
+            &SubscribeOptions::new().inactive_threshold(Duration::from_secs(30)),
+        )
+        .unwrap();
+    let info = sub.consumer_info().unwrap();
+    assert_eq!(info.config.inactive_threshold, Duration::from_secs(30));
+
+    let info = js
+        .add_consumer(
+            "TEST",
+            ConsumerConfig {
+                deliver_subject: Some("deliver".to_string()),
+                ..Default::default()
+            }
+            .inactive_threshold(Duration::from_secs(10)),
+        )
+        .unwrap();
+    assert_eq!(info.config.inactive_threshold, Duration::from_secs(10));
+
+    let err = js
+        .add_consumer(
+            "TEST",
+            ConsumerConfig {
+                deliver_subject: Some("deliver".to_string()),
+                idle_heartbeat: Duration::from_secs(10),
+                ..Default::default()
+            }
+            .inactive_threshold(Duration::from_secs(5)),
+        )
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+}
+
 #[test]
 fn jetstream_pull_subscribe_ephemeral() {
     let s = nats_server::run_server("tests/configs/jetstream.conf");
@@ -809,6 +1448,171 @@ fn jetstream_consumer_configs_sample_frequency() {
     assert_eq!(80, consumer.config.sample_frequency);
 }
 
+#[test]
+fn jetstream_update_stream_immutable_fields() {
+    let s = nats_server::run_server("tests/configs/jetstream.conf");
+    let nc = nats::connect(s.client_url()).unwrap();
+    let js = nats::jetstream::new(nc);
+
+    let original = StreamConfig {
+        name: "IMMUTABLE".to_string(),
+        subjects: vec!["immutable".to_string()],
+        deny_delete: true,
+        deny_purge: true,
+        ..Default::default()
+    };
+    js.add_stream(&original).unwrap();
+
+    type Change = fn(&mut StreamConfig);
+    let changes: Vec<(&str, Change)> = vec![
+        ("storage", |c| c.storage = StorageType::Memory),
+        ("retention", |c| c.retention = RetentionPolicy::WorkQueue),
+        ("max_consumers", |c| c.max_consumers = 5),
+        ("template_owner", |c| c.template_owner = "other".to_string()),
+        ("deny_delete", |c| c.deny_delete = false),
+        ("deny_purge", |c| c.deny_purge = false),
+    ];
+
+    for (field, change) in changes {
+        let mut config = original.clone();
+        change(&mut config);
+
+        let err = js.update_stream(&config).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        let err = err
+            .into_inner()
+            .expect("should be able to convert error into inner")
+            .downcast::<jetstream::StreamConfigError>()
+            .expect("should be able to downcast into error");
+        assert_eq!(*err, jetstream::StreamConfigError::ImmutableField(field));
+    }
+
+    // Mutable fields still go through.
+    let mut config = original.clone();
+    config.max_msgs = 10;
+    let info = js.update_stream(&config).unwrap();
+    assert_eq!(info.config.max_msgs, 10);
+
+    // Once sealed, a stream can't be unsealed.
+    config.sealed = true;
+    js.update_stream(&config).unwrap();
+    config.sealed = false;
+    let err = js.update_stream(&config).unwrap_err();
+    let err = err
+        .into_inner()
+        .unwrap()
+        .downcast::<jetstream::StreamConfigError>()
+        .unwrap();
+    assert_eq!(*err, jetstream::StreamConfigError::ImmutableField("sealed"));
+}
+
+#[test]
+fn jetstream_bind_consumer() {
+    let s = nats_server::run_server("tests/configs/jetstream.conf");
+    let nc = nats::connect(s.client_url()).unwrap();
+    let js = nats::jetstream::new(nc);
+
+    js.add_stream(&StreamConfig {
+        name: "BIND".to_string(),
+        subjects: vec!["bind".to_string()],
+        ..Default::default()
+    })
+    .unwrap();
+    js.add_consumer(
+        "BIND",
+        ConsumerConfig {
+            durable_name: Some("push".to_string()),
+            deliver_subject: Some("bind.deliver".to_string()),
+            ack_policy: AckPolicy::Explicit,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    js.add_consumer(
+        "BIND",
+        ConsumerConfig {
+            durable_name: Some("pull".to_string()),
+            ack_policy: AckPolicy::Explicit,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    js.publish("bind", "data").unwrap();
+
+    let sub = js.bind_consumer("BIND", "push").unwrap();
+    let msg = sub.next_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(msg.data, b"data");
+    msg.ack().unwrap();
+
+    // Binding doesn't touch the consumer.
+    let info = js.consumer_info("BIND", "push").unwrap();
+    assert_eq!(info.config.deliver_subject.as_deref(), Some("bind.deliver"));
+    assert_eq!(js.list_consumers("BIND").unwrap().count(), 2);
+
+    let err = js.bind_consumer("BIND", "pull").unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+
+    let err = js.bind_consumer("BIND", "missing").unwrap_err();
+    let err = err
+        .into_inner()
+        .unwrap()
+        .downcast::<jetstream::Error>()
+        .unwrap();
+    assert_eq!(err.error_code(), jetstream::ErrorCode::ConsumerNotFound);
+}
+
+#[test]
+fn jetstream_bind_queue_consumer() {
+    let s = nats_server::run_server("tests/configs/jetstream.conf");
+    let nc = nats::connect(s.client_url()).unwrap();
+    let js = nats::jetstream::new(nc);
+
+    js.add_stream(&StreamConfig {
+        name: "jobs".to_string(),
+        ..Default::default()
+    })
+    .unwrap();
+    js.add_consumer(
+        "jobs",
+        ConsumerConfig {
+            durable_name: Some("workers".to_string()),
+            deliver_subject: Some("deliver.jobs".to_string()),
+            ack_policy: AckPolicy::Explicit,
+            ..Default::default()
+        }
+        .deliver_group("pool"),
+    )
+    .unwrap();
+
+    let err = js
+        .bind_queue_consumer("jobs", "workers", "other")
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::Other);
+    assert_eq!(
+        err.to_string(),
+        "cannot create a queue subscription other for a consumer with a deliver group pool"
+    );
+
+    let first = js.bind_queue_consumer("jobs", "workers", "pool").unwrap();
+    let second = js.bind_queue_consumer("jobs", "workers", "pool").unwrap();
+
+    for _ in 0..100 {
+        js.publish("jobs", b"job").unwrap();
+    }
+
+    let mut received = [0, 0];
+    for (count, subscription) in received.iter_mut().zip([&first, &second]) {
+        while let Ok(message) = subscription.next_timeout(Duration::from_millis(500)) {
+            message.ack().unwrap();
+            *count += 1;
+        }
+    }
+    assert_eq!(received[0] + received[1], 100);
+    assert!(received[0] > 0);
+    assert!(received[1] > 0);
+}
+
 // Helper function to return server and client.
 pub fn run_basic_jetstream() -> (nats_server::Server, Connection, JetStream) {
     let s = nats_server::run_server("tests/configs/jetstream.conf");
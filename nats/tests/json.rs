@@ -0,0 +1,82 @@
+// Copyright 2020-2022 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Order {
+    id: u64,
+    item: String,
+}
+
+#[test]
+fn publish_and_decode_json() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+    let sub = nc.subscribe("orders").unwrap();
+
+    let order = Order {
+        id: 1,
+        item: "coffee".to_string(),
+    };
+    nc.publish_json("orders", &order).unwrap();
+
+    let msg = sub.next_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(msg.json::<Order>().unwrap(), order);
+
+    // Payloads that don't match fail with their own error.
+    nc.publish("orders", "not json").unwrap();
+    let msg = sub.next_timeout(Duration::from_secs(1)).unwrap();
+    let err = msg.json::<Order>().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    assert!(err.into_inner().unwrap().is::<serde_json::Error>());
+}
+
+#[test]
+fn request_json() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+
+    let _sub = nc.subscribe("orders.total").unwrap().with_handler(|msg| {
+        let orders: Vec<Order> = msg.json()?;
+        msg.respond_json(&orders.len())
+    });
+
+    let orders = vec![
+        Order {
+            id: 1,
+            item: "coffee".to_string(),
+        },
+        Order {
+            id: 2,
+            item: "tea".to_string(),
+        },
+    ];
+    let total: usize = nc.request_json("orders.total", &orders).unwrap();
+    assert_eq!(total, 2);
+
+    // The response is a number, not an order.
+    let err = nc
+        .request_json::<_, Order>("orders.total", &orders)
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+    // Transport errors are left as they are.
+    let err = nc
+        .request_json::<_, usize>("nobody-home", &orders)
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::NotFound);
+}
@@ -74,6 +74,34 @@ fn key_value_entry() {
     assert_eq!(status.bucket(), "ENTRY");
     assert_eq!(status.max_age(), std::time::Duration::from_secs(3600));
     assert_eq!(status.values(), 7);
+    assert!(status.bytes() > 0);
+    assert_eq!(status.stream_info().config.name, "KV_ENTRY");
+}
+
+#[test]
+fn key_value_stores() {
+    let server = nats_server::run_server("tests/configs/jetstream.conf");
+    let client = nats::connect(server.client_url()).unwrap();
+    let context = nats::jetstream::new(client);
+
+    for bucket in ["FIRST", "SECOND"] {
+        context
+            .create_key_value(&Config {
+                bucket: bucket.to_string(),
+                ..Default::default()
+            })
+            .unwrap();
+    }
+    context
+        .add_stream(&StreamConfig {
+            name: "EVENTS".to_string(),
+            ..Default::default()
+        })
+        .unwrap();
+
+    let mut buckets = context.key_value_stores().unwrap();
+    buckets.sort();
+    assert_eq!(buckets, vec!["FIRST".to_string(), "SECOND".to_string()]);
 }
 
 #[test]
@@ -20,3 +20,23 @@ fn no_responders() {
     let nc = nats::connect(s.client_url()).expect("could not connect");
     nc.request("nobody-home", "hello").unwrap();
 }
+
+#[test]
+fn has_responders() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).expect("could not connect");
+    let timeout = std::time::Duration::from_secs(1);
+
+    assert!(!nc.has_responders("nobody-home", timeout).unwrap());
+
+    let _sub = nc
+        .subscribe("somebody-home")
+        .unwrap()
+        .with_handler(|msg| msg.respond("hello"));
+    assert!(nc.has_responders("somebody-home", timeout).unwrap());
+
+    // Subscribed, but never replying.
+    let _silent = nc.subscribe("busy").unwrap();
+    let err = nc.has_responders("busy", timeout).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::TimedOut);
+}
@@ -0,0 +1,83 @@
+// Copyright 2020-2022 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io;
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel as channel;
+use nats::OutboundPolicy;
+
+fn disconnected(policy: OutboundPolicy) -> (nats::Connection, u16) {
+    let s = nats_server::run_basic_server();
+    let port = s.client_port();
+    let (dtx, drx) = channel::bounded(1);
+    let nc = nats::Options::new()
+        .outbound_policy(policy)
+        .disconnect_callback(move || {
+            dtx.try_send(()).ok();
+        })
+        .connect(s.client_url())
+        .unwrap();
+
+    drop(s);
+    drx.recv_timeout(Duration::from_secs(5)).unwrap();
+    (nc, port)
+}
+
+#[test]
+fn outbound_policy_buffer() {
+    let (nc, port) = disconnected(OutboundPolicy::Buffer);
+    let sub = nc.subscribe("foo").unwrap();
+    nc.publish("foo", "buffered").unwrap();
+
+    // The buffered message is sent once the client reconnects.
+    let _s = nats_server::run_server_with_port("", Some(&port.to_string()));
+    let msg = sub.next_timeout(Duration::from_secs(10)).unwrap();
+    assert_eq!(msg.data, b"buffered");
+}
+
+#[test]
+fn outbound_policy_error_immediately() {
+    let (nc, _port) = disconnected(OutboundPolicy::ErrorImmediately);
+    let err = nc.publish("foo", "data").unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::NotConnected);
+    let err = nc
+        .try_publish_with_reply_or_headers("foo", None, None, "data")
+        .unwrap()
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::NotConnected);
+}
+
+#[test]
+fn outbound_policy_block_times_out() {
+    let (nc, _port) = disconnected(OutboundPolicy::Block(Duration::from_millis(200)));
+    let err = nc.publish("foo", "data").unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+    assert!(nc
+        .try_publish_with_reply_or_headers("foo", None, None, "data")
+        .is_none());
+}
+
+#[test]
+fn outbound_policy_block_waits_for_reconnect() {
+    let (nc, port) = disconnected(OutboundPolicy::Block(Duration::from_secs(10)));
+
+    let restart = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(200));
+        nats_server::run_server_with_port("", Some(&port.to_string()))
+    });
+
+    nc.publish("foo", "data").unwrap();
+    let _s = restart.join().unwrap();
+}
@@ -0,0 +1,161 @@
+// Copyright 2020-2022 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+
+use nats::header::CONTENT_TYPE;
+
+#[test]
+fn publish_builder_sends_headers_and_reply() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+    let sub = nc.subscribe("foo").unwrap();
+
+    nats::Message::builder()
+        .subject("foo")
+        .reply("bar")
+        .header("X-Key", "a")
+        .header("X-Key", "b")
+        .content_type("text/plain")
+        .payload("hello")
+        .send(&nc)
+        .unwrap();
+
+    let msg = sub.next_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(msg.data, b"hello");
+    assert_eq!(msg.reply.as_deref(), Some("bar"));
+    let headers = msg.headers.unwrap();
+    assert_eq!(headers.get("X-Key").unwrap().len(), 2);
+    assert!(headers.get(CONTENT_TYPE).unwrap().contains("text/plain"));
+
+    nats::Message::builder()
+        .subject("foo")
+        .payload("plain")
+        .send(&nc)
+        .unwrap();
+    let msg = sub.next_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(msg.data, b"plain");
+    assert!(msg.headers.is_none());
+    assert!(msg.reply.is_none());
+}
+
+#[test]
+fn publish_builder_build() {
+    let msg = nats::Message::builder()
+        .subject("foo")
+        .content_type("application/json")
+        .payload("{}")
+        .build();
+    assert_eq!(msg.subject, "foo");
+    assert_eq!(msg.data, b"{}");
+    assert!(msg.headers.unwrap().get(CONTENT_TYPE).is_some());
+}
+
+#[test]
+fn publish_builder_send_from_reader() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+    let sub = nc.subscribe("foo").unwrap();
+
+    // Larger than the client's write buffer, so it goes out in several chunks.
+    let payload: Vec<u8> = (0..100 * 1024).map(|i| i as u8).collect();
+    nats::Message::builder()
+        .subject("foo")
+        .reply("bar")
+        .content_type("application/octet-stream")
+        .send_from_reader(&nc, payload.as_slice(), payload.len())
+        .unwrap();
+
+    let msg = sub.next_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(msg.data, payload);
+    assert_eq!(msg.reply.as_deref(), Some("bar"));
+    assert!(msg.headers.unwrap().get(CONTENT_TYPE).is_some());
+
+    // Oversized messages are refused before anything is sent.
+    let max_payload = nc.max_payload();
+    let err = nats::Message::builder()
+        .subject("foo")
+        .send_from_reader(&nc, std::io::repeat(0), max_payload + 1)
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    assert_eq!(
+        *err.into_inner()
+            .unwrap()
+            .downcast::<nats::ValidationError>()
+            .unwrap(),
+        nats::ValidationError::PayloadTooLarge {
+            size: max_payload + 1,
+            max_payload,
+        }
+    );
+
+    // The connection is still usable afterwards.
+    nats::Message::builder()
+        .subject("foo")
+        .send_from_reader(&nc, &b"hello world"[..], 5)
+        .unwrap();
+    let msg = sub.next_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(msg.data, b"hello");
+}
+
+#[test]
+fn connection_publish_builder() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+    let sub = nc.subscribe("foo").unwrap();
+
+    nc.publish_builder("foo")
+        .reply("bar")
+        .header("X-Key", "a")
+        .payload("hello")
+        .publish()
+        .unwrap();
+    let msg = sub.next_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(msg.data, b"hello");
+    assert_eq!(msg.reply.as_deref(), Some("bar"));
+    assert!(msg.headers.unwrap().get("X-Key").is_some());
+
+    let payload: Vec<u8> = (0..100 * 1024).map(|i| i as u8).collect();
+    nc.publish_builder("foo")
+        .publish_from_reader(payload.as_slice(), payload.len())
+        .unwrap();
+    let msg = sub.next_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(msg.data, payload);
+
+    // Chunks are concatenated, including ones larger than the write buffer.
+    let chunks = vec![&b"hello"[..], b"", b" ", payload.as_slice()];
+    nc.publish_builder("foo")
+        .publish_chunks(chunks, payload.len() + 6)
+        .unwrap();
+    let msg = sub.next_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(&msg.data[..6], b"hello ");
+    assert_eq!(&msg.data[6..], payload.as_slice());
+
+    // Oversized messages are refused before anything is sent.
+    let max_payload = nc.max_payload();
+    let err = nc
+        .publish_builder("foo")
+        .publish_chunks(std::iter::repeat([0u8; 1024]), max_payload + 1)
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn unbound_publish_builder() {
+    let err = nats::Message::builder()
+        .subject("foo")
+        .payload("hello")
+        .publish()
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::NotConnected);
+}
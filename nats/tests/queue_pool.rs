@@ -0,0 +1,76 @@
+// Copyright 2020-2022 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use nats::DispatchStrategy;
+
+fn run_pool(strategy: DispatchStrategy) -> HashMap<String, usize> {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+
+    let seen = Arc::new(Mutex::new(HashMap::new()));
+    let handler = nc
+        .queue_subscribe_pool("jobs", "workers", 3, strategy, {
+            let seen = seen.clone();
+            move |_| {
+                thread::sleep(Duration::from_millis(5));
+                let name = thread::current().name().unwrap().to_string();
+                *seen.lock().unwrap().entry(name).or_insert(0) += 1;
+                Ok(())
+            }
+        })
+        .unwrap();
+
+    for _ in 0..30 {
+        nc.publish("jobs", "data").unwrap();
+    }
+    nc.flush().unwrap();
+    thread::sleep(Duration::from_millis(50));
+
+    handler.drain(Duration::from_secs(5)).unwrap();
+    let seen = seen.lock().unwrap().clone();
+    assert_eq!(seen.values().sum::<usize>(), 30);
+    seen
+}
+
+#[test]
+fn queue_subscribe_pool_round_robin() {
+    let seen = run_pool(DispatchStrategy::RoundRobin);
+    assert_eq!(seen.len(), 3);
+    assert!(seen.values().all(|&n| n == 10));
+}
+
+#[test]
+fn queue_subscribe_pool_least_busy() {
+    let seen = run_pool(DispatchStrategy::LeastBusy);
+    assert_eq!(seen.len(), 3);
+}
+
+#[test]
+fn queue_subscribe_pool_needs_workers() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+
+    let err = nc
+        .queue_subscribe_pool("jobs", "workers", 0, DispatchStrategy::RoundRobin, |_| {
+            Ok(())
+        })
+        .err()
+        .unwrap();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+}
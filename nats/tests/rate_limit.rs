@@ -0,0 +1,68 @@
+// Copyright 2020-2022 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::{Duration, Instant};
+
+#[test]
+fn rate_limited_subscription() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+    let sub = nc.subscribe("foo").unwrap().with_rate_limit(10);
+
+    for _ in 0..15 {
+        nc.publish("foo", "data").unwrap();
+    }
+    nc.flush().unwrap();
+
+    // The first second worth of messages comes in a burst.
+    let start = Instant::now();
+    for _ in 0..10 {
+        sub.next_timeout(Duration::from_secs(1)).unwrap();
+    }
+    assert!(start.elapsed() < Duration::from_millis(200));
+
+    // The rest is throttled, and stays queued meanwhile.
+    assert!(sub.try_next().is_none());
+    for _ in 0..5 {
+        sub.next().unwrap();
+    }
+    assert!(start.elapsed() >= Duration::from_millis(400));
+
+    // Removing the limit delivers without delay again.
+    let sub = sub.with_rate_limit(0);
+    for _ in 0..20 {
+        nc.publish("foo", "data").unwrap();
+    }
+    nc.flush().unwrap();
+    let start = Instant::now();
+    for _ in 0..20 {
+        sub.next_timeout(Duration::from_secs(1)).unwrap();
+    }
+    assert!(start.elapsed() < Duration::from_millis(200));
+}
+
+#[test]
+fn rate_limited_next_timeout() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+    let sub = nc.subscribe("foo").unwrap().with_rate_limit(1);
+
+    nc.publish("foo", "data").unwrap();
+    nc.publish("foo", "data").unwrap();
+    nc.flush().unwrap();
+
+    sub.next_timeout(Duration::from_secs(1)).unwrap();
+    let err = sub.next_timeout(Duration::from_millis(100)).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::TimedOut);
+    sub.next_timeout(Duration::from_secs(2)).unwrap();
+}
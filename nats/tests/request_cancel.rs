@@ -0,0 +1,90 @@
+// Copyright 2020-2022 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io;
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[test]
+fn request_handle_response() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+
+    let service = nc.subscribe("slow").unwrap();
+    let request = nc.request_handle("slow", "ping").unwrap();
+
+    // Still pending after a timeout.
+    let err = request
+        .wait_timeout(Duration::from_millis(100))
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+
+    service
+        .next_timeout(Duration::from_secs(1))
+        .unwrap()
+        .respond("pong")
+        .unwrap();
+    let response = request.wait_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(response.data, b"pong");
+}
+
+#[test]
+fn request_handle_cancel() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+
+    // A responder that never answers.
+    let service = nc.subscribe("stuck").unwrap();
+    let request = nc.request_handle("stuck", "ping").unwrap();
+    let reply = service
+        .next_timeout(Duration::from_secs(1))
+        .unwrap()
+        .reply
+        .unwrap();
+
+    let waiter = thread::spawn({
+        let request = request.clone();
+        move || request.wait()
+    });
+    thread::sleep(Duration::from_millis(100));
+
+    let start = Instant::now();
+    request.cancel().unwrap();
+    let err = waiter.join().unwrap().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::Interrupted);
+    assert!(start.elapsed() < Duration::from_secs(1));
+    assert!(request.is_cancelled());
+
+    // The inbox is gone, so a late reply goes nowhere.
+    let probe = nc.subscribe(&reply).unwrap();
+    nc.publish(&reply, "late").unwrap();
+    probe.next_timeout(Duration::from_secs(1)).unwrap();
+    let err = request
+        .wait_timeout(Duration::from_millis(100))
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::Interrupted);
+}
+
+#[test]
+fn request_handle_default_timeout() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::Options::new()
+        .with_request_timeout(Some(Duration::from_millis(200)))
+        .connect(s.client_url())
+        .unwrap();
+
+    let _service = nc.subscribe("stuck").unwrap();
+    let request = nc.request_handle("stuck", "ping").unwrap();
+    let err = request.wait().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+}
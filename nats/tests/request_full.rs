@@ -0,0 +1,60 @@
+// Copyright 2020-2022 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io;
+
+use nats::{HeaderMap, ServiceError};
+
+#[test]
+fn request_full_service_error() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+
+    let responder = nc.clone();
+    let _handler = nc.subscribe("products").unwrap().with_handler(move |msg| {
+        if msg.data == b"fail" {
+            let headers: HeaderMap = [
+                ("Nats-Service-Error", "not found"),
+                ("Nats-Service-Error-Code", "404"),
+            ]
+            .iter()
+            .collect();
+            responder.publish_with_reply_or_headers(
+                msg.reply.as_deref().unwrap(),
+                None,
+                Some(&headers),
+                "",
+            )
+        } else {
+            msg.respond(&msg.data)
+        }
+    });
+
+    assert_eq!(nc.request_full("products", "data").unwrap(), b"data");
+
+    let err = nc.request_full("products", "fail").unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::Other);
+    assert_eq!(
+        *err.into_inner()
+            .unwrap()
+            .downcast::<ServiceError>()
+            .unwrap(),
+        ServiceError {
+            code: 404,
+            description: "not found".to_string(),
+        }
+    );
+
+    let err = nc.request_full("missing", "data").unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::NotFound);
+}
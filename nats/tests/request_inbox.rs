@@ -0,0 +1,41 @@
+// Copyright 2020-2022 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io;
+use std::time::Duration;
+
+#[test]
+fn request_with_inbox() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+
+    let inbox = "_INBOX.tenant-a.request";
+    let responder = nc.subscribe("help").unwrap();
+    let handle = std::thread::spawn(move || {
+        let msg = responder.next_timeout(Duration::from_secs(2)).unwrap();
+        msg.respond(msg.reply.clone().unwrap()).unwrap();
+    });
+
+    let resp = nc
+        .request_with_inbox("help", inbox, "please", Duration::from_secs(2))
+        .unwrap();
+    assert_eq!(resp.data, inbox.as_bytes());
+    handle.join().unwrap();
+
+    for inbox in ["_INBOX.*", "_INBOX.>", "", "_INBOX with space"] {
+        let err = nc
+            .request_with_inbox("help", inbox, "please", Duration::from_secs(1))
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+}
@@ -0,0 +1,112 @@
+// Copyright 2020-2022 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use nats::RequestManyOptions;
+
+#[test]
+fn request_many_collects_all_responders() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+
+    let _responders: Vec<_> = (0..3)
+        .map(|i| {
+            nc.subscribe("workers")
+                .unwrap()
+                .with_handler(move |msg| msg.respond(format!("worker {i}")))
+        })
+        .collect();
+
+    let start = Instant::now();
+    let options = RequestManyOptions::new().timeout(Duration::from_millis(500));
+    let mut responses: Vec<_> = nc
+        .request_many("workers", "status", options)
+        .unwrap()
+        .map(|msg| String::from_utf8(msg.data).unwrap())
+        .collect();
+    responses.sort();
+    assert_eq!(responses, vec!["worker 0", "worker 1", "worker 2"]);
+    // Without other limits, collecting lasts until the timeout.
+    assert!(start.elapsed() >= Duration::from_millis(500));
+
+    let options = RequestManyOptions::new()
+        .timeout(Duration::from_secs(5))
+        .max_responses(2);
+    let start = Instant::now();
+    assert_eq!(
+        nc.request_many("workers", "status", options)
+            .unwrap()
+            .count(),
+        2
+    );
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn request_many_stall_timeout() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+
+    // Responds twice quickly, then once more after a pause.
+    let _responder = nc.subscribe("slow").unwrap().with_handler(|msg| {
+        msg.respond("first")?;
+        msg.respond("second")?;
+        thread::sleep(Duration::from_millis(500));
+        msg.respond("late")
+    });
+
+    let options = RequestManyOptions::new()
+        .timeout(Duration::from_secs(5))
+        .stall_timeout(Duration::from_millis(200));
+    let start = Instant::now();
+    let responses: Vec<_> = nc
+        .request_many("slow", "status", options)
+        .unwrap()
+        .map(|msg| msg.data)
+        .collect();
+    assert_eq!(responses, vec![b"first".to_vec(), b"second".to_vec()]);
+    assert!(start.elapsed() < Duration::from_millis(500));
+}
+
+#[test]
+fn request_many_sentinel_and_no_responders() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+
+    let _responder = nc.subscribe("stream").unwrap().with_handler(|msg| {
+        msg.respond("a")?;
+        msg.respond("b")?;
+        msg.respond("")?;
+        msg.respond("after the end")
+    });
+
+    let options = RequestManyOptions::new()
+        .timeout(Duration::from_secs(5))
+        .sentinel(true);
+    let start = Instant::now();
+    let responses: Vec<_> = nc
+        .request_many("stream", "", options)
+        .unwrap()
+        .map(|msg| msg.data)
+        .collect();
+    assert_eq!(responses, vec![b"a".to_vec(), b"b".to_vec()]);
+    assert!(start.elapsed() < Duration::from_secs(5));
+
+    let start = Instant::now();
+    let mut responses = nc.request_many("nobody-home", "", options).unwrap();
+    assert!(responses.next().is_none());
+    assert!(responses.next().is_none());
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
@@ -0,0 +1,80 @@
+// Copyright 2020-2022 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::ErrorKind;
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[test]
+fn default_request_timeout() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::Options::new()
+        .with_request_timeout(Some(Duration::from_millis(200)))
+        .connect(s.client_url())
+        .unwrap();
+
+    // Subscribed, so the server has a responder, but it never replies.
+    let _sub = nc.subscribe("silent").unwrap();
+
+    let start = Instant::now();
+    let err = nc.request("silent", "hello").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::TimedOut);
+    assert!(start.elapsed() < Duration::from_secs(1));
+
+    // An explicit timeout takes precedence over the default.
+    let err = nc
+        .request_timeout("silent", "hello", Duration::from_millis(50))
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::TimedOut);
+}
+
+#[test]
+fn request_retries_no_responders() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::Options::new()
+        .with_request_timeout(Some(Duration::from_secs(5)))
+        .with_request_retries(5)
+        .connect(s.client_url())
+        .unwrap();
+
+    let responder = nats::connect(s.client_url()).unwrap();
+    let handle = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(150));
+        let sub = responder.subscribe("late").unwrap();
+        responder.flush().unwrap();
+        let msg = sub.next().unwrap();
+        msg.respond("here").unwrap();
+        responder.flush().unwrap();
+    });
+
+    let resp = nc.request("late", "hello").unwrap();
+    assert_eq!(resp.data, b"here");
+    handle.join().unwrap();
+}
+
+#[test]
+fn request_retries_exhausted() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::Options::new()
+        .with_request_retries(2)
+        .connect(s.client_url())
+        .unwrap();
+
+    let start = Instant::now();
+    let err = nc
+        .request_timeout("nobody-home", "hello", Duration::from_secs(5))
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NotFound);
+    // Backs off between attempts instead of failing right away.
+    assert!(start.elapsed() >= Duration::from_millis(300));
+}
@@ -0,0 +1,103 @@
+// Copyright 2020-2022 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io;
+use std::time::Duration;
+
+use nats::header::{NATS_CHUNK_LAST, NATS_CHUNK_SEQUENCE};
+use nats::HeaderMap;
+
+#[test]
+fn request_streamed() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+
+    let report: Vec<u8> = (0..100_000).map(|i| (i % 251) as u8).collect();
+    let _handler = nc.subscribe("report").unwrap().with_handler({
+        let report = report.clone();
+        move |msg| match msg.data.as_slice() {
+            b"empty" => msg.respond_chunked(b"", 1024),
+            _ => msg.respond_chunked(&report, 10_000),
+        }
+    });
+
+    let response = nc
+        .request_streamed("report", "full", Some(Duration::from_secs(5)))
+        .unwrap();
+    assert_eq!(response, report);
+
+    let response = nc
+        .request_streamed("report", "empty", Some(Duration::from_secs(5)))
+        .unwrap();
+    assert!(response.is_empty());
+
+    let err = nc
+        .request_streamed("nobody", "", Some(Duration::from_secs(1)))
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::NotFound);
+}
+
+#[test]
+fn request_streamed_broken_responses() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+
+    let _handler = nc.subscribe("broken").unwrap().with_handler({
+        let nc = nc.clone();
+        move |msg| {
+            let reply = msg.reply.clone().unwrap();
+            let chunk = |sequence: &str, last: bool| {
+                let mut headers = HeaderMap::new();
+                headers.insert(NATS_CHUNK_SEQUENCE, sequence.to_string());
+                if last {
+                    headers.insert(NATS_CHUNK_LAST, "true".to_string());
+                }
+                nc.publish_with_reply_or_headers(&reply, None, Some(&headers), "data")
+            };
+            match msg.data.as_slice() {
+                b"gap" => {
+                    chunk("0", false)?;
+                    chunk("2", true)
+                }
+                b"unnumbered" => msg.respond("data"),
+                _ => chunk("0", false),
+            }
+        }
+    });
+
+    for request in ["gap", "unnumbered"] {
+        let err = nc
+            .request_streamed("broken", request, Some(Duration::from_secs(1)))
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    // The final chunk never arrives.
+    let err = nc
+        .request_streamed("broken", "unfinished", Some(Duration::from_millis(500)))
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+}
+
+#[test]
+fn request_streamed_default_timeout() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::Options::new()
+        .with_request_timeout(Some(Duration::from_millis(200)))
+        .connect(s.client_url())
+        .unwrap();
+
+    let _service = nc.subscribe("stuck").unwrap();
+    let err = nc.request_streamed("stuck", "", None).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+}
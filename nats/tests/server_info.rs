@@ -0,0 +1,25 @@
+// Copyright 2020-2022 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#[test]
+fn server_info_available_after_connect() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+
+    let info = nc.server_info();
+    assert!(!info.server_id.is_empty());
+    assert!(info.headers);
+    assert!(info.max_payload > 0);
+    assert_eq!(info.max_payload, nc.max_payload());
+    assert_eq!(info.client_id, nc.client_id());
+}
@@ -0,0 +1,49 @@
+// Copyright 2020-2022 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+
+#[test]
+fn message_size_stats() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::connect(s.client_url()).unwrap();
+
+    let foo = nc.subscribe("foo").unwrap();
+    let bar = nc.subscribe("bar").unwrap();
+
+    nc.publish("foo", [0; 10]).unwrap();
+    nc.publish("foo", [0; 30]).unwrap();
+    nc.publish("bar", [0; 100]).unwrap();
+
+    for _ in 0..2 {
+        foo.next_timeout(Duration::from_secs(1)).unwrap();
+    }
+    bar.next_timeout(Duration::from_secs(1)).unwrap();
+
+    let stats = foo.size_stats().unwrap();
+    assert_eq!(stats.count, 2);
+    assert_eq!(stats.total_bytes, 40);
+    assert_eq!(stats.max, 30);
+    assert_eq!(stats.average(), Some(20));
+
+    let stats = nc.size_stats();
+    assert_eq!(stats.count, 3);
+    assert_eq!(stats.max, 100);
+
+    foo.reset_size_stats();
+    assert_eq!(foo.size_stats().unwrap().average(), None);
+    assert_eq!(bar.size_stats().unwrap().count, 1);
+
+    nc.reset_size_stats();
+    assert_eq!(nc.size_stats().count, 0);
+}
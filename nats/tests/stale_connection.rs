@@ -0,0 +1,77 @@
+// Copyright 2020-2022 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crossbeam_channel as channel;
+use nats::testing::{PongAction, PongEvent};
+
+#[test]
+fn dropped_pongs_disconnect() {
+    let s = nats_server::run_basic_server();
+
+    let drop_pongs = Arc::new(AtomicBool::new(true));
+    let dropped = Arc::new(AtomicUsize::new(0));
+    let (dtx, drx) = channel::unbounded();
+    let (rtx, rrx) = channel::unbounded();
+
+    let nc = nats::Options::new()
+        .with_ping_interval(Duration::from_millis(100))
+        .with_pong_hook({
+            let drop_pongs = drop_pongs.clone();
+            let dropped = dropped.clone();
+            move |event| {
+                if event == PongEvent::Received && drop_pongs.load(Ordering::SeqCst) {
+                    dropped.fetch_add(1, Ordering::SeqCst);
+                    PongAction::Drop
+                } else {
+                    PongAction::Continue
+                }
+            }
+        })
+        .disconnect_callback(move || dtx.send(()).unwrap())
+        .reconnect_callback(move || rtx.send(()).unwrap())
+        .connect(s.client_url())
+        .unwrap();
+
+    // Two unanswered PINGs make the client give up on the connection.
+    drx.recv_timeout(Duration::from_secs(5))
+        .expect("should disconnect after max pings out");
+    assert!(dropped.load(Ordering::SeqCst) >= 2);
+
+    drop_pongs.store(false, Ordering::SeqCst);
+    rrx.recv_timeout(Duration::from_secs(5))
+        .expect("should reconnect");
+    nc.flush().unwrap();
+}
+
+#[test]
+fn delayed_pongs_keep_connection() {
+    let s = nats_server::run_basic_server();
+    let (dtx, drx) = channel::unbounded();
+
+    let nc = nats::Options::new()
+        .with_ping_interval(Duration::from_millis(100))
+        .with_pong_hook(|event| match event {
+            PongEvent::Received => PongAction::Delay(Duration::from_millis(50)),
+            PongEvent::Sent => PongAction::Continue,
+        })
+        .disconnect_callback(move || dtx.send(()).unwrap())
+        .connect(s.client_url())
+        .unwrap();
+
+    nc.flush().unwrap();
+    assert!(drx.recv_timeout(Duration::from_secs(1)).is_err());
+}
@@ -0,0 +1,103 @@
+// Copyright 2020-2022 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+
+#[test]
+fn local_subscription_mux() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::Options::new()
+        .with_local_subscription_mux(true)
+        .connect(s.client_url())
+        .unwrap();
+
+    let first = nc.subscribe("foo").unwrap();
+    let second = nc.subscribe("foo").unwrap();
+    let other = nc.subscribe("bar").unwrap();
+
+    for i in 0..3 {
+        nc.publish("foo", format!("{}", i)).unwrap();
+    }
+    nc.flush().unwrap();
+
+    for sub in [&first, &second] {
+        for i in 0..3 {
+            let msg = sub.next_timeout(Duration::from_secs(1)).unwrap();
+            assert_eq!(msg.data, format!("{}", i).as_bytes());
+        }
+    }
+    assert!(other.try_next().is_none());
+
+    // The remaining subscriber keeps the server subscription alive.
+    first.unsubscribe().unwrap();
+    nc.publish("foo", "after").unwrap();
+    let msg = second.next_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(msg.data, b"after");
+
+    // Once everyone is gone a new subscriber starts from scratch.
+    second.unsubscribe().unwrap();
+    let third = nc.subscribe("foo").unwrap();
+    nc.publish("foo", "again").unwrap();
+    let msg = third.next_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(msg.data, b"again");
+    assert!(third.next_timeout(Duration::from_millis(100)).is_err());
+}
+
+#[test]
+fn local_subscription_mux_slow_consumer() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::Options::new()
+        .with_local_subscription_mux(true)
+        .connect(s.client_url())
+        .unwrap();
+
+    let slow = nc.subscribe("foo").unwrap();
+    let fast = nc.subscribe("foo").unwrap();
+    slow.set_message_limits(1);
+
+    for _ in 0..5 {
+        nc.publish("foo", "hello").unwrap();
+    }
+    nc.flush().unwrap();
+
+    for _ in 0..5 {
+        fast.next_timeout(Duration::from_secs(1)).unwrap();
+    }
+    assert_eq!(slow.dropped_messages().unwrap(), 4);
+    assert_eq!(fast.dropped_messages().unwrap(), 0);
+}
+
+#[test]
+fn local_subscription_mux_queue_group() {
+    let s = nats_server::run_basic_server();
+    let nc = nats::Options::new()
+        .with_local_subscription_mux(true)
+        .connect(s.client_url())
+        .unwrap();
+
+    let first = nc.queue_subscribe("foo", "workers").unwrap();
+    let second = nc.queue_subscribe("foo", "workers").unwrap();
+
+    for i in 0..4 {
+        nc.publish("foo", format!("{}", i)).unwrap();
+    }
+    nc.flush().unwrap();
+
+    // Every message goes to a single member of the queue group.
+    for sub in [&first, &second] {
+        for _ in 0..2 {
+            sub.next_timeout(Duration::from_secs(1)).unwrap();
+        }
+        assert!(sub.try_next().is_none());
+    }
+}
@@ -0,0 +1,53 @@
+// Copyright 2020-2022 The NATS Authors
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+
+use nats::system::{SystemEvent, SystemEventKind};
+
+#[test]
+fn subscribe_sys() {
+    let s = nats_server::run_server("tests/configs/system_account.conf");
+    let sys = nats::Options::with_user_pass("sys", "sys")
+        .connect(s.client_url())
+        .unwrap();
+
+    let connects = sys.subscribe_sys(SystemEventKind::Connect).unwrap();
+    let disconnects = sys.subscribe_sys(SystemEventKind::Disconnect).unwrap();
+    sys.flush().unwrap();
+
+    let app = nats::Options::with_user_pass("app", "app")
+        .with_name("system-events-test")
+        .connect(s.client_url())
+        .unwrap();
+    app.publish("foo", "hello").unwrap();
+    app.flush().unwrap();
+
+    match connects.next_timeout(Duration::from_secs(5)).unwrap() {
+        SystemEvent::Connect(event) => {
+            assert_eq!(event.client.account, "APP");
+            assert_eq!(event.client.user.as_deref(), Some("app"));
+            assert_eq!(event.client.name.as_deref(), Some("system-events-test"));
+        }
+        other => panic!("unexpected event: {:?}", other),
+    }
+
+    app.close();
+    match disconnects.next_timeout(Duration::from_secs(5)).unwrap() {
+        SystemEvent::Disconnect(event) => {
+            assert_eq!(event.client.account, "APP");
+            assert_eq!(event.received.msgs, 1);
+        }
+        other => panic!("unexpected event: {:?}", other),
+    }
+}